@@ -0,0 +1,8 @@
+-- Add down migration script here
+DROP TABLE IF EXISTS post_media;
+
+DROP TABLE IF EXISTS posts;
+
+DROP TABLE IF EXISTS media;
+
+DROP TABLE IF EXISTS users;
@@ -0,0 +1,49 @@
+-- Add up migration script here
+CREATE TABLE IF NOT EXISTS users (
+    id UUID PRIMARY KEY NOT NULL,
+    username VARCHAR(255) NOT NULL UNIQUE,
+    email VARCHAR(255) NOT NULL UNIQUE,
+    password_hash TEXT NOT NULL,
+    role TEXT NOT NULL DEFAULT 'User',
+    first_name VARCHAR(255),
+    last_name VARCHAR(255),
+    display_name VARCHAR(100),
+    bio TEXT,
+    website VARCHAR(255),
+    avatar_key VARCHAR(255),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ,
+    erased_at TIMESTAMPTZ
+);
+
+CREATE TABLE IF NOT EXISTS media (
+    id UUID PRIMARY KEY NOT NULL,
+    owner_id UUID NOT NULL REFERENCES users (id) ON DELETE CASCADE,
+    sha256 CHAR(64) NOT NULL,
+    mime_type VARCHAR(100) NOT NULL,
+    size_bytes BIGINT NOT NULL,
+    original_name VARCHAR(255),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    UNIQUE (owner_id, sha256)
+);
+
+CREATE INDEX IF NOT EXISTS idx_media_sha256 ON media (sha256);
+
+CREATE TABLE IF NOT EXISTS posts (
+    id UUID PRIMARY KEY NOT NULL,
+    user_id UUID NOT NULL REFERENCES users (id) ON DELETE RESTRICT ON UPDATE CASCADE,
+    title VARCHAR(255) NOT NULL,
+    content TEXT NOT NULL,
+    published BOOLEAN NOT NULL DEFAULT FALSE,
+    cover_media_id UUID REFERENCES media (id) ON DELETE SET NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ
+);
+
+CREATE TABLE IF NOT EXISTS post_media (
+    post_id UUID NOT NULL REFERENCES posts (id) ON DELETE CASCADE,
+    media_id UUID NOT NULL REFERENCES media (id) ON DELETE RESTRICT,
+    position INTEGER NOT NULL DEFAULT 0,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (post_id, media_id)
+);
@@ -0,0 +1,6 @@
+-- Add up migration script here
+ALTER TABLE users ADD COLUMN display_name VARCHAR(100);
+
+ALTER TABLE users ADD COLUMN bio TEXT;
+
+ALTER TABLE users ADD COLUMN website VARCHAR(255);
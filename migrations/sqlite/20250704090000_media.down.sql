@@ -0,0 +1,6 @@
+-- Add down migration script here
+ALTER TABLE posts DROP COLUMN cover_media_id;
+
+DROP TABLE IF EXISTS post_media;
+
+DROP TABLE IF EXISTS media;
@@ -0,0 +1,13 @@
+-- Add up migration script here
+-- No foreign keys: the entries outlive the accounts they mention.
+CREATE TABLE IF NOT EXISTS audit_log (
+    id TEXT PRIMARY KEY NOT NULL,
+    action VARCHAR(50) NOT NULL,
+    actor_id TEXT NOT NULL,
+    subject_id TEXT,
+    session_id TEXT,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS idx_audit_log_created_at ON audit_log (created_at);
+CREATE INDEX IF NOT EXISTS idx_audit_log_session_id ON audit_log (session_id);
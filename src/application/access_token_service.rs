@@ -0,0 +1,123 @@
+use chrono::{DateTime, Duration, Utc};
+use rand::{Rng, SeedableRng, distributions::Alphanumeric};
+use rand_chacha::ChaCha20Rng;
+use sha2::{Digest, Sha256};
+use uuid::Uuid;
+
+use crate::domain::{
+    error::DomainError,
+    model::access_token::{AccessToken, Scope},
+    repository::AccessTokenRepository,
+};
+
+/// Start of every personal access token, which tells them apart from JWTs
+/// and makes them easy to spot by secret scanners.
+pub const TOKEN_PREFIX: &str = "blog_pat_";
+
+/// Random characters following `TOKEN_PREFIX`.
+const SECRET_LEN: usize = 40;
+
+/// Characters of the secret kept in `AccessToken::prefix`.
+const VISIBLE_LEN: usize = 8;
+
+/// `last_used_at` is only written again after this delay, so that a busy
+/// token does not cost a write per request.
+const LAST_USED_RESOLUTION: Duration = Duration::minutes(1);
+
+#[derive(Clone)]
+pub struct AccessTokenService<R> {
+    repo: R,
+}
+
+impl<R> AccessTokenService<R>
+where
+    R: AccessTokenRepository + Send + Sync,
+{
+    pub fn new(repo: R) -> Self {
+        Self { repo }
+    }
+
+    /// Returns the stored token along with its secret, which cannot be
+    /// retrieved afterwards.
+    #[tracing::instrument(name = "AccessTokenService::create", skip_all, fields(%user_id))]
+    pub async fn create(
+        &self,
+        user_id: Uuid,
+        name: String,
+        scopes: Vec<Scope>,
+        expires_at: DateTime<Utc>,
+    ) -> Result<(AccessToken, String), DomainError> {
+        let secret: String = ChaCha20Rng::from_entropy()
+            .sample_iter(&Alphanumeric)
+            .take(SECRET_LEN)
+            .map(char::from)
+            .collect();
+        let token = format!("{}{}", TOKEN_PREFIX, secret);
+
+        let scopes = scopes.into_iter().fold(Vec::new(), |mut unique, scope| {
+            if !unique.contains(&scope) {
+                unique.push(scope);
+            }
+            unique
+        });
+
+        let access_token = AccessToken {
+            id: Uuid::new_v4(),
+            user_id,
+            name,
+            prefix: token[..TOKEN_PREFIX.len() + VISIBLE_LEN].to_string(),
+            token_hash: hash_token(&token),
+            scopes,
+            created_at: Utc::now(),
+            expires_at,
+            last_used_at: None,
+            revoked_at: None,
+        };
+        let access_token = self.repo.create(access_token).await?;
+
+        Ok((access_token, token))
+    }
+
+    #[tracing::instrument(name = "AccessTokenService::list", skip_all, fields(%user_id))]
+    pub async fn list(&self, user_id: Uuid) -> Result<Vec<AccessToken>, DomainError> {
+        self.repo.list_by_user(user_id).await
+    }
+
+    #[tracing::instrument(name = "AccessTokenService::revoke", skip_all, fields(%id))]
+    pub async fn revoke(&self, id: Uuid, user_id: Uuid) -> Result<AccessToken, DomainError> {
+        self.repo.revoke(id, user_id, Utc::now()).await
+    }
+
+    /// The unexpired, unrevoked token whose secret is `token`, if any. Its
+    /// `last_used_at` is updated on the way.
+    #[tracing::instrument(name = "AccessTokenService::authenticate", skip_all)]
+    pub async fn authenticate(&self, token: &str) -> Result<Option<AccessToken>, DomainError> {
+        let now = Utc::now();
+        let Some(access_token) = self.repo.find_by_hash(&hash_token(token)).await? else {
+            return Ok(None);
+        };
+        if !access_token.is_active(now) {
+            return Ok(None);
+        }
+
+        if access_token
+            .last_used_at
+            .is_none_or(|used| now - used >= LAST_USED_RESOLUTION)
+            && let Err(e) = self.repo.touch(access_token.id, now).await
+        {
+            log::warn!(
+                "Failed to record the use of access token {}: {}",
+                access_token.id,
+                e
+            );
+        }
+
+        Ok(Some(access_token))
+    }
+}
+
+/// The secret has enough entropy for a fast digest; it is what gets stored
+/// and looked up.
+fn hash_token(token: &str) -> String {
+    hex::encode(Sha256::digest(token.as_bytes()))
+}
@@ -0,0 +1,98 @@
+use std::sync::Arc;
+
+use chrono::Utc;
+use uuid::Uuid;
+
+use crate::{
+    domain::{
+        error::DomainError,
+        model::user::{AVATAR_SIZES, User},
+        repository::UserRepository,
+    },
+    infrastructure::{imaging::square_thumbnails, storage::BlobStore},
+};
+
+#[derive(Clone)]
+pub struct AvatarService<UR> {
+    user_repo: UR,
+    store: Arc<dyn BlobStore>,
+    max_bytes: usize,
+}
+
+impl<UR> AvatarService<UR>
+where
+    UR: UserRepository + Send + Sync,
+{
+    pub fn new(user_repo: UR, store: Arc<dyn BlobStore>, max_bytes: usize) -> Self {
+        Self {
+            user_repo,
+            store,
+            max_bytes,
+        }
+    }
+
+    pub fn max_bytes(&self) -> usize {
+        self.max_bytes
+    }
+
+    pub async fn upload(&self, user_id: Uuid, bytes: Vec<u8>) -> Result<User, DomainError> {
+        if bytes.len() > self.max_bytes {
+            return Err(DomainError::FileTooLarge(self.max_bytes));
+        }
+
+        let mut user = self
+            .user_repo
+            .find_by_id(user_id)
+            .await?
+            .ok_or(DomainError::NotFound)?;
+
+        let thumbnails =
+            tokio::task::spawn_blocking(move || square_thumbnails(&bytes, &AVATAR_SIZES))
+                .await
+                .map_err(|_| DomainError::InternalError)??;
+
+        // A fresh prefix per upload keeps URLs cacheable forever.
+        let key = format!("avatars/{}/{}", user.id.simple(), Uuid::new_v4().simple());
+        for (size, png) in thumbnails {
+            self.store
+                .put(&format!("{}/{}.png", key, size), png, "image/png")
+                .await
+                .map_err(|e| DomainError::StorageError(e.to_string()))?;
+        }
+
+        let previous = user.avatar_key.replace(key);
+        user.updated_at = Some(Utc::now());
+        let user = self.user_repo.update(user).await?;
+
+        if let Some(previous) = previous {
+            self.remove_files(&previous).await;
+        }
+
+        Ok(user)
+    }
+
+    pub async fn remove(&self, user_id: Uuid) -> Result<User, DomainError> {
+        let mut user = self
+            .user_repo
+            .find_by_id(user_id)
+            .await?
+            .ok_or(DomainError::NotFound)?;
+
+        let Some(previous) = user.avatar_key.take() else {
+            return Ok(user);
+        };
+
+        user.updated_at = Some(Utc::now());
+        let user = self.user_repo.update(user).await?;
+
+        self.remove_files(&previous).await;
+
+        Ok(user)
+    }
+
+    async fn remove_files(&self, key: &str) {
+        if let Err(e) = self.store.delete_prefix(key).await {
+            log::warn!("Failed to delete avatar files under {}: {}", key, e);
+        }
+    }
+}
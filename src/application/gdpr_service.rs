@@ -0,0 +1,137 @@
+use std::sync::Arc;
+
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+use crate::{
+    domain::{
+        error::DomainError,
+        model::{media::Media, post::Post, user::User},
+        repository::{MediaRepository, PostRepository, UserRepository},
+    },
+    infrastructure::storage::{BlobLocks, BlobStore},
+};
+
+pub struct UserDataExport {
+    pub user: User,
+    pub posts: Vec<Post>,
+    pub exported_at: DateTime<Utc>,
+}
+
+#[derive(Clone)]
+pub struct GdprService<R, UR, MR> {
+    post_repo: R,
+    user_repo: UR,
+    media_repo: MR,
+    store: Arc<dyn BlobStore>,
+    locks: BlobLocks,
+}
+
+impl<R, UR, MR> GdprService<R, UR, MR>
+where
+    R: PostRepository + Send + Sync,
+    UR: UserRepository + Send + Sync,
+    MR: MediaRepository + Send + Sync,
+{
+    pub fn new(
+        post_repo: R,
+        user_repo: UR,
+        media_repo: MR,
+        store: Arc<dyn BlobStore>,
+        locks: BlobLocks,
+    ) -> Self {
+        Self {
+            post_repo,
+            user_repo,
+            media_repo,
+            store,
+            locks,
+        }
+    }
+
+    pub async fn export(&self, user_id: Uuid) -> Result<UserDataExport, DomainError> {
+        let user = self
+            .user_repo
+            .find_by_id(user_id)
+            .await?
+            .ok_or(DomainError::NotFound)?;
+
+        let posts = self.post_repo.list_by_author(user_id).await?;
+
+        Ok(UserDataExport {
+            user,
+            posts,
+            exported_at: Utc::now(),
+        })
+    }
+
+    /// Anonymises the account in place: published posts stay online but are
+    /// attributed to a pseudonym, and the account can no longer log in. Its
+    /// identities, access tokens and media library are deleted.
+    pub async fn erase(&self, user_id: Uuid) -> Result<User, DomainError> {
+        let user = self
+            .user_repo
+            .find_by_id(user_id)
+            .await?
+            .ok_or(DomainError::NotFound)?;
+
+        if user.erased_at.is_some() {
+            return Ok(user);
+        }
+
+        let simple = user.id.simple().to_string();
+        let pseudonym = format!("deleted-user-{}", &simple[..12]);
+        let email = format!("{}@erased.invalid", simple);
+        let media = self.media_repo.list_by_owner(user.id).await?;
+
+        let erased = self
+            .user_repo
+            .anonymize(user.id, &pseudonym, &email, Utc::now())
+            .await?;
+
+        self.remove_files(user.avatar_key.as_deref(), media).await?;
+
+        Ok(erased)
+    }
+
+    /// Deletes the account for good, with its media library, detached from
+    /// the posts, and the stored files.
+    pub async fn delete(&self, user_id: Uuid) -> Result<(), DomainError> {
+        let user = self
+            .user_repo
+            .find_by_id(user_id)
+            .await?
+            .ok_or(DomainError::NotFound)?;
+        let media = self.media_repo.list_by_owner(user.id).await?;
+
+        self.user_repo.delete(user.id).await?;
+
+        self.remove_files(user.avatar_key.as_deref(), media).await
+    }
+
+    /// Files of a user whose rows are gone. Media blobs are shared by
+    /// identical files of other users, and only go with the last of them.
+    async fn remove_files(
+        &self,
+        avatar_key: Option<&str>,
+        media: Vec<Media>,
+    ) -> Result<(), DomainError> {
+        if let Some(avatar_key) = avatar_key {
+            self.store
+                .delete_prefix(avatar_key)
+                .await
+                .map_err(|e| DomainError::StorageError(e.to_string()))?;
+        }
+        for media in media {
+            let _guard = self.locks.lock(&media.sha256).await;
+            if self.media_repo.count_by_hash(&media.sha256).await? == 0 {
+                self.store
+                    .delete(&media.storage_key())
+                    .await
+                    .map_err(|e| DomainError::StorageError(e.to_string()))?;
+            }
+        }
+
+        Ok(())
+    }
+}
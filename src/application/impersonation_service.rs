@@ -0,0 +1,154 @@
+use chrono::{DateTime, Duration, Utc};
+use jsonwebtoken::{Header, encode};
+use uuid::Uuid;
+
+use crate::{
+    domain::{
+        error::DomainError,
+        model::{
+            audit::{AuditAction, AuditEntry},
+            user::Role,
+        },
+        repository::{AuditRepository, UserRepository},
+    },
+    infrastructure::{
+        auth::{Actor, Claims},
+        security::keys::Keys,
+    },
+};
+
+/// Validity of an impersonation token, short since it skips the password of
+/// the user.
+pub const IMPERSONATION_LIFETIME: Duration = Duration::minutes(15);
+
+/// Lets administrators act as another user, recording each session in the
+/// audit log.
+#[derive(Clone)]
+pub struct ImpersonationService<U, A> {
+    users: U,
+    audit: A,
+    keys: Keys,
+}
+
+impl<U, A> ImpersonationService<U, A>
+where
+    U: UserRepository + Send + Sync,
+    A: AuditRepository + Send + Sync,
+{
+    pub fn new(users: U, audit: A, keys: Keys) -> Self {
+        Self { users, audit, keys }
+    }
+
+    /// Opens a session as `target_id`, returning its token and expiry. Other
+    /// administrators cannot be impersonated, so that a session never
+    /// carries more rights than the user it shows.
+    #[tracing::instrument(name = "ImpersonationService::start", skip_all, fields(%admin_id, %target_id))]
+    pub async fn start(
+        &self,
+        admin_id: Uuid,
+        target_id: Uuid,
+    ) -> Result<(String, DateTime<Utc>), DomainError> {
+        if admin_id == target_id {
+            return Err(DomainError::ImpersonationForbidden);
+        }
+        let target = self
+            .users
+            .find_by_id(target_id)
+            .await?
+            .filter(|user| user.erased_at.is_none())
+            .ok_or(DomainError::NotFound)?;
+        if target.role == Role::Admin {
+            return Err(DomainError::ImpersonationForbidden);
+        }
+
+        let now = Utc::now();
+        let session_id = Uuid::new_v4();
+        self.audit
+            .record(AuditEntry {
+                id: Uuid::new_v4(),
+                action: AuditAction::ImpersonationStarted,
+                actor_id: admin_id,
+                subject_id: Some(target.id),
+                session_id: Some(session_id),
+                created_at: now,
+            })
+            .await?;
+        log::info!(
+            "Admin {} started impersonating user {}",
+            admin_id,
+            target.id
+        );
+
+        let expires_at = now + IMPERSONATION_LIFETIME;
+        let claims = Claims {
+            sub: target.id.to_string(),
+            exp: expires_at.timestamp() as usize,
+            iat: now.timestamp() as usize,
+            role: target.role,
+            scopes: None,
+            act: Some(Actor {
+                sub: admin_id.to_string(),
+                sid: session_id,
+            }),
+        };
+        let token = encode(&Header::default(), &claims, &self.keys.encoding)
+            .map_err(|_| DomainError::InternalError)?;
+
+        Ok((token, expires_at))
+    }
+
+    /// Closes the session of an impersonation token, which is refused from
+    /// then on.
+    #[tracing::instrument(name = "ImpersonationService::end", skip_all)]
+    pub async fn end(&self, claims: &Claims) -> Result<(), DomainError> {
+        let actor = claims.act.as_ref().ok_or(DomainError::NotFound)?;
+        let admin_id = Uuid::parse_str(&actor.sub).map_err(|_| DomainError::InvalidUserId)?;
+
+        self.audit
+            .record(AuditEntry {
+                id: Uuid::new_v4(),
+                action: AuditAction::ImpersonationEnded,
+                actor_id: admin_id,
+                subject_id: Some(claims.user_id()?),
+                session_id: Some(actor.sid),
+                created_at: Utc::now(),
+            })
+            .await?;
+        log::info!(
+            "Admin {} stopped impersonating user {}",
+            admin_id,
+            claims.sub
+        );
+
+        Ok(())
+    }
+
+    /// Whether a token issued at `iat` may still act for `actor`: the
+    /// administrator still is one, has not revoked their tokens, and the
+    /// session was not ended.
+    pub async fn accepts(&self, actor: &Actor, iat: i64) -> Result<bool, DomainError> {
+        let admin_id = Uuid::parse_str(&actor.sub).map_err(|_| DomainError::InvalidUserId)?;
+        let admin_ok = self
+            .users
+            .find_by_id(admin_id)
+            .await?
+            .is_some_and(|admin| admin.role == Role::Admin && admin.accepts_token(iat));
+        if !admin_ok {
+            return Ok(false);
+        }
+
+        let ended = self
+            .audit
+            .list_by_session(actor.sid)
+            .await?
+            .iter()
+            .any(|entry| entry.action == AuditAction::ImpersonationEnded);
+
+        Ok(!ended)
+    }
+
+    /// The `limit` most recent entries of the audit log.
+    pub async fn audit_log(&self, limit: i64) -> Result<Vec<AuditEntry>, DomainError> {
+        self.audit.list(limit).await
+    }
+}
@@ -0,0 +1,257 @@
+use std::{sync::Arc, time::Duration};
+
+use chrono::Utc;
+use sha2::{Digest, Sha256};
+use uuid::Uuid;
+
+use crate::{
+    domain::{
+        error::DomainError,
+        model::{
+            media::Media,
+            post::{Post, PostWithAuthor},
+            user::Role,
+        },
+        repository::{MediaRepository, PostRepository},
+    },
+    infrastructure::storage::{BlobLocks, BlobStore},
+};
+
+const ALLOWED_MIME_TYPES: [&str; 9] = [
+    "image/png",
+    "image/jpeg",
+    "image/gif",
+    "image/webp",
+    "application/pdf",
+    "video/mp4",
+    "video/webm",
+    "audio/mpeg",
+    "audio/ogg",
+];
+
+#[derive(Clone)]
+pub struct MediaService<MR, R> {
+    media_repo: MR,
+    post_repo: R,
+    store: Arc<dyn BlobStore>,
+    locks: BlobLocks,
+    max_bytes: usize,
+    url_ttl: Duration,
+}
+
+impl<MR, R> MediaService<MR, R>
+where
+    MR: MediaRepository + Send + Sync,
+    R: PostRepository + Send + Sync,
+{
+    pub fn new(
+        media_repo: MR,
+        post_repo: R,
+        store: Arc<dyn BlobStore>,
+        locks: BlobLocks,
+        max_bytes: usize,
+        url_ttl: Duration,
+    ) -> Self {
+        Self {
+            media_repo,
+            post_repo,
+            store,
+            locks,
+            max_bytes,
+            url_ttl,
+        }
+    }
+
+    pub fn max_bytes(&self) -> usize {
+        self.max_bytes
+    }
+
+    /// Adds a file to the owner's library. Uploading the same bytes twice
+    /// returns the existing entry, and identical files of different users
+    /// share one stored blob.
+    pub async fn upload(
+        &self,
+        owner_id: Uuid,
+        bytes: Vec<u8>,
+        original_name: Option<String>,
+    ) -> Result<Media, DomainError> {
+        if bytes.len() > self.max_bytes {
+            return Err(DomainError::FileTooLarge(self.max_bytes));
+        }
+
+        let mime_type = infer::get(&bytes)
+            .map(|kind| kind.mime_type())
+            .ok_or_else(|| DomainError::UnsupportedMediaType("unknown".to_string()))?;
+
+        if !ALLOWED_MIME_TYPES.contains(&mime_type) {
+            return Err(DomainError::UnsupportedMediaType(mime_type.to_string()));
+        }
+
+        let sha256 = hex::encode(Sha256::digest(&bytes));
+        let _guard = self.locks.lock(&sha256).await;
+
+        if let Some(existing) = self
+            .media_repo
+            .find_by_owner_and_hash(owner_id, &sha256)
+            .await?
+        {
+            return Ok(existing);
+        }
+
+        let media = Media {
+            id: Uuid::new_v4(),
+            owner_id,
+            sha256,
+            mime_type: mime_type.to_string(),
+            size_bytes: bytes.len() as i64,
+            original_name: original_name.map(|name| name.chars().take(255).collect()),
+            created_at: Utc::now(),
+        };
+
+        if self.media_repo.count_by_hash(&media.sha256).await? == 0 {
+            self.store
+                .put(&media.storage_key(), bytes, &media.mime_type)
+                .await
+                .map_err(|e| DomainError::StorageError(e.to_string()))?;
+        }
+
+        self.media_repo.create(media).await
+    }
+
+    /// Media files are private: clients only get expiring signed URLs.
+    pub async fn download_url(&self, media: &Media) -> Result<String, DomainError> {
+        self.store
+            .signed_url(&media.storage_key(), self.url_ttl)
+            .await
+            .map_err(|e| DomainError::StorageError(e.to_string()))
+    }
+
+    pub async fn sign_cover_urls(&self, posts: &mut [PostWithAuthor]) -> Result<(), DomainError> {
+        for post in posts.iter_mut() {
+            if let Some(key) = &post.cover_key {
+                post.cover_url = Some(
+                    self.store
+                        .signed_url(key, self.url_ttl)
+                        .await
+                        .map_err(|e| DomainError::StorageError(e.to_string()))?,
+                );
+            }
+        }
+
+        Ok(())
+    }
+
+    pub async fn list(&self, owner_id: Uuid) -> Result<Vec<Media>, DomainError> {
+        self.media_repo.list_by_owner(owner_id).await
+    }
+
+    pub async fn find(&self, owner_id: Uuid, id: Uuid) -> Result<Media, DomainError> {
+        self.media_repo
+            .find_by_id(id)
+            .await?
+            .filter(|media| media.owner_id == owner_id)
+            .ok_or(DomainError::NotFound)
+    }
+
+    /// Removes a media from the owner's library. The blob itself is only
+    /// deleted once no other library entry points to it.
+    pub async fn delete(&self, owner_id: Uuid, id: Uuid) -> Result<(), DomainError> {
+        let media = self.find(owner_id, id).await?;
+        let _guard = self.locks.lock(&media.sha256).await;
+
+        if self.media_repo.is_referenced(media.id).await? {
+            return Err(DomainError::MediaInUse);
+        }
+
+        self.media_repo.delete(media.id).await?;
+
+        if self.media_repo.count_by_hash(&media.sha256).await? == 0 {
+            self.store
+                .delete(&media.storage_key())
+                .await
+                .map_err(|e| DomainError::StorageError(e.to_string()))?;
+        }
+
+        Ok(())
+    }
+
+    pub async fn list_for_post(&self, post_id: Uuid) -> Result<Vec<Media>, DomainError> {
+        self.post_repo
+            .find_by_id(post_id)
+            .await?
+            .ok_or(DomainError::NotFound)?;
+
+        self.media_repo.list_for_post(post_id).await
+    }
+
+    /// Only the caller's own media can be attached, to a post they wrote
+    /// unless they are an administrator.
+    pub async fn attach(
+        &self,
+        user_id: Uuid,
+        role: &Role,
+        post_id: Uuid,
+        media_id: Uuid,
+    ) -> Result<(), DomainError> {
+        self.editable_post(user_id, role, post_id).await?;
+        let media = self.find(user_id, media_id).await?;
+
+        self.media_repo.attach(post_id, media.id).await
+    }
+
+    pub async fn detach(
+        &self,
+        user_id: Uuid,
+        role: &Role,
+        post_id: Uuid,
+        media_id: Uuid,
+    ) -> Result<(), DomainError> {
+        self.editable_post(user_id, role, post_id).await?;
+        if *role != Role::Admin {
+            self.find(user_id, media_id).await?;
+        }
+
+        self.media_repo.detach(post_id, media_id).await
+    }
+
+    pub async fn set_cover(
+        &self,
+        user_id: Uuid,
+        role: &Role,
+        post_id: Uuid,
+        media_id: Option<Uuid>,
+    ) -> Result<Post, DomainError> {
+        let mut post = self.editable_post(user_id, role, post_id).await?;
+
+        if let Some(media_id) = media_id {
+            let media = self.find(user_id, media_id).await?;
+            if !media.mime_type.starts_with("image/") {
+                return Err(DomainError::UnsupportedMediaType(media.mime_type));
+            }
+        }
+
+        post.cover_media_id = media_id;
+
+        self.post_repo.update(post).await
+    }
+
+    async fn editable_post(
+        &self,
+        user_id: Uuid,
+        role: &Role,
+        post_id: Uuid,
+    ) -> Result<Post, DomainError> {
+        let post: Post = self
+            .post_repo
+            .find_by_id(post_id)
+            .await?
+            .ok_or(DomainError::NotFound)?
+            .into();
+
+        if post.user_id != user_id && *role != Role::Admin {
+            return Err(DomainError::NotPostAuthor);
+        }
+
+        Ok(post)
+    }
+}
@@ -0,0 +1,241 @@
+use std::sync::Arc;
+
+use chrono::Utc;
+use jsonwebtoken::{Header, Validation, decode, encode};
+use rand::{Rng, SeedableRng, distributions::Alphanumeric};
+use rand_chacha::ChaCha20Rng;
+use uuid::Uuid;
+
+use crate::{
+    domain::{
+        error::DomainError,
+        model::{
+            identity::ExternalIdentity,
+            user::{Role, USERNAME_MAX, USERNAME_MIN, User},
+        },
+        repository::{IdentityRepository, UserRepository},
+    },
+    infrastructure::{
+        auth::{
+            create_jwt_token,
+            oidc::{IdTokenClaims, OidcClient, PendingLogin},
+            password::PasswordHashing,
+        },
+        security::keys::Keys,
+    },
+};
+
+/// Outcome of a successful `OidcService::sign_in`.
+pub struct OidcSignIn {
+    pub token: String,
+    /// Whether the account was created by this sign-in.
+    pub provisioned: bool,
+}
+
+/// Signs users in with the ID tokens of the configured provider, linking
+/// each identity to a local account the first time it is seen.
+#[derive(Clone)]
+pub struct OidcService<U, I> {
+    client: Arc<OidcClient>,
+    users: U,
+    identities: I,
+    keys: Keys,
+    /// Signs the `PendingLogin` cookies; derived apart from `keys`.
+    pending_keys: Keys,
+    hashing: PasswordHashing,
+}
+
+impl<U, I> OidcService<U, I>
+where
+    U: UserRepository + Send + Sync,
+    I: IdentityRepository + Send + Sync,
+{
+    pub fn new(
+        client: Arc<OidcClient>,
+        users: U,
+        identities: I,
+        keys: Keys,
+        pending_keys: Keys,
+        hashing: PasswordHashing,
+    ) -> Self {
+        Self {
+            client,
+            users,
+            identities,
+            keys,
+            pending_keys,
+            hashing,
+        }
+    }
+
+    pub fn client(&self) -> &OidcClient {
+        &self.client
+    }
+
+    pub fn seal_pending(&self, pending: &PendingLogin) -> Result<String, DomainError> {
+        encode(&Header::default(), pending, &self.pending_keys.encoding)
+            .map_err(|_| DomainError::InternalError)
+    }
+
+    /// `None` when the cookie was not issued by `seal_pending` or expired.
+    pub fn open_pending(&self, sealed: &str) -> Option<PendingLogin> {
+        decode::<PendingLogin>(sealed, &self.pending_keys.decoding, &Validation::default())
+            .ok()
+            .map(|data| data.claims)
+    }
+
+    /// The API's own token for the account of a verified ID token. An
+    /// unknown identity is linked to the account with the same verified
+    /// email, or to a new account when auto-provisioning is enabled.
+    #[tracing::instrument(name = "OidcService::sign_in", skip_all)]
+    pub async fn sign_in(&self, claims: &IdTokenClaims) -> Result<OidcSignIn, DomainError> {
+        let issuer = self.client.issuer();
+
+        let (user, provisioned) = match self.identities.find(issuer, &claims.sub).await? {
+            Some(identity) => (
+                self.users
+                    .find_by_id(identity.user_id)
+                    .await?
+                    .ok_or(DomainError::NotFound)?,
+                false,
+            ),
+            None => {
+                let (user, provisioned) = self.account_for(claims).await?;
+                self.identities
+                    .create(ExternalIdentity {
+                        issuer: issuer.to_string(),
+                        subject: claims.sub.clone(),
+                        user_id: user.id,
+                        email: claims.email.clone(),
+                        created_at: Utc::now(),
+                    })
+                    .await?;
+                log::info!(
+                    "Linked identity {} of {} to user {}",
+                    claims.sub,
+                    issuer,
+                    user.id
+                );
+                (user, provisioned)
+            }
+        };
+
+        if user.erased_at.is_some() {
+            return Err(DomainError::Unauthorized("Account erased".to_string()));
+        }
+
+        let token = create_jwt_token(user.id, user.role, &self.keys)
+            .map_err(|_| DomainError::InternalError)?;
+
+        Ok(OidcSignIn { token, provisioned })
+    }
+
+    /// Only an address the provider vouches for may claim an existing
+    /// account, or a new one that someone could register later. `true` along
+    /// with a new account.
+    async fn account_for(&self, claims: &IdTokenClaims) -> Result<(User, bool), DomainError> {
+        let email = claims
+            .email
+            .as_deref()
+            .filter(|email| !email.is_empty() && claims.email_verified)
+            .ok_or(DomainError::EmailNotVerified)?;
+
+        if let Some(user) = self.users.find_by_email(email).await? {
+            return Ok((user, false));
+        }
+        if !self.client.auto_provision() {
+            return Err(DomainError::UnknownAccount);
+        }
+
+        Ok((self.provision(claims, email).await?, true))
+    }
+
+    async fn provision(&self, claims: &IdTokenClaims, email: &str) -> Result<User, DomainError> {
+        // Nobody knows this password: the account signs in through the
+        // provider until an admin sets one.
+        let secret: String = ChaCha20Rng::from_entropy()
+            .sample_iter(&Alphanumeric)
+            .take(32)
+            .map(char::from)
+            .collect();
+        let password_hash = self
+            .hashing
+            .hash(&secret)
+            .map_err(|e| DomainError::PasswordHashingError(e.to_string()))?;
+
+        let user = User {
+            id: Uuid::new_v4(),
+            username: self.free_username(claims, email).await?,
+            password_hash,
+            email: email.to_string(),
+            first_name: claims.given_name.clone(),
+            last_name: claims.family_name.clone(),
+            display_name: claims.name.clone(),
+            bio: None,
+            website: None,
+            avatar_key: None,
+            created_at: Utc::now(),
+            updated_at: None,
+            role: Role::User,
+            erased_at: None,
+            tokens_valid_after: None,
+        };
+
+        self.users.create(user).await
+    }
+
+    /// `preferred_username`, or the local part of the email, made to fit the
+    /// username rules and suffixed with a number while it is taken.
+    async fn free_username(
+        &self,
+        claims: &IdTokenClaims,
+        email: &str,
+    ) -> Result<String, DomainError> {
+        let wanted = claims
+            .preferred_username
+            .as_deref()
+            .filter(|name| !name.trim().is_empty())
+            .unwrap_or_else(|| email.split('@').next().unwrap_or_default());
+        let base = normalize_username(wanted);
+
+        for n in 1..100 {
+            let suffix = if n == 1 { String::new() } else { n.to_string() };
+            let candidate = with_suffix(&base, &suffix);
+            if self.users.find_by_username(&candidate).await?.is_none() {
+                return Ok(candidate);
+            }
+        }
+
+        Ok(with_suffix(
+            &base,
+            &Uuid::new_v4().simple().to_string()[..8],
+        ))
+    }
+}
+
+/// Keeps letters, digits, `.`, `-` and `_`, replacing anything else, spaces
+/// and control characters included, with `_`; pads to `USERNAME_MIN`.
+fn normalize_username(wanted: &str) -> String {
+    let mut name: String = wanted
+        .trim()
+        .chars()
+        .map(|c| {
+            if c.is_alphanumeric() || matches!(c, '.' | '-' | '_') {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    while name.chars().count() < USERNAME_MIN as usize {
+        name.push('_');
+    }
+
+    name
+}
+
+/// `base` cut so that, once `suffix` is appended, it fits `USERNAME_MAX`.
+fn with_suffix(base: &str, suffix: &str) -> String {
+    let room = USERNAME_MAX as usize - suffix.chars().count();
+    base.chars().take(room).chain(suffix.chars()).collect()
+}
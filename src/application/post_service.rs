@@ -25,14 +25,17 @@ where
         Self { repo, user_repo }
     }
 
+    #[tracing::instrument(name = "PostService::list", skip_all)]
     pub async fn list(&self) -> Result<Vec<PostWithAuthor>, DomainError> {
         self.repo.list().await
     }
 
+    #[tracing::instrument(name = "PostService::find_by_id", skip_all, fields(%id))]
     pub async fn find_by_id(&self, id: Uuid) -> Result<Option<PostWithAuthor>, DomainError> {
         self.repo.find_by_id(id).await
     }
 
+    #[tracing::instrument(name = "PostService::create", skip_all, fields(%user_id))]
     pub async fn create(
         &self,
         title: String,
@@ -48,6 +51,7 @@ where
             user_id,
             created_at: Utc::now(),
             updated_at: None,
+            cover_media_id: None,
         };
 
         self.repo.create(post.clone()).await?;
@@ -55,6 +59,20 @@ where
         Ok(post)
     }
 
+    /// Inserts a post as is, keeping its id and dates. Returns `false` when a
+    /// post with the same id already exists.
+    #[tracing::instrument(name = "PostService::import", skip_all)]
+    pub async fn import(&self, post: Post) -> Result<bool, DomainError> {
+        if self.repo.find_by_id(post.id).await?.is_some() {
+            return Ok(false);
+        }
+
+        self.repo.create(post).await?;
+
+        Ok(true)
+    }
+
+    #[tracing::instrument(name = "PostService::update", skip_all, fields(%post_id))]
     pub async fn update(
         &self,
         post_id: Uuid,
@@ -94,6 +112,7 @@ where
         Ok(updated)
     }
 
+    #[tracing::instrument(name = "PostService::delete", skip_all, fields(%id))]
     pub async fn delete(&self, id: Uuid) -> Result<(), DomainError> {
         self.repo.delete(id).await
     }
@@ -8,10 +8,7 @@ use crate::{
         repository::UserRepository,
     },
     infrastructure::{
-        auth::{
-            create_jwt_token,
-            password::{hash_password, verify_password},
-        },
+        auth::{create_jwt_token, password::PasswordHashing},
         security::keys::Keys,
     },
     interfaces::api::dto::user::UpdateUserPayload,
@@ -21,60 +18,122 @@ use crate::{
 pub struct UserService<R> {
     repo: R,
     keys: Keys,
+    hashing: PasswordHashing,
 }
 
 impl<R> UserService<R>
 where
     R: UserRepository + Send + Sync,
 {
-    pub fn new(repo: R, keys: Keys) -> Self {
-        UserService { repo, keys }
+    pub fn new(repo: R, keys: Keys, hashing: PasswordHashing) -> Self {
+        UserService {
+            repo,
+            keys,
+            hashing,
+        }
     }
 
+    #[tracing::instrument(name = "UserService::list", skip_all)]
     pub async fn list(&self) -> Result<Vec<User>, DomainError> {
         self.repo.list().await
     }
 
+    #[tracing::instrument(name = "UserService::find_by_id", skip_all, fields(%id))]
     pub async fn find_by_id(&self, id: uuid::Uuid) -> Result<Option<User>, DomainError> {
         self.repo.find_by_id(id).await
     }
 
+    #[tracing::instrument(name = "UserService::find_by_username", skip_all)]
+    pub async fn find_by_username(&self, username: &str) -> Result<Option<User>, DomainError> {
+        self.repo.find_by_username(username).await
+    }
+
+    #[tracing::instrument(name = "UserService::count", skip_all)]
+    pub async fn count(&self) -> Result<i64, DomainError> {
+        self.repo.count().await
+    }
+
+    #[tracing::instrument(name = "UserService::login", skip_all)]
     pub async fn login(&self, username: &str, password: &str) -> Result<String, DomainError> {
-        let user: User = self
+        let mut user: User = self
             .repo
             .find_by_username(username)
             .await?
             .ok_or(DomainError::Unauthorized("Invalid credentials".to_string()))?;
 
-        let valid = verify_password(password, &user.password_hash)
+        let valid = self
+            .hashing
+            .verify(password, &user.password_hash)
             .map_err(|_| DomainError::Unauthorized("Invalid credentials".to_string()))?;
 
         if !valid {
             return Err(DomainError::Unauthorized("Invalid credentials".to_string()));
         }
 
+        // The password is only known here, when the hashing settings were
+        // strengthened since it was last set.
+        if self.hashing.needs_rehash(&user.password_hash) {
+            match self.hashing.hash(password) {
+                Ok(hash) => {
+                    user.password_hash = hash;
+                    if let Err(e) = self.repo.update(user.clone()).await {
+                        log::warn!(
+                            "Failed to store the rehashed password of {}: {}",
+                            user.id,
+                            e
+                        );
+                    }
+                }
+                Err(e) => log::warn!("Failed to rehash the password of {}: {}", user.id, e),
+            }
+        }
+
         let token = create_jwt_token(user.id, user.role.clone(), &self.keys)
             .map_err(|_| DomainError::InternalError)?;
 
         Ok(token)
     }
 
+    #[tracing::instrument(name = "UserService::create_user", skip_all)]
     pub async fn create_user(
         &self,
         username: String,
         password: String,
         email: String,
     ) -> Result<User, DomainError> {
-        let hashed_password = hash_password(&password).map_err(|_| DomainError::InternalError)?;
+        self.create_user_with_role(username, password, email, Role::User)
+            .await
+    }
+
+    #[tracing::instrument(name = "UserService::create_user_with_role", skip_all)]
+    pub async fn create_user_with_role(
+        &self,
+        username: String,
+        password: String,
+        email: String,
+        role: Role,
+    ) -> Result<User, DomainError> {
+        let hashed_password = self
+            .hashing
+            .hash(&password)
+            .map_err(|_| DomainError::InternalError)?;
 
         let user = User {
             id: uuid::Uuid::new_v4(),
             username,
             password_hash: hashed_password,
             email,
-            role: Role::User,
+            first_name: None,
+            last_name: None,
+            display_name: None,
+            bio: None,
+            website: None,
+            avatar_key: None,
+            role,
             created_at: Utc::now(),
             updated_at: None,
+            erased_at: None,
+            tokens_valid_after: None,
         };
 
         self.repo.create(user.clone()).await?;
@@ -82,10 +141,7 @@ where
         Ok(user)
     }
 
-    pub async fn delete(&self, id: uuid::Uuid) -> Result<(), DomainError> {
-        self.repo.delete(id).await
-    }
-
+    #[tracing::instrument(name = "UserService::update", skip_all, fields(%user_id))]
     pub async fn update(
         &self,
         user_id: Uuid,
@@ -104,8 +160,31 @@ where
             user.email = e;
         }
 
+        if let Some(first_name) = payload.first_name {
+            user.first_name = first_name;
+        }
+
+        if let Some(last_name) = payload.last_name {
+            user.last_name = last_name;
+        }
+
+        if let Some(display_name) = payload.display_name {
+            user.display_name = display_name;
+        }
+
+        if let Some(bio) = payload.bio {
+            user.bio = bio;
+        }
+
+        if let Some(website) = payload.website {
+            user.website = website;
+        }
+
         if let Some(raw_pwd) = payload.password {
-            user.password_hash = hash_password(&raw_pwd).map_err(|_| DomainError::InternalError)?;
+            user.password_hash = self
+                .hashing
+                .hash(&raw_pwd)
+                .map_err(|_| DomainError::InternalError)?;
         }
 
         if let Some(r) = payload.role {
@@ -118,4 +197,14 @@ where
 
         Ok(updated)
     }
+
+    /// Invalidates every token issued so far for this user.
+    #[tracing::instrument(name = "UserService::revoke_tokens", skip_all, fields(%id))]
+    pub async fn revoke_tokens(&self, id: Uuid) -> Result<User, DomainError> {
+        let mut user = self.find_by_id(id).await?.ok_or(DomainError::NotFound)?;
+
+        user.tokens_valid_after = Some(Utc::now());
+
+        self.repo.update(user).await
+    }
 }
@@ -0,0 +1,312 @@
+use std::{collections::HashMap, io::BufRead, path::PathBuf, str::FromStr};
+
+use anyhow::{Context, Result, anyhow, bail};
+use api_back_trio::application::{post_service::PostService, user_service::UserService};
+use api_back_trio::config::Settings;
+use api_back_trio::domain::model::{
+    post::Post,
+    user::{Role, User},
+};
+use api_back_trio::infrastructure::{
+    auth::password::PasswordHashing,
+    db::connect_db,
+    security::{keys::Keys, password_policy::PasswordPolicy},
+};
+use api_back_trio::interfaces::api::{
+    dto::user::{NewUser, UpdateUserPayload},
+    error::ApiError,
+    state::{DynPostService, DynUserService},
+};
+use chrono::{DateTime, Utc};
+use clap::{Parser, Subcommand};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// Administration du blog, sur la base pointée par `DATABASE_URL`.
+#[derive(Parser)]
+#[command(name = "blog-admin", version)]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Create a user with the given role
+    CreateUser {
+        #[arg(long)]
+        username: String,
+        #[arg(long)]
+        email: String,
+        #[arg(long, default_value = "user", value_parser = parse_role)]
+        role: Role,
+        /// Read from standard input when absent
+        #[arg(long)]
+        password: Option<String>,
+    },
+    /// Set a new password and revoke the user's tokens
+    ResetPassword {
+        username: String,
+        /// Read from standard input when absent
+        #[arg(long)]
+        password: Option<String>,
+    },
+    /// Give the admin role and revoke the user's tokens
+    Promote { username: String },
+    /// Give the user role back and revoke the user's tokens
+    Demote { username: String },
+    /// List every account
+    ListUsers,
+    /// Apply pending migrations
+    Migrate,
+    /// Refuse every token issued so far for the user
+    RevokeTokens { username: String },
+    /// Write all posts to a JSON file
+    Export { file: PathBuf },
+    /// Read posts from a JSON file, skipping ids already present
+    Import { file: PathBuf },
+}
+
+/// Format of `export`/`import`: authors are referenced by username so that a
+/// dump can be loaded in a database where the accounts have other ids.
+#[derive(Serialize, Deserialize)]
+struct ContentDump {
+    exported_at: DateTime<Utc>,
+    posts: Vec<PostRecord>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct PostRecord {
+    id: Uuid,
+    author: String,
+    title: String,
+    content: String,
+    published: bool,
+    created_at: DateTime<Utc>,
+    updated_at: Option<DateTime<Utc>>,
+}
+
+fn parse_role(s: &str) -> Result<Role, String> {
+    Role::from_str(s).map_err(|_| format!("unknown role `{s}`, expected user or admin"))
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    let cli = Cli::parse();
+    let settings = Settings::from_env()?;
+
+    let db = connect_db(&settings.database_url).await?;
+    if let Command::Migrate = cli.command {
+        let applied = db.migrate().await?;
+        for migration in &applied {
+            println!("applied {migration}");
+        }
+        println!("{} migrations applied", applied.len());
+        return Ok(());
+    }
+    let pending = db.pending_migrations().await?;
+    if pending > 0 {
+        bail!("{pending} pending migrations, run `blog-admin migrate` first");
+    }
+
+    let repos = db.repositories();
+    let keys = Keys::new(settings.jwt_secret.as_bytes());
+    let hashing = PasswordHashing::new(&settings.argon2)?;
+    let users: DynUserService = UserService::new(repos.users.clone(), keys, hashing);
+    let posts: DynPostService = PostService::new(repos.posts, repos.users);
+    let policy = PasswordPolicy::from_settings(&settings.password);
+
+    match cli.command {
+        Command::CreateUser {
+            username,
+            email,
+            role,
+            password,
+        } => {
+            // Same rules as the accounts created through the API.
+            let dto = NewUser {
+                username: Some(username),
+                password: Some(prompt_password(password)?),
+                email: Some(email),
+            };
+            let (username, password, email) = dto
+                .validate_and_into_domain(&policy)
+                .await
+                .map_err(invalid_user)?;
+            let user = users
+                .create_user_with_role(username, password, email, role)
+                .await?;
+            println!("{} ({}) created as {}", user.username, user.id, user.role);
+        }
+        Command::ResetPassword { username, password } => {
+            let user = find_user(&users, &username).await?;
+            let password = read_password(password, &policy, &[&user.username, &user.email]).await?;
+            users
+                .update(
+                    user.id,
+                    UpdateUserPayload {
+                        password: Some(password),
+                        ..Default::default()
+                    },
+                )
+                .await?;
+            users.revoke_tokens(user.id).await?;
+            println!("password of {username} reset, existing tokens revoked");
+        }
+        Command::Promote { username } => set_role(&users, &username, Role::Admin).await?,
+        Command::Demote { username } => set_role(&users, &username, Role::User).await?,
+        Command::ListUsers => {
+            for user in users.list().await? {
+                let state = if user.erased_at.is_some() {
+                    " (erased)"
+                } else {
+                    ""
+                };
+                println!(
+                    "{}\t{}\t{}\t{}{}",
+                    user.id, user.username, user.email, user.role, state
+                );
+            }
+        }
+        Command::Migrate => unreachable!("handled before building the services"),
+        Command::RevokeTokens { username } => {
+            let user = find_user(&users, &username).await?;
+            users.revoke_tokens(user.id).await?;
+            println!("tokens of {username} revoked");
+        }
+        Command::Export { file } => {
+            let dump = ContentDump {
+                exported_at: Utc::now(),
+                posts: posts
+                    .list()
+                    .await?
+                    .into_iter()
+                    .map(|post| PostRecord {
+                        id: post.id,
+                        author: post.author.username,
+                        title: post.title,
+                        content: post.content,
+                        published: post.published,
+                        created_at: post.created_at,
+                        updated_at: post.updated_at,
+                    })
+                    .collect(),
+            };
+            std::fs::write(&file, serde_json::to_vec_pretty(&dump)?)
+                .with_context(|| format!("cannot write {}", file.display()))?;
+            println!("{} posts exported to {}", dump.posts.len(), file.display());
+        }
+        Command::Import { file } => {
+            let raw =
+                std::fs::read(&file).with_context(|| format!("cannot read {}", file.display()))?;
+            let dump: ContentDump = serde_json::from_slice(&raw)?;
+
+            // Every author is resolved before the first insert, so that a dump
+            // naming unknown accounts leaves the database untouched.
+            let mut authors = HashMap::new();
+            let mut unknown = Vec::new();
+            for record in &dump.posts {
+                if authors.contains_key(&record.author) || unknown.contains(&record.author) {
+                    continue;
+                }
+                match users.find_by_username(&record.author).await? {
+                    Some(user) => {
+                        authors.insert(record.author.clone(), user.id);
+                    }
+                    None => unknown.push(record.author.clone()),
+                }
+            }
+            if !unknown.is_empty() {
+                bail!("unknown authors, nothing imported: {}", unknown.join(", "));
+            }
+
+            let (mut imported, mut skipped) = (0, 0);
+            for record in dump.posts {
+                let post = Post {
+                    id: record.id,
+                    user_id: authors[&record.author],
+                    title: record.title,
+                    content: record.content,
+                    published: record.published,
+                    created_at: record.created_at,
+                    updated_at: record.updated_at,
+                    cover_media_id: None,
+                };
+
+                if posts.import(post).await? {
+                    imported += 1;
+                } else {
+                    skipped += 1;
+                }
+            }
+            println!("{imported} posts imported, {skipped} already present");
+        }
+    }
+
+    Ok(())
+}
+
+async fn find_user(users: &DynUserService, username: &str) -> Result<User> {
+    match users.find_by_username(username).await? {
+        Some(user) => Ok(user),
+        None => bail!("no user named {username}"),
+    }
+}
+
+async fn set_role(users: &DynUserService, username: &str, role: Role) -> Result<()> {
+    let user = find_user(users, username).await?;
+    users
+        .update(
+            user.id,
+            UpdateUserPayload {
+                role: Some(role.clone()),
+                ..Default::default()
+            },
+        )
+        .await?;
+    users.revoke_tokens(user.id).await?;
+    println!("{username} is now {role}, existing tokens revoked");
+
+    Ok(())
+}
+
+/// `password`, or a line read from standard input when absent.
+fn prompt_password(password: Option<String>) -> Result<String> {
+    match password {
+        Some(password) => Ok(password),
+        None => {
+            eprintln!("Password:");
+            let mut line = String::new();
+            std::io::stdin().lock().read_line(&mut line)?;
+            Ok(line.trim_end_matches(['\r', '\n']).to_string())
+        }
+    }
+}
+
+async fn read_password(
+    password: Option<String>,
+    policy: &PasswordPolicy,
+    personal: &[&str],
+) -> Result<String> {
+    let password = prompt_password(password)?;
+
+    if let Some(violation) = policy.check(&password, personal).await.first() {
+        bail!(violation.to_string());
+    }
+
+    Ok(password)
+}
+
+/// Lists every invalid field, where the API would answer 422.
+fn invalid_user(err: ApiError) -> anyhow::Error {
+    match err {
+        ApiError::Validation(errors) => anyhow!(
+            errors
+                .iter()
+                .map(|e| format!("{}: {}", e.field, e.message))
+                .collect::<Vec<_>>()
+                .join("\n")
+        ),
+        err => anyhow!(err),
+    }
+}
@@ -1,5 +1,7 @@
 use serde::Deserialize;
 
+use crate::interfaces::api::i18n::Locale;
+
 #[derive(Debug, Deserialize, Clone)]
 pub struct ServerSettings {
     pub host: String,
@@ -12,6 +14,280 @@ pub struct TlsSettings {
     pub key_path: String,
 }
 
+#[derive(Debug, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum StorageBackend {
+    #[default]
+    Local,
+    S3,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct S3Settings {
+    /// e.g. `https://s3.eu-west-3.amazonaws.com` or `http://localhost:9000` for MinIO
+    pub endpoint: String,
+    #[serde(default = "default_s3_region")]
+    pub region: String,
+    pub bucket: String,
+    pub access_key: String,
+    pub secret_key: String,
+    /// `{endpoint}/{bucket}/{key}` as MinIO expects; `false` addresses
+    /// `{bucket}.{endpoint host}/{key}` instead.
+    #[serde(default = "default_s3_path_style")]
+    pub path_style: bool,
+}
+
+fn default_s3_region() -> String {
+    "us-east-1".to_string()
+}
+
+fn default_s3_path_style() -> bool {
+    true
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct StorageSettings {
+    #[serde(default)]
+    pub backend: StorageBackend,
+    #[serde(default = "default_storage_path")]
+    pub local_path: String,
+    /// Secret used to sign local download URLs, derived from `JWT_SECRET`
+    /// when absent.
+    pub signing_secret: Option<String>,
+    #[serde(default = "default_signed_url_ttl_secs")]
+    pub signed_url_ttl_secs: u64,
+    pub s3: Option<S3Settings>,
+}
+
+impl Default for StorageSettings {
+    fn default() -> Self {
+        Self {
+            backend: StorageBackend::default(),
+            local_path: default_storage_path(),
+            signing_secret: None,
+            signed_url_ttl_secs: default_signed_url_ttl_secs(),
+            s3: None,
+        }
+    }
+}
+
+fn default_storage_path() -> String {
+    "./uploads".to_string()
+}
+
+fn default_signed_url_ttl_secs() -> u64 {
+    3600
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct UploadSettings {
+    #[serde(default = "default_avatar_max_bytes")]
+    pub avatar_max_bytes: usize,
+    #[serde(default = "default_media_max_bytes")]
+    pub media_max_bytes: usize,
+}
+
+impl Default for UploadSettings {
+    fn default() -> Self {
+        Self {
+            avatar_max_bytes: default_avatar_max_bytes(),
+            media_max_bytes: default_media_max_bytes(),
+        }
+    }
+}
+
+fn default_avatar_max_bytes() -> usize {
+    5 * 1024 * 1024
+}
+
+fn default_media_max_bytes() -> usize {
+    20 * 1024 * 1024
+}
+
+/// `/metrics` is served on the API when `token` is set (as a Bearer token),
+/// and without authentication on a separate plain HTTP listener when `port` is
+/// set, meant to stay on a private network.
+#[derive(Debug, Deserialize, Clone)]
+pub struct MetricsSettings {
+    pub token: Option<String>,
+    #[serde(default = "default_metrics_host")]
+    pub host: String,
+    pub port: Option<u16>,
+}
+
+impl Default for MetricsSettings {
+    fn default() -> Self {
+        Self {
+            token: None,
+            host: default_metrics_host(),
+            port: None,
+        }
+    }
+}
+
+fn default_metrics_host() -> String {
+    "127.0.0.1".to_string()
+}
+
+/// OpenTelemetry export; traces stay local (logs only) without an endpoint.
+#[derive(Debug, Deserialize, Clone)]
+pub struct TracingSettings {
+    /// Base URL of an OTLP/HTTP collector, e.g. `http://localhost:4318`.
+    pub otlp_endpoint: Option<String>,
+    /// Share of new traces kept, between 0 and 1; requests carrying a
+    /// `traceparent` follow the caller's decision.
+    #[serde(default = "default_sample_ratio")]
+    pub sample_ratio: f64,
+    #[serde(default = "default_service_name")]
+    pub service_name: String,
+}
+
+impl Default for TracingSettings {
+    fn default() -> Self {
+        Self {
+            otlp_endpoint: None,
+            sample_ratio: default_sample_ratio(),
+            service_name: default_service_name(),
+        }
+    }
+}
+
+fn default_sample_ratio() -> f64 {
+    1.0
+}
+
+fn default_service_name() -> String {
+    "blog-api".to_string()
+}
+
+/// Rules applied to every new password, of the API and of `blog-admin`.
+#[derive(Debug, Deserialize, Clone)]
+#[serde(default)]
+pub struct PasswordSettings {
+    pub min_length: usize,
+    pub max_length: usize,
+    pub require_uppercase: bool,
+    pub require_lowercase: bool,
+    pub require_digit: bool,
+    pub require_special_char: bool,
+    /// Lowest zxcvbn score accepted, from 0 (no check) to 4.
+    pub min_score: u8,
+    /// Directory of leaked password hashes, one `<first 5 hex chars of the
+    /// SHA-1>.txt` file per prefix holding `<remaining 35 chars>:<count>`
+    /// lines, as laid out by the Pwned Passwords downloader.
+    pub breached_path: Option<String>,
+}
+
+impl Default for PasswordSettings {
+    fn default() -> Self {
+        Self {
+            min_length: 8,
+            max_length: 255,
+            require_uppercase: true,
+            require_lowercase: true,
+            require_digit: true,
+            require_special_char: true,
+            min_score: 0,
+            breached_path: None,
+        }
+    }
+}
+
+/// Cost of the Argon2id password hashes; hashes made with other values are
+/// replaced at the next successful login.
+#[derive(Debug, Deserialize, Clone)]
+#[serde(default)]
+pub struct Argon2Settings {
+    pub memory_kib: u32,
+    pub iterations: u32,
+    pub parallelism: u32,
+    /// Secret mixed into every hash and kept out of the database. Changing
+    /// it makes the passwords hashed with the previous one unusable.
+    pub pepper: Option<String>,
+}
+
+impl Default for Argon2Settings {
+    fn default() -> Self {
+        Self {
+            memory_kib: 19 * 1024,
+            iterations: 2,
+            parallelism: 1,
+            pepper: None,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum SameSitePolicy {
+    #[default]
+    Strict,
+    Lax,
+    None,
+}
+
+/// Browser sessions: `/api/login` also sets the JWT in an `HttpOnly` cookie,
+/// which authenticates the requests without `Authorization` header as long as
+/// the state-changing ones repeat the CSRF token in `X-CSRF-Token`.
+#[derive(Debug, Deserialize, Clone)]
+#[serde(default)]
+pub struct SessionSettings {
+    pub enabled: bool,
+    pub cookie_name: String,
+    /// Readable by scripts, unlike the session cookie.
+    pub csrf_cookie_name: String,
+    /// `none` is needed when the front end is served from another site.
+    pub same_site: SameSitePolicy,
+    pub domain: Option<String>,
+}
+
+impl Default for SessionSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            cookie_name: "blog_session".to_string(),
+            csrf_cookie_name: "blog_csrf".to_string(),
+            same_site: SameSitePolicy::default(),
+            domain: None,
+        }
+    }
+}
+
+/// Sign-in through an OpenID Connect provider, with the authorization code
+/// flow and PKCE.
+#[derive(Debug, Deserialize, Clone)]
+pub struct OidcSettings {
+    /// Base URL of the provider, where `/.well-known/openid-configuration`
+    /// is served; also the expected `iss` of its ID tokens.
+    pub issuer: String,
+    pub client_id: String,
+    /// Absent for public clients.
+    pub client_secret: Option<String>,
+    /// Must be registered at the provider, usually `…/api/oidc/callback`.
+    pub redirect_uri: String,
+    #[serde(default = "default_oidc_scopes")]
+    pub scopes: String,
+    /// Create an account on the first sign-in of an unknown identity.
+    #[serde(default = "default_auto_provision")]
+    pub auto_provision: bool,
+}
+
+fn default_oidc_scopes() -> String {
+    "openid email profile".to_string()
+}
+
+fn default_auto_provision() -> bool {
+    true
+}
+
+/// First administrator, created at startup when the `users` table is empty.
+#[derive(Debug, Deserialize, Clone)]
+pub struct AdminSettings {
+    pub username: String,
+    pub email: String,
+    pub password: String,
+}
+
 #[derive(Debug, Deserialize, Clone)]
 pub struct Settings {
     pub database_url: String,
@@ -19,6 +295,25 @@ pub struct Settings {
     pub tls: Option<TlsSettings>,
     pub jwt_secret: String,
     pub cors_origin: String,
+    #[serde(default)]
+    pub storage: StorageSettings,
+    #[serde(default)]
+    pub uploads: UploadSettings,
+    pub admin: Option<AdminSettings>,
+    pub oidc: Option<OidcSettings>,
+    #[serde(default)]
+    pub metrics: MetricsSettings,
+    #[serde(default)]
+    pub tracing: TracingSettings,
+    #[serde(default)]
+    pub password: PasswordSettings,
+    #[serde(default)]
+    pub argon2: Argon2Settings,
+    #[serde(default)]
+    pub session: SessionSettings,
+    /// Language of the messages when `Accept-Language` names none we support.
+    #[serde(default)]
+    pub default_locale: Locale,
 }
 
 impl Settings {
@@ -22,4 +22,49 @@ pub enum DomainError {
     InternalError,
     #[error("This email is already used")]
     DuplicateEmail,
+    #[error("Image invalide: {0}")]
+    InvalidImage(String),
+    #[error("Type de fichier non supporté: {0}")]
+    UnsupportedMediaType(String),
+    #[error("Fichier trop volumineux (maximum {0} octets)")]
+    FileTooLarge(usize),
+    #[error("Ce média est encore utilisé par un post")]
+    MediaInUse,
+    #[error("Erreur de stockage: {0}")]
+    StorageError(String),
+    #[error("L'adresse e-mail n'est pas vérifiée par le fournisseur d'identité")]
+    EmailNotVerified,
+    #[error("Aucun compte ne correspond à cette identité")]
+    UnknownAccount,
+    #[error("Action interdite dans le cadre d'une usurpation d'identité")]
+    ImpersonationForbidden,
+    #[error("Seul l'auteur du post ou un administrateur peut le modifier")]
+    NotPostAuthor,
+}
+
+impl DomainError {
+    /// Stable identifier of the error, returned to API clients as `code`.
+    pub fn code(&self) -> &'static str {
+        match self {
+            DomainError::InvalidMinLentgthTitle => "title_too_short",
+            DomainError::InvalidMaxLentgthTitle => "title_too_long",
+            DomainError::EmptyContent => "content_empty",
+            DomainError::DatabaseError(_) => "database_error",
+            DomainError::NotFound => "not_found",
+            DomainError::InvalidUserId => "invalid_user_id",
+            DomainError::PasswordHashingError(_) => "password_hashing_failed",
+            DomainError::Unauthorized(_) => "invalid_credentials",
+            DomainError::InternalError => "internal_error",
+            DomainError::DuplicateEmail => "email_taken",
+            DomainError::InvalidImage(_) => "invalid_image",
+            DomainError::UnsupportedMediaType(_) => "unsupported_media_type",
+            DomainError::FileTooLarge(_) => "file_too_large",
+            DomainError::MediaInUse => "media_in_use",
+            DomainError::StorageError(_) => "storage_error",
+            DomainError::EmailNotVerified => "email_not_verified",
+            DomainError::UnknownAccount => "unknown_account",
+            DomainError::ImpersonationForbidden => "impersonation_forbidden",
+            DomainError::NotPostAuthor => "not_post_author",
+        }
+    }
 }
@@ -0,0 +1,107 @@
+use std::{fmt, str::FromStr};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// What a personal access token may be used for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Scope {
+    #[serde(rename = "posts:read")]
+    PostsRead,
+    #[serde(rename = "posts:write")]
+    PostsWrite,
+    #[serde(rename = "media:read")]
+    MediaRead,
+    #[serde(rename = "media:write")]
+    MediaWrite,
+    #[serde(rename = "profile:read")]
+    ProfileRead,
+    #[serde(rename = "profile:write")]
+    ProfileWrite,
+    /// The `/api/users` administration, for tokens of administrators.
+    #[serde(rename = "admin")]
+    Admin,
+}
+
+impl Scope {
+    pub const ALL: [Scope; 7] = [
+        Scope::PostsRead,
+        Scope::PostsWrite,
+        Scope::MediaRead,
+        Scope::MediaWrite,
+        Scope::ProfileRead,
+        Scope::ProfileWrite,
+        Scope::Admin,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Scope::PostsRead => "posts:read",
+            Scope::PostsWrite => "posts:write",
+            Scope::MediaRead => "media:read",
+            Scope::MediaWrite => "media:write",
+            Scope::ProfileRead => "profile:read",
+            Scope::ProfileWrite => "profile:write",
+            Scope::Admin => "admin",
+        }
+    }
+
+    /// Space separated, the form the scopes are stored in.
+    pub fn join(scopes: &[Scope]) -> String {
+        scopes
+            .iter()
+            .map(Scope::as_str)
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    /// Inverse of `join`; unknown names are skipped.
+    pub fn split(scopes: &str) -> Vec<Scope> {
+        scopes
+            .split_whitespace()
+            .filter_map(|s| s.parse().ok())
+            .collect()
+    }
+}
+
+impl fmt::Display for Scope {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for Scope {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Scope::ALL
+            .into_iter()
+            .find(|scope| scope.as_str() == s)
+            .ok_or(())
+    }
+}
+
+/// A named token a user creates to call the API without a password, e.g.
+/// from CI. Only a digest of the secret is kept; `prefix` is its beginning,
+/// shown so that the owner can tell the tokens apart.
+#[derive(Debug, Clone)]
+pub struct AccessToken {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub name: String,
+    pub prefix: String,
+    /// SHA-256 of the whole token, hex encoded.
+    pub token_hash: String,
+    pub scopes: Vec<Scope>,
+    pub created_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+    pub last_used_at: Option<DateTime<Utc>>,
+    pub revoked_at: Option<DateTime<Utc>>,
+}
+
+impl AccessToken {
+    pub fn is_active(&self, now: DateTime<Utc>) -> bool {
+        self.revoked_at.is_none() && self.expires_at > now
+    }
+}
@@ -0,0 +1,57 @@
+use std::{fmt, str::FromStr};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AuditAction {
+    ImpersonationStarted,
+    ImpersonationEnded,
+}
+
+impl AuditAction {
+    pub const ALL: [AuditAction; 2] = [
+        AuditAction::ImpersonationStarted,
+        AuditAction::ImpersonationEnded,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AuditAction::ImpersonationStarted => "impersonation_started",
+            AuditAction::ImpersonationEnded => "impersonation_ended",
+        }
+    }
+}
+
+impl fmt::Display for AuditAction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for AuditAction {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        AuditAction::ALL
+            .into_iter()
+            .find(|action| action.as_str() == s)
+            .ok_or(())
+    }
+}
+
+/// A sensitive action, kept after the accounts involved are deleted.
+#[derive(Debug, Clone)]
+pub struct AuditEntry {
+    pub id: Uuid,
+    pub action: AuditAction,
+    /// Who acted: the administrator, for an impersonation.
+    pub actor_id: Uuid,
+    /// Whom the action was about.
+    pub subject_id: Option<Uuid>,
+    /// Groups the entries of one impersonation session.
+    pub session_id: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+}
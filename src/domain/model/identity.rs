@@ -0,0 +1,14 @@
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+/// Account of an OpenID Connect provider linked to a local user, identified
+/// by the `iss` and `sub` claims of its ID tokens.
+#[derive(Debug, Clone)]
+pub struct ExternalIdentity {
+    pub issuer: String,
+    pub subject: String,
+    pub user_id: Uuid,
+    /// Address given by the provider when the identity was linked.
+    pub email: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
@@ -0,0 +1,25 @@
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+/// A file of a user's media library. The bytes are content addressed: every
+/// `Media` sharing the same `sha256` points to a single stored blob.
+#[derive(Debug, Clone)]
+pub struct Media {
+    pub id: Uuid,
+    pub owner_id: Uuid,
+    pub sha256: String,
+    pub mime_type: String,
+    pub size_bytes: i64,
+    pub original_name: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl Media {
+    pub fn storage_key(&self) -> String {
+        media_storage_key(&self.sha256)
+    }
+}
+
+pub fn media_storage_key(sha256: &str) -> String {
+    format!("media/{}/{}", &sha256[..2], sha256)
+}
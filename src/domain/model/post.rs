@@ -12,6 +12,7 @@ pub struct Post {
     pub published: bool,
     pub created_at: DateTime<Utc>,
     pub updated_at: Option<DateTime<Utc>>,
+    pub cover_media_id: Option<Uuid>,
 }
 
 #[derive(Debug, Serialize)]
@@ -22,6 +23,12 @@ pub struct PostWithAuthor {
     pub published: bool,
     pub created_at: DateTime<Utc>,
     pub updated_at: Option<DateTime<Utc>>,
+    pub cover_media_id: Option<Uuid>,
+    /// Storage key of the cover image; exposed to clients through `cover_url`,
+    /// a signed URL filled in by the HTTP layer.
+    #[serde(skip)]
+    pub cover_key: Option<String>,
+    pub cover_url: Option<String>,
     pub author: UserPublic,
 }
 
@@ -35,6 +42,7 @@ impl From<PostWithAuthor> for Post {
             published: pwa.published,
             created_at: pwa.created_at,
             updated_at: pwa.updated_at,
+            cover_media_id: pwa.cover_media_id,
         }
     }
 }
@@ -5,6 +5,10 @@ use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
+/// Bounds, in characters, of the usernames accepted by the API.
+pub const USERNAME_MIN: u64 = 3;
+pub const USERNAME_MAX: u64 = 50;
+
 #[derive(Debug, Clone, Serialize, Deserialize, sqlx::Type, PartialEq, Eq)]
 #[sqlx(type_name = "TEXT")]
 pub enum Role {
@@ -35,13 +39,48 @@ impl FromStr for Role {
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// Never serialized as is: the API exposes users through the `UserPublic`,
+/// `UserProfile` and `UserAdminView` projections so that `password_hash`
+/// cannot leak and `email` is only shown to its owner and to admins.
+#[derive(Debug, Clone)]
 pub struct User {
     pub id: Uuid,
     pub username: String,
     pub password_hash: String,
     pub email: String,
+    pub first_name: Option<String>,
+    pub last_name: Option<String>,
+    pub display_name: Option<String>,
+    pub bio: Option<String>,
+    pub website: Option<String>,
+    pub avatar_key: Option<String>,
     pub created_at: DateTime<Utc>,
     pub updated_at: Option<DateTime<Utc>>,
     pub role: Role,
+    pub erased_at: Option<DateTime<Utc>>,
+    /// Tokens issued before this instant are refused, see `accepts_token`.
+    pub tokens_valid_after: Option<DateTime<Utc>>,
+}
+
+/// Edge lengths, in pixels, of the square thumbnails generated for avatars.
+pub const AVATAR_SIZES: [u32; 3] = [64, 128, 256];
+
+impl User {
+    /// Whether a token issued at `issued_at` (a Unix timestamp) may still
+    /// authenticate this user: erased accounts and revoked tokens are refused.
+    /// `iat` only has second precision, so tokens issued during the second of
+    /// the revocation are refused as well.
+    pub fn accepts_token(&self, issued_at: i64) -> bool {
+        self.erased_at.is_none()
+            && self
+                .tokens_valid_after
+                .is_none_or(|after| issued_at > after.timestamp())
+    }
+
+    /// Storage key of the avatar thumbnail of the given size, if any.
+    pub fn avatar_path(&self, size: u32) -> Option<String> {
+        self.avatar_key
+            .as_ref()
+            .map(|key| format!("{}/{}.png", key, size))
+    }
 }
@@ -1,17 +1,33 @@
 use crate::domain::{
     error::DomainError,
     model::{
+        access_token::AccessToken,
+        audit::AuditEntry,
+        identity::ExternalIdentity,
+        media::Media,
         post::{Post, PostWithAuthor},
         user::User,
     },
 };
 use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use std::sync::Arc;
 use uuid::Uuid;
 
+/// Repositories as shared trait objects, the form the HTTP layer works with so
+/// that the backend (SQLite, PostgreSQL, a test double…) is chosen at startup.
+pub type DynPostRepository = Arc<dyn PostRepository + Send + Sync>;
+pub type DynUserRepository = Arc<dyn UserRepository + Send + Sync>;
+pub type DynMediaRepository = Arc<dyn MediaRepository + Send + Sync>;
+pub type DynAccessTokenRepository = Arc<dyn AccessTokenRepository + Send + Sync>;
+pub type DynIdentityRepository = Arc<dyn IdentityRepository + Send + Sync>;
+pub type DynAuditRepository = Arc<dyn AuditRepository + Send + Sync>;
+
 #[async_trait]
 pub trait PostRepository {
     async fn list(&self) -> Result<Vec<PostWithAuthor>, DomainError>;
     async fn find_by_id(&self, id: Uuid) -> Result<Option<PostWithAuthor>, DomainError>;
+    async fn list_by_author(&self, user_id: Uuid) -> Result<Vec<Post>, DomainError>;
     async fn create(&self, new_post: Post) -> Result<Post, DomainError>;
     async fn update(&self, post: Post) -> Result<Post, DomainError>;
     async fn delete(&self, id: Uuid) -> Result<(), DomainError>;
@@ -23,6 +39,279 @@ pub trait UserRepository {
     async fn create(&self, user: User) -> Result<User, DomainError>;
     async fn find_by_id(&self, id: Uuid) -> Result<Option<User>, DomainError>;
     async fn update(&self, user: User) -> Result<User, DomainError>;
+    /// Also deletes the user's media, detached from the posts, along with
+    /// their access tokens and identities.
     async fn delete(&self, id: Uuid) -> Result<(), DomainError>;
     async fn find_by_username(&self, username: &str) -> Result<Option<User>, DomainError>;
+    async fn find_by_email(&self, email: &str) -> Result<Option<User>, DomainError>;
+    /// Number of accounts, erased ones included.
+    async fn count(&self) -> Result<i64, DomainError>;
+    /// Replaces every piece of personal data on the row with the given pseudonym
+    /// and marks the account as erased. Posts keep pointing to the same id.
+    /// In the same transaction, deletes the linked identities, the personal
+    /// access tokens and the media library, detached from the posts.
+    async fn anonymize(
+        &self,
+        id: Uuid,
+        pseudonym: &str,
+        email: &str,
+        erased_at: DateTime<Utc>,
+    ) -> Result<User, DomainError>;
+}
+
+#[async_trait]
+pub trait MediaRepository {
+    async fn create(&self, media: Media) -> Result<Media, DomainError>;
+    async fn find_by_id(&self, id: Uuid) -> Result<Option<Media>, DomainError>;
+    async fn find_by_owner_and_hash(
+        &self,
+        owner_id: Uuid,
+        sha256: &str,
+    ) -> Result<Option<Media>, DomainError>;
+    async fn list_by_owner(&self, owner_id: Uuid) -> Result<Vec<Media>, DomainError>;
+    async fn delete(&self, id: Uuid) -> Result<(), DomainError>;
+    /// Number of media rows, across all users, sharing the same stored blob.
+    async fn count_by_hash(&self, sha256: &str) -> Result<i64, DomainError>;
+    /// Whether a post uses the media as an attachment or as its cover.
+    async fn is_referenced(&self, id: Uuid) -> Result<bool, DomainError>;
+    async fn attach(&self, post_id: Uuid, media_id: Uuid) -> Result<(), DomainError>;
+    async fn detach(&self, post_id: Uuid, media_id: Uuid) -> Result<(), DomainError>;
+    async fn list_for_post(&self, post_id: Uuid) -> Result<Vec<Media>, DomainError>;
+}
+
+#[async_trait]
+pub trait AccessTokenRepository {
+    async fn create(&self, token: AccessToken) -> Result<AccessToken, DomainError>;
+    async fn find_by_hash(&self, token_hash: &str) -> Result<Option<AccessToken>, DomainError>;
+    async fn list_by_user(&self, user_id: Uuid) -> Result<Vec<AccessToken>, DomainError>;
+    /// Marks a token of `user_id` as revoked, keeping the first revocation
+    /// time; `NotFound` when the user has no token with this id.
+    async fn revoke(
+        &self,
+        id: Uuid,
+        user_id: Uuid,
+        revoked_at: DateTime<Utc>,
+    ) -> Result<AccessToken, DomainError>;
+    async fn touch(&self, id: Uuid, used_at: DateTime<Utc>) -> Result<(), DomainError>;
+}
+
+#[async_trait]
+pub trait IdentityRepository {
+    /// Fails when the identity is already linked, to this user or another.
+    async fn create(&self, identity: ExternalIdentity) -> Result<ExternalIdentity, DomainError>;
+    async fn find(
+        &self,
+        issuer: &str,
+        subject: &str,
+    ) -> Result<Option<ExternalIdentity>, DomainError>;
+    async fn list_by_user(&self, user_id: Uuid) -> Result<Vec<ExternalIdentity>, DomainError>;
+}
+
+/// Append-only: entries are never updated nor deleted.
+#[async_trait]
+pub trait AuditRepository {
+    async fn record(&self, entry: AuditEntry) -> Result<AuditEntry, DomainError>;
+    /// The `limit` most recent entries, newest first.
+    async fn list(&self, limit: i64) -> Result<Vec<AuditEntry>, DomainError>;
+    /// Entries of a session, oldest first.
+    async fn list_by_session(&self, session_id: Uuid) -> Result<Vec<AuditEntry>, DomainError>;
+}
+
+#[async_trait]
+impl<T> PostRepository for Arc<T>
+where
+    T: PostRepository + Send + Sync + ?Sized,
+{
+    async fn list(&self) -> Result<Vec<PostWithAuthor>, DomainError> {
+        (**self).list().await
+    }
+
+    async fn find_by_id(&self, id: Uuid) -> Result<Option<PostWithAuthor>, DomainError> {
+        (**self).find_by_id(id).await
+    }
+
+    async fn list_by_author(&self, user_id: Uuid) -> Result<Vec<Post>, DomainError> {
+        (**self).list_by_author(user_id).await
+    }
+
+    async fn create(&self, new_post: Post) -> Result<Post, DomainError> {
+        (**self).create(new_post).await
+    }
+
+    async fn update(&self, post: Post) -> Result<Post, DomainError> {
+        (**self).update(post).await
+    }
+
+    async fn delete(&self, id: Uuid) -> Result<(), DomainError> {
+        (**self).delete(id).await
+    }
+}
+
+#[async_trait]
+impl<T> UserRepository for Arc<T>
+where
+    T: UserRepository + Send + Sync + ?Sized,
+{
+    async fn list(&self) -> Result<Vec<User>, DomainError> {
+        (**self).list().await
+    }
+
+    async fn create(&self, user: User) -> Result<User, DomainError> {
+        (**self).create(user).await
+    }
+
+    async fn find_by_id(&self, id: Uuid) -> Result<Option<User>, DomainError> {
+        (**self).find_by_id(id).await
+    }
+
+    async fn update(&self, user: User) -> Result<User, DomainError> {
+        (**self).update(user).await
+    }
+
+    async fn delete(&self, id: Uuid) -> Result<(), DomainError> {
+        (**self).delete(id).await
+    }
+
+    async fn find_by_username(&self, username: &str) -> Result<Option<User>, DomainError> {
+        (**self).find_by_username(username).await
+    }
+
+    async fn find_by_email(&self, email: &str) -> Result<Option<User>, DomainError> {
+        (**self).find_by_email(email).await
+    }
+
+    async fn count(&self) -> Result<i64, DomainError> {
+        (**self).count().await
+    }
+
+    async fn anonymize(
+        &self,
+        id: Uuid,
+        pseudonym: &str,
+        email: &str,
+        erased_at: DateTime<Utc>,
+    ) -> Result<User, DomainError> {
+        (**self).anonymize(id, pseudonym, email, erased_at).await
+    }
+}
+
+#[async_trait]
+impl<T> MediaRepository for Arc<T>
+where
+    T: MediaRepository + Send + Sync + ?Sized,
+{
+    async fn create(&self, media: Media) -> Result<Media, DomainError> {
+        (**self).create(media).await
+    }
+
+    async fn find_by_id(&self, id: Uuid) -> Result<Option<Media>, DomainError> {
+        (**self).find_by_id(id).await
+    }
+
+    async fn find_by_owner_and_hash(
+        &self,
+        owner_id: Uuid,
+        sha256: &str,
+    ) -> Result<Option<Media>, DomainError> {
+        (**self).find_by_owner_and_hash(owner_id, sha256).await
+    }
+
+    async fn list_by_owner(&self, owner_id: Uuid) -> Result<Vec<Media>, DomainError> {
+        (**self).list_by_owner(owner_id).await
+    }
+
+    async fn delete(&self, id: Uuid) -> Result<(), DomainError> {
+        (**self).delete(id).await
+    }
+
+    async fn count_by_hash(&self, sha256: &str) -> Result<i64, DomainError> {
+        (**self).count_by_hash(sha256).await
+    }
+
+    async fn is_referenced(&self, id: Uuid) -> Result<bool, DomainError> {
+        (**self).is_referenced(id).await
+    }
+
+    async fn attach(&self, post_id: Uuid, media_id: Uuid) -> Result<(), DomainError> {
+        (**self).attach(post_id, media_id).await
+    }
+
+    async fn detach(&self, post_id: Uuid, media_id: Uuid) -> Result<(), DomainError> {
+        (**self).detach(post_id, media_id).await
+    }
+
+    async fn list_for_post(&self, post_id: Uuid) -> Result<Vec<Media>, DomainError> {
+        (**self).list_for_post(post_id).await
+    }
+}
+
+#[async_trait]
+impl<T> AccessTokenRepository for Arc<T>
+where
+    T: AccessTokenRepository + Send + Sync + ?Sized,
+{
+    async fn create(&self, token: AccessToken) -> Result<AccessToken, DomainError> {
+        (**self).create(token).await
+    }
+
+    async fn find_by_hash(&self, token_hash: &str) -> Result<Option<AccessToken>, DomainError> {
+        (**self).find_by_hash(token_hash).await
+    }
+
+    async fn list_by_user(&self, user_id: Uuid) -> Result<Vec<AccessToken>, DomainError> {
+        (**self).list_by_user(user_id).await
+    }
+
+    async fn revoke(
+        &self,
+        id: Uuid,
+        user_id: Uuid,
+        revoked_at: DateTime<Utc>,
+    ) -> Result<AccessToken, DomainError> {
+        (**self).revoke(id, user_id, revoked_at).await
+    }
+
+    async fn touch(&self, id: Uuid, used_at: DateTime<Utc>) -> Result<(), DomainError> {
+        (**self).touch(id, used_at).await
+    }
+}
+
+#[async_trait]
+impl<T> IdentityRepository for Arc<T>
+where
+    T: IdentityRepository + Send + Sync + ?Sized,
+{
+    async fn create(&self, identity: ExternalIdentity) -> Result<ExternalIdentity, DomainError> {
+        (**self).create(identity).await
+    }
+
+    async fn find(
+        &self,
+        issuer: &str,
+        subject: &str,
+    ) -> Result<Option<ExternalIdentity>, DomainError> {
+        (**self).find(issuer, subject).await
+    }
+
+    async fn list_by_user(&self, user_id: Uuid) -> Result<Vec<ExternalIdentity>, DomainError> {
+        (**self).list_by_user(user_id).await
+    }
+}
+
+#[async_trait]
+impl<T> AuditRepository for Arc<T>
+where
+    T: AuditRepository + Send + Sync + ?Sized,
+{
+    async fn record(&self, entry: AuditEntry) -> Result<AuditEntry, DomainError> {
+        (**self).record(entry).await
+    }
+
+    async fn list(&self, limit: i64) -> Result<Vec<AuditEntry>, DomainError> {
+        (**self).list(limit).await
+    }
+
+    async fn list_by_session(&self, session_id: Uuid) -> Result<Vec<AuditEntry>, DomainError> {
+        (**self).list_by_session(session_id).await
+    }
 }
@@ -1,3 +1,6 @@
+use std::fmt;
+
+#[derive(Debug, Clone)]
 pub struct PasswordRequirements {
     pub min_length: usize,
     pub max_length: usize,
@@ -5,6 +8,8 @@ pub struct PasswordRequirements {
     pub require_lowercase: bool,
     pub require_digit: bool,
     pub require_special_char: bool,
+    /// Lowest zxcvbn score (0 to 4) accepted; 0 skips the estimation.
+    pub min_score: u8,
 }
 
 impl Default for PasswordRequirements {
@@ -16,35 +21,104 @@ impl Default for PasswordRequirements {
             require_lowercase: true,
             require_digit: true,
             require_special_char: true,
+            min_score: 0,
+        }
+    }
+}
+
+/// A password rule that is not met.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PasswordViolation {
+    TooShort(usize),
+    TooLong(usize),
+    MissingUppercase,
+    MissingLowercase,
+    MissingDigit,
+    MissingSpecialChar,
+    /// The zxcvbn score is below the required one.
+    TooWeak(u8),
+    /// The password contains the username or the email of its owner.
+    ContainsPersonalInfo,
+    /// The password appears in a list of leaked passwords.
+    Breached,
+}
+
+impl PasswordViolation {
+    pub fn code(&self) -> &'static str {
+        match self {
+            PasswordViolation::TooShort(_) => "password_too_short",
+            PasswordViolation::TooLong(_) => "password_too_long",
+            PasswordViolation::MissingUppercase => "password_missing_uppercase",
+            PasswordViolation::MissingLowercase => "password_missing_lowercase",
+            PasswordViolation::MissingDigit => "password_missing_digit",
+            PasswordViolation::MissingSpecialChar => "password_missing_special_char",
+            PasswordViolation::TooWeak(_) => "password_too_weak",
+            PasswordViolation::ContainsPersonalInfo => "password_contains_personal_info",
+            PasswordViolation::Breached => "password_breached",
         }
     }
 }
 
-pub fn validate_password(password: &str, req: &PasswordRequirements) -> Result<(), String> {
+impl fmt::Display for PasswordViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PasswordViolation::TooShort(min) => {
+                write!(f, "Password must be at least {} characters long", min)
+            }
+            PasswordViolation::TooLong(max) => {
+                write!(f, "Password must be at most {} characters long", max)
+            }
+            PasswordViolation::MissingUppercase => {
+                write!(f, "Password must contain at least one uppercase letter")
+            }
+            PasswordViolation::MissingLowercase => {
+                write!(f, "Password must contain at least one lowercase letter")
+            }
+            PasswordViolation::MissingDigit => {
+                write!(f, "Password must contain at least one digit")
+            }
+            PasswordViolation::MissingSpecialChar => {
+                write!(f, "Password must contain at least one special character")
+            }
+            PasswordViolation::TooWeak(_) => write!(f, "Password is too easy to guess"),
+            PasswordViolation::ContainsPersonalInfo => {
+                write!(f, "Password must not contain the username or the email")
+            }
+            PasswordViolation::Breached => {
+                write!(f, "Password appears in a list of leaked passwords")
+            }
+        }
+    }
+}
+
+/// Every rule of `req` that `password` breaks, in the order they are checked.
+/// `personal` holds what the password must not contain: the username, the
+/// email, ... of its owner.
+pub fn password_violations(
+    password: &str,
+    req: &PasswordRequirements,
+    personal: &[&str],
+) -> Vec<PasswordViolation> {
+    let mut violations = Vec::new();
+
     if password.len() < req.min_length {
-        return Err(format!(
-            "Password must be at least {} characters long",
-            req.min_length
-        ));
+        violations.push(PasswordViolation::TooShort(req.min_length));
     }
 
     if password.len() > req.max_length {
-        return Err(format!(
-            "Password must be at most {} characters long",
-            req.max_length
-        ));
+        violations.push(PasswordViolation::TooLong(req.max_length));
     }
 
     if req.require_uppercase && !password.chars().any(|c| c.is_uppercase()) {
-        return Err("Password must contain at least one uppercase letter".to_string());
+        violations.push(PasswordViolation::MissingUppercase);
     }
 
     if req.require_lowercase && !password.chars().any(|c| c.is_lowercase()) {
-        return Err("Password must contain at least one lowercase letter".to_string());
+        violations.push(PasswordViolation::MissingLowercase);
     }
 
     if req.require_digit && !password.chars().any(|c| c.is_ascii_digit()) {
-        return Err("Password must contain at least one digit".to_string());
+        violations.push(PasswordViolation::MissingDigit);
     }
 
     if req.require_special_char
@@ -52,8 +126,29 @@ pub fn validate_password(password: &str, req: &PasswordRequirements) -> Result<(
             .chars()
             .any(|c| !c.is_alphanumeric() && !c.is_whitespace())
     {
-        return Err("Password must contain at least one special character".to_string());
+        violations.push(PasswordViolation::MissingSpecialChar);
     }
 
-    Ok(())
+    if contains_personal_info(password, personal) {
+        violations.push(PasswordViolation::ContainsPersonalInfo);
+    }
+
+    if req.min_score > 0 && u8::from(zxcvbn::zxcvbn(password, personal).score()) < req.min_score {
+        violations.push(PasswordViolation::TooWeak(req.min_score));
+    }
+
+    violations
+}
+
+/// Case-insensitive; an email also counts through its local part. Values
+/// shorter than 3 characters are ignored, they would match too often.
+fn contains_personal_info(password: &str, personal: &[&str]) -> bool {
+    let password = password.to_lowercase();
+
+    personal
+        .iter()
+        .flat_map(|value| [Some(*value), value.split_once('@').map(|(local, _)| local)])
+        .flatten()
+        .map(str::to_lowercase)
+        .any(|value| value.chars().count() >= 3 && password.contains(&value))
 }
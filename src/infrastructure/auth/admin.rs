@@ -4,19 +4,15 @@ use std::{
 };
 
 use actix_web::{
-    Error, HttpResponse,
+    Error, ResponseError,
     body::{BoxBody, MessageBody},
     dev::{Service, ServiceRequest, ServiceResponse, Transform},
-    http::header::AUTHORIZATION,
-    web,
 };
 use futures_util::future::{LocalBoxFuture, Ready, ready};
-use jsonwebtoken::{Validation, decode};
-use serde_json::json;
 
 use crate::{
     domain::model::user::Role,
-    infrastructure::{auth::Claims, security::keys::Keys},
+    infrastructure::auth::{AuthError, authenticate},
 };
 
 pub struct AdminMiddleware;
@@ -71,38 +67,17 @@ where
     fn call(&self, req: ServiceRequest) -> Self::Future {
         let srv = self.service.clone();
 
-        let keys_opt = req.app_data::<web::Data<Keys>>().cloned();
-        let token_opt = req
-            .headers()
-            .get(AUTHORIZATION)
-            .and_then(|h| h.to_str().ok())
-            .and_then(|s| s.strip_prefix("Bearer ").map(str::to_owned));
-
         Box::pin(async move {
-            let claims = if let (Some(keys), Some(token)) = (keys_opt, token_opt) {
-                match decode::<Claims>(&token, &keys.decoding, &Validation::default()) {
-                    Ok(data) => data.claims,
-                    Err(_) => {
-                        let body = json!({
-                            "error": "Invalid token"
-                        });
-
-                        let resp = HttpResponse::Unauthorized().json(body);
-
-                        return Ok(req.into_response(resp));
-                    }
+            let claims = match authenticate(req.request()).await {
+                Ok(claims) => claims,
+                Err(err) => {
+                    let resp = err.middleware_problem();
+                    return Ok(req.into_response(resp));
                 }
-            } else {
-                let body = json!({ "error": "Missing Authorization header" });
-                let resp = HttpResponse::Unauthorized().json(body);
-                return Ok(req.into_response(resp));
             };
 
             if claims.role != Role::Admin {
-                let body = json!({
-                    "error": "Forbidden: Admin access required"
-                });
-                let resp = HttpResponse::Forbidden().json(body);
+                let resp = AuthError::AdminRequired.error_response();
                 return Ok(req.into_response(resp));
             }
 
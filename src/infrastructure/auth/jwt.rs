@@ -1,19 +1,16 @@
 use futures_util::future::{LocalBoxFuture, Ready, ready};
-use jsonwebtoken::{Validation, decode};
 use std::{
     sync::Arc,
     task::{Context, Poll},
 };
 
 use actix_web::{
-    Error, HttpResponse,
+    Error,
     body::{BoxBody, MessageBody},
     dev::{Service, ServiceRequest, ServiceResponse, Transform},
-    http::header::AUTHORIZATION,
-    web,
 };
 
-use crate::infrastructure::{auth::Claims, security::keys::Keys};
+use crate::infrastructure::auth::authenticate;
 
 pub struct JwtMiddleware;
 
@@ -47,29 +44,12 @@ where
     }
 
     fn call(&self, req: ServiceRequest) -> Self::Future {
-        // Clone keys
-        let keys_opt = req.app_data::<web::Data<Keys>>().cloned();
-
-        // Extract token
-        let token_opt = req
-            .headers()
-            .get(AUTHORIZATION)
-            .and_then(|h| h.to_str().ok())
-            .and_then(|s| s.strip_prefix("Bearer ").map(str::to_owned));
-
         // clone service for async call
         let srv = self.service.clone();
 
         Box::pin(async move {
-            let valid = if let (Some(keys), Some(token)) = (keys_opt, token_opt) {
-                decode::<Claims>(&token, &keys.decoding, &Validation::default()).is_ok()
-            } else {
-                false
-            };
-
-            if !valid {
-                let body = serde_json::json!({"error": "Invalid token"});
-                let resp = HttpResponse::Unauthorized().json(body);
+            if let Err(err) = authenticate(req.request()).await {
+                let resp = err.middleware_problem();
                 return Ok(req.into_response(resp));
             }
 
@@ -1,44 +1,127 @@
 use actix_web::{
-    Error, FromRequest, HttpRequest, HttpResponse, ResponseError,
+    Error, FromRequest, HttpMessage, HttpRequest, HttpResponse, ResponseError,
     dev::Payload,
-    http::{StatusCode, header::AUTHORIZATION},
+    http::{Method, StatusCode, header::AUTHORIZATION},
     web,
 };
 use chrono::{Duration, Utc};
 use core::fmt;
+use futures_util::future::LocalBoxFuture;
 use jsonwebtoken::{Header, errors::Error as JwtError};
 use jsonwebtoken::{Validation, decode, encode};
 use serde::{Deserialize, Serialize};
-use serde_json::json;
-use std::future::{Ready, ready};
 use uuid::Uuid;
 
 use crate::{
-    domain::{error::DomainError, model::user::Role},
+    application::access_token_service::TOKEN_PREFIX,
+    config::Settings,
+    domain::{
+        error::DomainError,
+        model::{access_token::Scope, user::Role},
+        repository::UserRepository,
+    },
     infrastructure::security::keys::Keys,
+    interfaces::api::{
+        error::Problem,
+        state::{DynAccessTokenService, DynImpersonationService},
+    },
 };
 
 pub mod admin;
 pub mod jwt;
+pub mod oidc;
 pub mod password;
+pub mod session;
+pub mod setup;
 
-#[derive(Debug, Serialize, Deserialize)]
+/// Validity of the JWTs of `/api/login`, and of their session cookie.
+pub const TOKEN_LIFETIME: Duration = Duration::hours(24);
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Claims {
     pub sub: String,
     pub exp: usize,
+    /// Issue time, compared with the user's `tokens_valid_after`.
+    #[serde(default)]
+    pub iat: usize,
     pub role: Role,
+    /// What a personal access token grants; `None` for the tokens of
+    /// `/api/login`, which grant everything the role allows.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scopes: Option<Vec<Scope>>,
+    /// The administrator acting as `sub`, on the tokens of
+    /// `/api/users/{id}/impersonate`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub act: Option<Actor>,
+}
+
+/// `act` claim of RFC 8693, along with the impersonation session the token
+/// belongs to.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Actor {
+    pub sub: String,
+    /// Key of the session in the audit log.
+    pub sid: Uuid,
 }
 
 impl Claims {
     pub fn user_id(&self) -> Result<Uuid, DomainError> {
         Uuid::parse_str(&self.sub).map_err(|_| DomainError::InvalidUserId)
     }
+
+    /// Refuses what an impersonated session may not do, e.g. change the
+    /// credentials of the account.
+    pub fn not_impersonated(&self) -> Result<(), DomainError> {
+        match self.act {
+            Some(_) => Err(DomainError::ImpersonationForbidden),
+            None => Ok(()),
+        }
+    }
+
+    /// Refuses personal access tokens, whose scopes could not be carried over.
+    pub fn not_delegated(&self) -> Result<(), DomainError> {
+        match self.scopes {
+            Some(_) => Err(DomainError::ImpersonationForbidden),
+            None => Ok(()),
+        }
+    }
 }
 
 #[derive(Debug)]
 pub enum AuthError {
     MissingAuth,
     InvalidToken,
+    AdminRequired,
+    InsufficientScope,
+    CsrfFailed,
+}
+
+impl AuthError {
+    pub fn code(&self) -> &'static str {
+        match self {
+            AuthError::MissingAuth => "missing_credentials",
+            AuthError::InvalidToken => "invalid_token",
+            AuthError::AdminRequired => "admin_required",
+            AuthError::InsufficientScope => "insufficient_scope",
+            AuthError::CsrfFailed => "csrf_failed",
+        }
+    }
+
+    pub fn problem(&self, status: StatusCode) -> HttpResponse {
+        Problem::new(status, self.code(), &[]).response()
+    }
+
+    /// The middlewares answer 401 to any unusable token, where the `Claims`
+    /// extractor answers 403; a token lacking the scope of the route, or a
+    /// session request without its CSRF token, gets 403 from both.
+    pub fn middleware_problem(&self) -> HttpResponse {
+        match self {
+            AuthError::InsufficientScope | AuthError::CsrfFailed => {
+                self.problem(StatusCode::FORBIDDEN)
+            }
+            _ => self.problem(StatusCode::UNAUTHORIZED),
+        }
+    }
 }
 
 impl fmt::Display for AuthError {
@@ -46,6 +129,9 @@ impl fmt::Display for AuthError {
         let msg = match self {
             AuthError::MissingAuth => "Authorization header is missing",
             AuthError::InvalidToken => "Invalid or expired token",
+            AuthError::AdminRequired => "Admin access required",
+            AuthError::InsufficientScope => "Token scope does not allow this request",
+            AuthError::CsrfFailed => "CSRF token is missing or invalid",
         };
         write!(f, "{}", msg)
     }
@@ -55,56 +141,188 @@ impl ResponseError for AuthError {
     fn status_code(&self) -> StatusCode {
         match self {
             AuthError::MissingAuth => StatusCode::UNAUTHORIZED,
-            AuthError::InvalidToken => StatusCode::FORBIDDEN,
+            AuthError::InvalidToken
+            | AuthError::AdminRequired
+            | AuthError::InsufficientScope
+            | AuthError::CsrfFailed => StatusCode::FORBIDDEN,
         }
     }
 
     fn error_response(&self) -> HttpResponse {
-        let msg = self.to_string();
-        HttpResponse::build(self.status_code()).json(json!({"error": msg}))
+        self.problem(self.status_code())
     }
 }
 
 impl FromRequest for Claims {
     type Error = Error;
-    type Future = Ready<Result<Self, Self::Error>>;
+    type Future = LocalBoxFuture<'static, Result<Self, Self::Error>>;
 
     fn from_request(req: &HttpRequest, _: &mut Payload) -> Self::Future {
-        let keys_data = req
-            .app_data::<web::Data<Keys>>()
-            .map(|k| k.get_ref().clone());
-
-        let token_opt = req
-            .headers()
-            .get(AUTHORIZATION)
-            .and_then(|h| h.to_str().ok())
-            .and_then(|s| s.strip_prefix("Bearer ").map(str::to_owned));
-
-        // Validate the presence of settings and header token
-        let (keys, token) = match (keys_data, token_opt) {
-            (Some(k), Some(t)) => (k, t),
-            _ => return ready(Err(AuthError::MissingAuth.into())),
-        };
+        let req = req.clone();
+
+        Box::pin(async move { authenticate(&req).await.map_err(Error::from) })
+    }
+}
 
-        // Decode and validate the JWT token
-        let res = decode::<Claims>(&token, &keys.decoding, &Validation::default())
-            .map(|data| data.claims)
-            .map_err(|_| AuthError::InvalidToken.into());
+fn bearer_token(req: &HttpRequest) -> Option<&str> {
+    req.headers()
+        .get(AUTHORIZATION)
+        .and_then(|h| h.to_str().ok())
+        .and_then(|s| s.strip_prefix("Bearer "))
+}
+
+/// Decodes the bearer token of the request, or the JWT of its session cookie
+/// when it has no `Authorization` header, then checks against the user it
+/// names that the account still exists and the token was not revoked, and for
+/// an impersonation token that its session is still open.
+///
+/// The outcome is cached in the request extensions, so the middlewares and the
+/// `Claims` extractor of a same request only hit the database once.
+pub async fn authenticate(req: &HttpRequest) -> Result<Claims, AuthError> {
+    if let Some(claims) = req.extensions().get::<Claims>() {
+        return Ok(claims.clone());
+    }
+
+    let claims = match bearer_token(req) {
+        Some(token) if token.starts_with(TOKEN_PREFIX) => access_token_claims(req, token).await?,
+        Some(token) => jwt_claims(req, token)?,
+        None => {
+            let settings = req
+                .app_data::<web::Data<Settings>>()
+                .ok_or(AuthError::MissingAuth)?;
+            jwt_claims(req, &session::session_token(req, settings)?)?
+        }
+    };
+
+    let users = req
+        .app_data::<web::Data<dyn UserRepository + Send + Sync>>()
+        .ok_or(AuthError::InvalidToken)?;
+    let user_id = claims.user_id().map_err(|_| AuthError::InvalidToken)?;
+    let user = users
+        .find_by_id(user_id)
+        .await
+        .map_err(|_| AuthError::InvalidToken)?
+        .ok_or(AuthError::InvalidToken)?;
+
+    if !user.accepts_token(claims.iat as i64) {
+        return Err(AuthError::InvalidToken);
+    }
+    if let Some(actor) = &claims.act {
+        let service = req
+            .app_data::<web::Data<DynImpersonationService>>()
+            .ok_or(AuthError::InvalidToken)?;
+        if !service
+            .accepts(actor, claims.iat as i64)
+            .await
+            .map_err(|_| AuthError::InvalidToken)?
+        {
+            return Err(AuthError::InvalidToken);
+        }
+    }
+
+    // The role of a personal access token is the current one of its owner.
+    let claims = match claims.scopes {
+        Some(_) => Claims {
+            role: user.role,
+            ..claims
+        },
+        None => claims,
+    };
+
+    req.extensions_mut().insert(claims.clone());
+    tracing::Span::current().record("user_id", tracing::field::display(&claims.sub));
+
+    Ok(claims)
+}
+
+fn jwt_claims(req: &HttpRequest, token: &str) -> Result<Claims, AuthError> {
+    let keys = req
+        .app_data::<web::Data<Keys>>()
+        .ok_or(AuthError::MissingAuth)?;
+    decode::<Claims>(token, &keys.decoding, &Validation::default())
+        .map(|data| data.claims)
+        .map_err(|_| AuthError::InvalidToken)
+}
+
+/// Claims of a personal access token, once checked that its scopes cover the
+/// request. Its creation time stands for `iat`, so that revoking every token
+/// of a user revokes these as well.
+async fn access_token_claims(req: &HttpRequest, token: &str) -> Result<Claims, AuthError> {
+    let service = req
+        .app_data::<web::Data<DynAccessTokenService>>()
+        .ok_or(AuthError::InvalidToken)?;
+    let access_token = service
+        .authenticate(token)
+        .await
+        .map_err(|_| AuthError::InvalidToken)?
+        .ok_or(AuthError::InvalidToken)?;
+
+    match required_scope(req.method(), req.path()) {
+        Some(scope) if access_token.scopes.contains(&scope) => {}
+        _ => return Err(AuthError::InsufficientScope),
+    }
+
+    Ok(Claims {
+        sub: access_token.user_id.to_string(),
+        exp: access_token.expires_at.timestamp() as usize,
+        iat: access_token.created_at.timestamp() as usize,
+        // Replaced by the role of the user once it is loaded.
+        role: Role::User,
+        scopes: Some(access_token.scopes),
+        act: None,
+    })
+}
+
+/// Scope a personal access token needs for a route; `None` where these tokens
+/// are never accepted, e.g. to manage the tokens themselves or to start an
+/// impersonation, whose token would not be limited to the scopes.
+pub fn required_scope(method: &Method, path: &str) -> Option<Scope> {
+    let read = matches!(*method, Method::GET | Method::HEAD);
+    let under = |prefix: &str| {
+        path.strip_prefix(prefix)
+            .is_some_and(|rest| rest.is_empty() || rest.starts_with('/'))
+    };
 
-        ready(res)
+    if under("/api/profile/tokens") || path.ends_with("/impersonate") {
+        None
+    } else if under("/api/posts") {
+        Some(if read {
+            Scope::PostsRead
+        } else {
+            Scope::PostsWrite
+        })
+    } else if under("/api/media") {
+        Some(if read {
+            Scope::MediaRead
+        } else {
+            Scope::MediaWrite
+        })
+    } else if under("/api/profile") {
+        Some(if read {
+            Scope::ProfileRead
+        } else {
+            Scope::ProfileWrite
+        })
+    } else if under("/api/users") || under("/api/audit") {
+        Some(Scope::Admin)
+    } else {
+        None
     }
 }
 
 pub fn create_jwt_token(user_id: Uuid, role: Role, keys: &Keys) -> Result<String, JwtError> {
     let exp = Utc::now()
-        .checked_add_signed(Duration::hours(24))
+        .checked_add_signed(TOKEN_LIFETIME)
         .expect("Failed to calculate expiration time")
         .timestamp() as usize;
 
     let claims = Claims {
         sub: user_id.to_string(),
         exp,
+        iat: Utc::now().timestamp() as usize,
         role,
+        scopes: None,
+        act: None,
     };
 
     encode(&Header::default(), &claims, &keys.encoding)
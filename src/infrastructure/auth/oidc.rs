@@ -0,0 +1,274 @@
+use std::time::Duration;
+
+use base64::{Engine, engine::general_purpose::URL_SAFE_NO_PAD};
+use jsonwebtoken::{
+    Algorithm, DecodingKey, Validation, decode, decode_header,
+    jwk::{Jwk, JwkSet},
+};
+use rand::{RngCore, SeedableRng};
+use rand_chacha::ChaCha20Rng;
+use reqwest::Client;
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
+use sha2::{Digest, Sha256};
+use thiserror::Error;
+use tokio::sync::{OnceCell, RwLock};
+use url::Url;
+
+use crate::{config::OidcSettings, infrastructure::security::keys::same_secret};
+
+/// Lifetime of an authorization started by `/api/oidc/login`.
+pub const LOGIN_TIMEOUT: chrono::Duration = chrono::Duration::minutes(10);
+
+#[derive(Error, Debug)]
+pub enum OidcError {
+    #[error("Identity provider error: {0}")]
+    Provider(String),
+    #[error("Invalid ID token: {0}")]
+    InvalidIdToken(String),
+}
+
+/// Endpoints of the provider, from its discovery document.
+#[derive(Debug, Clone, Deserialize)]
+struct ProviderMetadata {
+    issuer: String,
+    authorization_endpoint: String,
+    token_endpoint: String,
+    jwks_uri: String,
+}
+
+#[derive(Deserialize)]
+struct TokenResponse {
+    id_token: String,
+}
+
+/// What the callback needs to finish an authorization, kept by the browser
+/// in a signed cookie between `/api/oidc/login` and `/api/oidc/callback`.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PendingLogin {
+    pub state: String,
+    pub nonce: String,
+    /// PKCE `code_verifier`.
+    pub verifier: String,
+    pub exp: usize,
+}
+
+/// Claims of a verified ID token used to find or create the account.
+#[derive(Debug, Clone, Deserialize)]
+pub struct IdTokenClaims {
+    pub sub: String,
+    pub email: Option<String>,
+    #[serde(default)]
+    pub email_verified: bool,
+    pub preferred_username: Option<String>,
+    pub name: Option<String>,
+    pub given_name: Option<String>,
+    pub family_name: Option<String>,
+    nonce: Option<String>,
+    azp: Option<String>,
+}
+
+/// Relying party of the provider of `OidcSettings`. The discovery document
+/// is fetched once, the signing keys again whenever a token names an unknown
+/// one.
+pub struct OidcClient {
+    settings: OidcSettings,
+    http: Client,
+    provider: OnceCell<ProviderMetadata>,
+    jwks: RwLock<JwkSet>,
+}
+
+impl OidcClient {
+    pub fn new(settings: OidcSettings) -> Self {
+        let http = Client::builder()
+            .timeout(Duration::from_secs(10))
+            .build()
+            .expect("HTTP client without custom TLS settings");
+
+        Self {
+            settings,
+            http,
+            provider: OnceCell::new(),
+            jwks: RwLock::new(JwkSet { keys: Vec::new() }),
+        }
+    }
+
+    pub fn issuer(&self) -> &str {
+        &self.settings.issuer
+    }
+
+    pub fn auto_provision(&self) -> bool {
+        self.settings.auto_provision
+    }
+
+    /// URL of the provider to send the browser to, along with what the
+    /// callback will need to check its answer.
+    pub async fn authorization_url(&self) -> Result<(Url, PendingLogin), OidcError> {
+        let provider = self.provider().await?;
+        let pending = PendingLogin {
+            state: random_token(),
+            nonce: random_token(),
+            verifier: random_token(),
+            exp: (chrono::Utc::now() + LOGIN_TIMEOUT).timestamp() as usize,
+        };
+        let challenge = URL_SAFE_NO_PAD.encode(Sha256::digest(pending.verifier.as_bytes()));
+
+        let mut url = Url::parse(&provider.authorization_endpoint)
+            .map_err(|e| OidcError::Provider(format!("invalid authorization_endpoint: {e}")))?;
+        url.query_pairs_mut()
+            .append_pair("response_type", "code")
+            .append_pair("client_id", &self.settings.client_id)
+            .append_pair("redirect_uri", &self.settings.redirect_uri)
+            .append_pair("scope", &self.settings.scopes)
+            .append_pair("state", &pending.state)
+            .append_pair("nonce", &pending.nonce)
+            .append_pair("code_challenge", &challenge)
+            .append_pair("code_challenge_method", "S256");
+
+        Ok((url, pending))
+    }
+
+    /// Redeems the authorization code, then returns the claims of the ID
+    /// token once its signature, issuer, audience, expiry and nonce are
+    /// checked.
+    pub async fn exchange(
+        &self,
+        code: &str,
+        pending: &PendingLogin,
+    ) -> Result<IdTokenClaims, OidcError> {
+        let provider = self.provider().await?;
+
+        let form = [
+            ("grant_type", "authorization_code"),
+            ("code", code),
+            ("redirect_uri", &self.settings.redirect_uri),
+            ("code_verifier", &pending.verifier),
+            ("client_id", &self.settings.client_id),
+        ];
+        let mut request = self.http.post(&provider.token_endpoint).form(&form);
+        if let Some(secret) = &self.settings.client_secret {
+            request = request.basic_auth(&self.settings.client_id, Some(secret));
+        }
+        let response: TokenResponse = fetch_json(request).await?;
+
+        self.verify_id_token(provider, &response.id_token, &pending.nonce)
+            .await
+    }
+
+    async fn verify_id_token(
+        &self,
+        provider: &ProviderMetadata,
+        id_token: &str,
+        nonce: &str,
+    ) -> Result<IdTokenClaims, OidcError> {
+        let header =
+            decode_header(id_token).map_err(|e| OidcError::InvalidIdToken(e.to_string()))?;
+        // Symmetric algorithms would take the key from the token's own JWKS
+        // entry; only public key signatures are accepted.
+        if matches!(
+            header.alg,
+            Algorithm::HS256 | Algorithm::HS384 | Algorithm::HS512
+        ) {
+            return Err(OidcError::InvalidIdToken(format!(
+                "unsupported algorithm {:?}",
+                header.alg
+            )));
+        }
+        let jwk = self.jwk(provider, header.kid.as_deref()).await?;
+        let key =
+            DecodingKey::from_jwk(&jwk).map_err(|e| OidcError::InvalidIdToken(e.to_string()))?;
+
+        let mut validation = Validation::new(header.alg);
+        validation.set_issuer(&[&provider.issuer]);
+        validation.set_audience(&[&self.settings.client_id]);
+        validation.set_required_spec_claims(&["exp", "iss", "aud", "sub"]);
+        let claims = decode::<IdTokenClaims>(id_token, &key, &validation)
+            .map_err(|e| OidcError::InvalidIdToken(e.to_string()))?
+            .claims;
+
+        if !claims
+            .nonce
+            .as_deref()
+            .is_some_and(|claimed| same_secret(nonce, claimed))
+        {
+            return Err(OidcError::InvalidIdToken("nonce mismatch".to_string()));
+        }
+        if claims
+            .azp
+            .as_deref()
+            .is_some_and(|azp| azp != self.settings.client_id)
+        {
+            return Err(OidcError::InvalidIdToken("azp mismatch".to_string()));
+        }
+
+        Ok(claims)
+    }
+
+    async fn provider(&self) -> Result<&ProviderMetadata, OidcError> {
+        self.provider
+            .get_or_try_init(|| async {
+                let url = format!(
+                    "{}/.well-known/openid-configuration",
+                    self.settings.issuer.trim_end_matches('/')
+                );
+                let provider: ProviderMetadata = fetch_json(self.http.get(url)).await?;
+                if provider.issuer.trim_end_matches('/')
+                    != self.settings.issuer.trim_end_matches('/')
+                {
+                    return Err(OidcError::Provider(format!(
+                        "discovery document is for issuer {}",
+                        provider.issuer
+                    )));
+                }
+
+                Ok(provider)
+            })
+            .await
+    }
+
+    /// Key of the provider named `kid`, or its only key when the token names
+    /// none.
+    async fn jwk(&self, provider: &ProviderMetadata, kid: Option<&str>) -> Result<Jwk, OidcError> {
+        let find = |jwks: &JwkSet| match kid {
+            Some(kid) => jwks.find(kid).cloned(),
+            None if jwks.keys.len() == 1 => jwks.keys.first().cloned(),
+            None => None,
+        };
+
+        if let Some(jwk) = find(&*self.jwks.read().await) {
+            return Ok(jwk);
+        }
+
+        // The provider may have rotated its keys since they were fetched.
+        let jwks: JwkSet = fetch_json(self.http.get(&provider.jwks_uri)).await?;
+        let jwk = find(&jwks);
+        *self.jwks.write().await = jwks;
+
+        jwk.ok_or_else(|| OidcError::InvalidIdToken("unknown signing key".to_string()))
+    }
+}
+
+async fn fetch_json<T: DeserializeOwned>(request: reqwest::RequestBuilder) -> Result<T, OidcError> {
+    let response = request
+        .send()
+        .await
+        .map_err(|e| OidcError::Provider(e.to_string()))?;
+    let status = response.status();
+    let url = response.url().clone();
+    let body = response
+        .bytes()
+        .await
+        .map_err(|e| OidcError::Provider(e.to_string()))?;
+    if !status.is_success() {
+        return Err(OidcError::Provider(format!("{url} answered {status}")));
+    }
+
+    serde_json::from_slice(&body).map_err(|e| OidcError::Provider(format!("{url}: {e}")))
+}
+
+/// 256 random bits, base64url encoded: the shape RFC 7636 gives the PKCE
+/// verifier, also used for `state` and `nonce`.
+fn random_token() -> String {
+    let mut bytes = [0u8; 32];
+    ChaCha20Rng::from_entropy().fill_bytes(&mut bytes);
+    URL_SAFE_NO_PAD.encode(bytes)
+}
@@ -1,30 +1,124 @@
-use argon2::password_hash::{Error, PasswordHash, SaltString, rand_core::RngCore};
-use argon2::{Argon2, PasswordHasher, PasswordVerifier};
+use std::sync::Arc;
+
+use anyhow::anyhow;
+use argon2::password_hash::{Error, PasswordHash, SaltString};
+use argon2::{
+    Algorithm, Argon2, KeyId, Params, ParamsBuilder, PasswordHasher, PasswordVerifier, Version,
+};
 use rand::SeedableRng;
 use rand_chacha::ChaCha20Rng;
 
-pub fn hash_password(password: &str) -> Result<String, Error> {
-    // Generate a random salt
-    let mut rng = ChaCha20Rng::from_entropy();
-    let mut salt_bytes = [0u8; 32];
-    rng.fill_bytes(&mut salt_bytes);
-
-    let salt = SaltString::generate(&mut rng);
+use crate::config::Argon2Settings;
 
-    // hash the password
-    let argon2 = Argon2::default();
-    let password_hash = argon2
-        .hash_password(password.as_bytes(), &salt)?
-        .to_string();
+/// `keyid` recorded in the hashes computed with the pepper, so that the ones
+/// created before it was configured can still be verified, then upgraded.
+const PEPPER_KEY_ID: &[u8] = b"pepper";
 
-    Ok(password_hash)
+/// Argon2id with the parameters and the optional pepper of `Argon2Settings`.
+#[derive(Clone)]
+pub struct PasswordHashing {
+    params: Params,
+    peppered_params: Params,
+    pepper: Option<Arc<[u8]>>,
 }
 
-pub fn verify_password(password: &str, hash: &str) -> Result<bool, Error> {
-    let parsed_hash = PasswordHash::new(hash)?;
-    let argon2 = Argon2::default();
+impl PasswordHashing {
+    pub fn new(settings: &Argon2Settings) -> anyhow::Result<Self> {
+        let mut builder = ParamsBuilder::new();
+        builder
+            .m_cost(settings.memory_kib)
+            .t_cost(settings.iterations)
+            .p_cost(settings.parallelism);
+        let params = builder
+            .build()
+            .map_err(|e| anyhow!("Invalid ARGON2__* settings: {e}"))?;
+        let peppered_params = builder
+            .keyid(KeyId::new(PEPPER_KEY_ID).map_err(|e| anyhow!("{e}"))?)
+            .build()
+            .map_err(|e| anyhow!("Invalid ARGON2__* settings: {e}"))?;
+
+        let pepper = settings
+            .pepper
+            .as_ref()
+            .map(|pepper| Arc::from(pepper.as_bytes()));
+        if let Some(pepper) = &pepper {
+            Argon2::new_with_secret(pepper, Algorithm::Argon2id, Version::V0x13, params.clone())
+                .map_err(|e| anyhow!("Invalid ARGON2__PEPPER: {e}"))?;
+        }
+
+        Ok(Self {
+            params,
+            peppered_params,
+            pepper,
+        })
+    }
+
+    pub fn hash(&self, password: &str) -> Result<String, Error> {
+        let salt = SaltString::generate(&mut ChaCha20Rng::from_entropy());
+
+        let params = match &self.pepper {
+            Some(_) => self.peppered_params.clone(),
+            None => self.params.clone(),
+        };
+        let password_hash = self
+            .argon2(self.pepper.as_ref(), params)?
+            .hash_password(password.as_bytes(), &salt)?
+            .to_string();
+
+        Ok(password_hash)
+    }
+
+    /// Hashes carrying the pepper `keyid` are verified with the pepper, the
+    /// other ones without it.
+    pub fn verify(&self, password: &str, hash: &str) -> Result<bool, Error> {
+        let parsed_hash = PasswordHash::new(hash)?;
+        let secret = match Params::try_from(&parsed_hash)?.keyid() {
+            [] => None,
+            PEPPER_KEY_ID => Some(self.pepper.as_ref().ok_or(Error::Crypto)?),
+            _ => return Err(Error::Crypto),
+        };
+
+        // The cost parameters of the hash itself are used for the check.
+        Ok(self
+            .argon2(secret, Params::default())?
+            .verify_password(password.as_bytes(), &parsed_hash)
+            .is_ok())
+    }
+
+    /// Whether `hash` was computed with another algorithm, other costs or
+    /// without the current pepper, and should be replaced after a login.
+    pub fn needs_rehash(&self, hash: &str) -> bool {
+        let Ok(parsed_hash) = PasswordHash::new(hash) else {
+            return true;
+        };
+        let Ok(params) = Params::try_from(&parsed_hash) else {
+            return true;
+        };
+        let expected = if self.pepper.is_some() {
+            &self.peppered_params
+        } else {
+            &self.params
+        };
+
+        parsed_hash.algorithm != Algorithm::Argon2id.ident()
+            || parsed_hash.version != Some(Version::V0x13.into())
+            || params.m_cost() != expected.m_cost()
+            || params.t_cost() != expected.t_cost()
+            || params.p_cost() != expected.p_cost()
+            || params.keyid() != expected.keyid()
+    }
 
-    Ok(argon2
-        .verify_password(password.as_bytes(), &parsed_hash)
-        .is_ok())
+    fn argon2<'a>(
+        &self,
+        secret: Option<&'a Arc<[u8]>>,
+        params: Params,
+    ) -> Result<Argon2<'a>, Error> {
+        match secret {
+            Some(secret) => {
+                Argon2::new_with_secret(secret, Algorithm::Argon2id, Version::V0x13, params)
+                    .map_err(Error::from)
+            }
+            None => Ok(Argon2::new(Algorithm::Argon2id, Version::V0x13, params)),
+        }
+    }
 }
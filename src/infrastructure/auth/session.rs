@@ -0,0 +1,122 @@
+use actix_web::{
+    HttpRequest,
+    cookie::{Cookie, SameSite, time},
+    http::Method,
+};
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+
+use crate::{
+    config::{SameSitePolicy, SessionSettings, Settings},
+    infrastructure::{
+        auth::{AuthError, TOKEN_LIFETIME},
+        security::keys::same_secret,
+    },
+};
+
+pub const CSRF_HEADER: &str = "x-csrf-token";
+
+/// The CSRF token of a session is derived from its JWT, so that a cookie
+/// planted by a sibling subdomain cannot be paired with a token of the
+/// attacker's choosing.
+pub fn csrf_token(secret: &str, session: &str) -> String {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts keys of any size");
+    mac.update(b"csrf:");
+    mac.update(session.as_bytes());
+    hex::encode(mac.finalize().into_bytes())
+}
+
+/// The session cookie and the CSRF cookie set by `/api/login`.
+pub fn session_cookies(settings: &Settings, token: &str) -> [Cookie<'static>; 2] {
+    let session = &settings.session;
+    let max_age = time::Duration::seconds(TOKEN_LIFETIME.num_seconds());
+
+    [
+        cookie(
+            session,
+            session.cookie_name.clone(),
+            token.to_string(),
+            true,
+        )
+        .max_age(max_age)
+        .finish(),
+        cookie(
+            session,
+            session.csrf_cookie_name.clone(),
+            csrf_token(&settings.jwt_secret, token),
+            false,
+        )
+        .max_age(max_age)
+        .finish(),
+    ]
+}
+
+/// Expired copies of the session cookies, which make browsers drop them.
+pub fn removal_cookies(session: &SessionSettings) -> [Cookie<'static>; 2] {
+    [
+        cookie(session, session.cookie_name.clone(), String::new(), true)
+            .max_age(time::Duration::ZERO)
+            .expires(time::OffsetDateTime::UNIX_EPOCH)
+            .finish(),
+        cookie(
+            session,
+            session.csrf_cookie_name.clone(),
+            String::new(),
+            false,
+        )
+        .max_age(time::Duration::ZERO)
+        .expires(time::OffsetDateTime::UNIX_EPOCH)
+        .finish(),
+    ]
+}
+
+fn cookie(
+    session: &SessionSettings,
+    name: String,
+    value: String,
+    http_only: bool,
+) -> actix_web::cookie::CookieBuilder<'static> {
+    let same_site = match session.same_site {
+        SameSitePolicy::Strict => SameSite::Strict,
+        SameSitePolicy::Lax => SameSite::Lax,
+        SameSitePolicy::None => SameSite::None,
+    };
+    let builder = Cookie::build(name, value)
+        .path("/")
+        .secure(true)
+        .http_only(http_only)
+        .same_site(same_site);
+
+    match &session.domain {
+        Some(domain) => builder.domain(domain.clone()),
+        None => builder,
+    }
+}
+
+/// JWT of the session cookie, for a request without `Authorization` header.
+/// Requests other than `GET`, `HEAD` and `OPTIONS` must also carry the CSRF
+/// token of the session in `X-CSRF-Token`.
+pub fn session_token(req: &HttpRequest, settings: &Settings) -> Result<String, AuthError> {
+    if !settings.session.enabled {
+        return Err(AuthError::MissingAuth);
+    }
+    let token = req
+        .cookie(&settings.session.cookie_name)
+        .map(|cookie| cookie.value().to_string())
+        .filter(|token| !token.is_empty())
+        .ok_or(AuthError::MissingAuth)?;
+
+    if !matches!(*req.method(), Method::GET | Method::HEAD | Method::OPTIONS) {
+        let candidate = req
+            .headers()
+            .get(CSRF_HEADER)
+            .and_then(|h| h.to_str().ok())
+            .ok_or(AuthError::CsrfFailed)?;
+        if !same_secret(&csrf_token(&settings.jwt_secret, &token), candidate) {
+            return Err(AuthError::CsrfFailed);
+        }
+    }
+
+    Ok(token)
+}
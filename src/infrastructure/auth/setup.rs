@@ -0,0 +1,51 @@
+use std::sync::Arc;
+
+use rand::{RngCore, SeedableRng};
+use rand_chacha::ChaCha20Rng;
+use tokio::sync::{Mutex, MutexGuard};
+
+use crate::infrastructure::security::keys::same_secret;
+
+/// One-time token unlocking `/api/setup`, issued at startup when the database
+/// has no user and no `ADMIN__*` settings were given.
+#[derive(Clone, Default)]
+pub struct SetupToken {
+    pending: Arc<Mutex<Option<String>>>,
+}
+
+impl SetupToken {
+    /// Generates a new token, replacing any previous one.
+    pub async fn issue(&self) -> String {
+        let mut bytes = [0u8; 32];
+        ChaCha20Rng::from_entropy().fill_bytes(&mut bytes);
+        let token = hex::encode(bytes);
+
+        *self.pending.lock().await = Some(token.clone());
+
+        token
+    }
+
+    /// Holds the token for the whole setup so that two concurrent requests
+    /// cannot both create an administrator.
+    pub async fn lock(&self) -> SetupGuard<'_> {
+        SetupGuard(self.pending.lock().await)
+    }
+}
+
+pub struct SetupGuard<'a>(MutexGuard<'a, Option<String>>);
+
+impl SetupGuard<'_> {
+    pub fn is_pending(&self) -> bool {
+        self.0.is_some()
+    }
+
+    pub fn matches(&self, candidate: &str) -> bool {
+        self.0
+            .as_deref()
+            .is_some_and(|token| same_secret(token, candidate))
+    }
+
+    pub fn consume(&mut self) {
+        *self.0 = None;
+    }
+}
@@ -1,12 +1,206 @@
-use anyhow::Result;
-use sqlx::{SqlitePool, migrate::Migrator};
+use std::{collections::HashSet, sync::Arc};
 
-static MIGRATOR: Migrator = sqlx::migrate!("./migrations");
+use anyhow::{Result, bail};
+use sqlx::{
+    PgPool, SqlitePool,
+    migrate::{Migrate, Migrator},
+};
 
-pub async fn init_db(database_url: &str) -> Result<SqlitePool> {
-    let pool = SqlitePool::connect(database_url).await?;
+use crate::{
+    domain::repository::{
+        DynAccessTokenRepository, DynAuditRepository, DynIdentityRepository, DynMediaRepository,
+        DynPostRepository, DynUserRepository,
+    },
+    infrastructure::persistence::{
+        postgres::{
+            access_token_repo::PgAccessTokenRepo, audit_repo::PgAuditRepo,
+            identity_repo::PgIdentityRepo, media_repo::PgMediaRepo, post_repo::PgPostRepo,
+            user_repo::PgUserRepo,
+        },
+        sqlite::{
+            access_token_repo::SqliteAccessTokenRepo, audit_repo::SqliteAuditRepo,
+            identity_repo::SqliteIdentityRepo, media_repo::SqliteMediaRepo,
+            post_repo::SqlitePostRepo, user_repo::SqliteUserRepo,
+        },
+    },
+};
 
-    MIGRATOR.run(&pool).await?;
+#[cfg(feature = "in-memory")]
+use crate::infrastructure::persistence::memory::database::InMemoryDatabase;
 
-    Ok(pool)
+static SQLITE_MIGRATOR: Migrator = sqlx::migrate!("./migrations/sqlite");
+static POSTGRES_MIGRATOR: Migrator = sqlx::migrate!("./migrations/postgres");
+
+/// Connection pool of the backend selected by the `DATABASE_URL` scheme.
+#[derive(Clone)]
+pub enum Database {
+    Sqlite(SqlitePool),
+    Postgres(PgPool),
+    #[cfg(feature = "in-memory")]
+    Memory(InMemoryDatabase),
+}
+
+pub struct PoolStats {
+    pub size: u32,
+    pub idle: usize,
+}
+
+#[derive(Clone)]
+pub struct Repositories {
+    pub posts: DynPostRepository,
+    pub users: DynUserRepository,
+    pub media: DynMediaRepository,
+    pub access_tokens: DynAccessTokenRepository,
+    pub identities: DynIdentityRepository,
+    pub audit_log: DynAuditRepository,
+}
+
+impl Database {
+    pub fn repositories(&self) -> Repositories {
+        match self {
+            Database::Sqlite(pool) => Repositories {
+                posts: Arc::new(SqlitePostRepo::new(pool.clone())),
+                users: Arc::new(SqliteUserRepo::new(pool.clone())),
+                media: Arc::new(SqliteMediaRepo::new(pool.clone())),
+                access_tokens: Arc::new(SqliteAccessTokenRepo::new(pool.clone())),
+                identities: Arc::new(SqliteIdentityRepo::new(pool.clone())),
+                audit_log: Arc::new(SqliteAuditRepo::new(pool.clone())),
+            },
+            Database::Postgres(pool) => Repositories {
+                posts: Arc::new(PgPostRepo::new(pool.clone())),
+                users: Arc::new(PgUserRepo::new(pool.clone())),
+                media: Arc::new(PgMediaRepo::new(pool.clone())),
+                access_tokens: Arc::new(PgAccessTokenRepo::new(pool.clone())),
+                identities: Arc::new(PgIdentityRepo::new(pool.clone())),
+                audit_log: Arc::new(PgAuditRepo::new(pool.clone())),
+            },
+            #[cfg(feature = "in-memory")]
+            Database::Memory(db) => db.repositories(),
+        }
+    }
+
+    /// Connection counts of the pool, `None` for backends without one.
+    pub fn pool_stats(&self) -> Option<PoolStats> {
+        match self {
+            Database::Sqlite(pool) => Some(PoolStats {
+                size: pool.size(),
+                idle: pool.num_idle(),
+            }),
+            Database::Postgres(pool) => Some(PoolStats {
+                size: pool.size(),
+                idle: pool.num_idle(),
+            }),
+            #[cfg(feature = "in-memory")]
+            Database::Memory(_) => None,
+        }
+    }
+
+    pub async fn ping(&self) -> Result<()> {
+        match self {
+            Database::Sqlite(pool) => {
+                sqlx::query("SELECT 1").execute(pool).await?;
+            }
+            Database::Postgres(pool) => {
+                sqlx::query("SELECT 1").execute(pool).await?;
+            }
+            #[cfg(feature = "in-memory")]
+            Database::Memory(_) => {}
+        }
+
+        Ok(())
+    }
+
+    /// Number of migrations shipped with the binary that the database has not
+    /// applied yet.
+    pub async fn pending_migrations(&self) -> Result<usize> {
+        Ok(self.pending().await?.len())
+    }
+
+    /// Applies the pending migrations; returns them as `version description`.
+    pub async fn migrate(&self) -> Result<Vec<String>> {
+        let pending = self.pending().await?;
+        match self {
+            Database::Sqlite(pool) => SQLITE_MIGRATOR.run(pool).await?,
+            Database::Postgres(pool) => POSTGRES_MIGRATOR.run(pool).await?,
+            #[cfg(feature = "in-memory")]
+            Database::Memory(_) => {}
+        }
+
+        Ok(pending)
+    }
+
+    /// Read only: a database that was never migrated has no migrations table
+    /// yet, and all the migrations are pending.
+    async fn pending(&self) -> Result<Vec<String>> {
+        match self {
+            Database::Sqlite(pool) => {
+                let mut conn = pool.acquire().await?;
+                let migrated: bool = sqlx::query_scalar(
+                    "SELECT EXISTS (SELECT 1 FROM sqlite_master \
+                     WHERE type = 'table' AND name = '_sqlx_migrations')",
+                )
+                .fetch_one(&mut *conn)
+                .await?;
+                pending(&SQLITE_MIGRATOR, migrated, &mut *conn).await
+            }
+            Database::Postgres(pool) => {
+                let mut conn = pool.acquire().await?;
+                let migrated: bool =
+                    sqlx::query_scalar("SELECT to_regclass('_sqlx_migrations') IS NOT NULL")
+                        .fetch_one(&mut *conn)
+                        .await?;
+                pending(&POSTGRES_MIGRATOR, migrated, &mut *conn).await
+            }
+            #[cfg(feature = "in-memory")]
+            Database::Memory(_) => Ok(Vec::new()),
+        }
+    }
+}
+
+async fn pending(
+    migrator: &Migrator,
+    migrated: bool,
+    conn: &mut impl Migrate,
+) -> Result<Vec<String>> {
+    let applied: HashSet<i64> = if migrated {
+        conn.list_applied_migrations()
+            .await?
+            .into_iter()
+            .map(|m| m.version)
+            .collect()
+    } else {
+        HashSet::new()
+    };
+
+    Ok(migrator
+        .iter()
+        .filter(|m| !m.migration_type.is_down_migration() && !applied.contains(&m.version))
+        .map(|m| format!("{} {}", m.version, m.description))
+        .collect())
+}
+
+/// Opens the database without touching its schema; see `Database::migrate`.
+pub async fn connect_db(database_url: &str) -> Result<Database> {
+    #[cfg(feature = "in-memory")]
+    if database_url.starts_with("memory:") {
+        return Ok(Database::Memory(InMemoryDatabase::new()));
+    }
+
+    let db = if database_url.starts_with("sqlite:") {
+        Database::Sqlite(SqlitePool::connect(database_url).await?)
+    } else if database_url.starts_with("postgres:") || database_url.starts_with("postgresql:") {
+        Database::Postgres(PgPool::connect(database_url).await?)
+    } else {
+        bail!("Unsupported DATABASE_URL scheme, expected sqlite: or postgres:");
+    };
+
+    Ok(db)
+}
+
+/// Opens the database and applies the pending migrations.
+pub async fn init_db(database_url: &str) -> Result<Database> {
+    let db = connect_db(database_url).await?;
+    db.migrate().await?;
+
+    Ok(db)
 }
@@ -0,0 +1,139 @@
+use std::io::{Cursor, Write};
+
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use uuid::Uuid;
+use zip::{ZipWriter, write::SimpleFileOptions};
+
+use crate::{
+    application::gdpr_service::UserDataExport,
+    domain::model::{post::Post, user::Role},
+};
+
+#[derive(Serialize)]
+struct AccountRecord<'a> {
+    id: Uuid,
+    username: &'a str,
+    email: &'a str,
+    first_name: Option<&'a str>,
+    last_name: Option<&'a str>,
+    display_name: Option<&'a str>,
+    bio: Option<&'a str>,
+    website: Option<&'a str>,
+    role: &'a Role,
+    created_at: DateTime<Utc>,
+    updated_at: Option<DateTime<Utc>>,
+    exported_at: DateTime<Utc>,
+}
+
+/// Builds the zip archive handed to the user: machine readable JSON files plus
+/// one Markdown document per post.
+pub fn build_archive(export: &UserDataExport) -> Result<Vec<u8>> {
+    let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+    let options = SimpleFileOptions::default();
+
+    let account = AccountRecord {
+        id: export.user.id,
+        username: &export.user.username,
+        email: &export.user.email,
+        first_name: export.user.first_name.as_deref(),
+        last_name: export.user.last_name.as_deref(),
+        display_name: export.user.display_name.as_deref(),
+        bio: export.user.bio.as_deref(),
+        website: export.user.website.as_deref(),
+        role: &export.user.role,
+        created_at: export.user.created_at,
+        updated_at: export.user.updated_at,
+        exported_at: export.exported_at,
+    };
+
+    zip.start_file("account.json", options)?;
+    zip.write_all(&serde_json::to_vec_pretty(&account)?)?;
+
+    zip.start_file("posts.json", options)?;
+    zip.write_all(&serde_json::to_vec_pretty(&export.posts)?)?;
+
+    zip.start_file("README.md", options)?;
+    zip.write_all(account_markdown(export).as_bytes())?;
+
+    for post in &export.posts {
+        let name = format!(
+            "posts/{}-{}.md",
+            post.created_at.format("%Y-%m-%d"),
+            post.id.simple()
+        );
+        zip.start_file(name, options)?;
+        zip.write_all(post_markdown(post).as_bytes())?;
+    }
+
+    Ok(zip.finish()?.into_inner())
+}
+
+fn account_markdown(export: &UserDataExport) -> String {
+    let user = &export.user;
+    let mut md = String::new();
+
+    md.push_str("# Personal data export\n\n");
+    md.push_str(&format!(
+        "Exported at {}\n\n",
+        export.exported_at.to_rfc3339()
+    ));
+    md.push_str("## Account\n\n");
+    md.push_str(&format!("- **Id**: {}\n", user.id));
+    md.push_str(&format!("- **Username**: {}\n", user.username));
+    md.push_str(&format!("- **Email**: {}\n", user.email));
+    for (label, value) in [
+        ("First name", &user.first_name),
+        ("Last name", &user.last_name),
+        ("Display name", &user.display_name),
+        ("Website", &user.website),
+    ] {
+        if let Some(value) = value {
+            md.push_str(&format!("- **{}**: {}\n", label, value));
+        }
+    }
+    md.push_str(&format!("- **Role**: {}\n", user.role));
+    md.push_str(&format!(
+        "- **Created at**: {}\n",
+        user.created_at.to_rfc3339()
+    ));
+    if let Some(updated_at) = user.updated_at {
+        md.push_str(&format!("- **Updated at**: {}\n", updated_at.to_rfc3339()));
+    }
+
+    if let Some(bio) = &user.bio {
+        md.push_str(&format!("\n## Bio\n\n{}\n", bio));
+    }
+
+    md.push_str(&format!("\n## Posts ({})\n\n", export.posts.len()));
+    for post in &export.posts {
+        md.push_str(&format!(
+            "- [{}](posts/{}-{}.md)\n",
+            post.title,
+            post.created_at.format("%Y-%m-%d"),
+            post.id.simple()
+        ));
+    }
+
+    md
+}
+
+fn post_markdown(post: &Post) -> String {
+    let mut md = String::new();
+
+    md.push_str("---\n");
+    md.push_str(&format!("id: {}\n", post.id));
+    md.push_str(&format!("title: {:?}\n", post.title));
+    md.push_str(&format!("published: {}\n", post.published));
+    md.push_str(&format!("created_at: {}\n", post.created_at.to_rfc3339()));
+    if let Some(updated_at) = post.updated_at {
+        md.push_str(&format!("updated_at: {}\n", updated_at.to_rfc3339()));
+    }
+    md.push_str("---\n\n");
+    md.push_str(&format!("# {}\n\n", post.title));
+    md.push_str(&post.content);
+    md.push('\n');
+
+    md
+}
@@ -0,0 +1,58 @@
+use std::collections::BTreeMap;
+
+use serde::Serialize;
+
+use crate::infrastructure::{db::Database, storage::BlobStore};
+
+/// Written then deleted by every readiness probe.
+const STORAGE_PROBE_KEY: &str = "health/probe";
+
+#[derive(Debug, Serialize)]
+pub struct Readiness {
+    pub ready: bool,
+    /// `"ok"`, `"pending"` or `"unavailable"`, per dependency. The reason of
+    /// a failure is logged, not returned.
+    pub checks: BTreeMap<&'static str, &'static str>,
+}
+
+/// Checks that the database answers and is fully migrated, and that the blob
+/// store accepts writes.
+pub async fn check_readiness(db: &Database, store: &dyn BlobStore) -> Readiness {
+    let mut checks = BTreeMap::new();
+
+    checks.insert("database", outcome("database", db.ping().await));
+
+    let migrations = match db.pending_migrations().await {
+        Ok(0) => "ok",
+        Ok(n) => {
+            log::warn!("Readiness check: {} pending migration(s)", n);
+            "pending"
+        }
+        Err(e) => outcome("migrations", Err(e)),
+    };
+    checks.insert("migrations", migrations);
+
+    let storage = match store
+        .put(STORAGE_PROBE_KEY, b"ok".to_vec(), "text/plain")
+        .await
+    {
+        Ok(()) => store.delete(STORAGE_PROBE_KEY).await,
+        Err(e) => Err(e),
+    };
+    checks.insert("storage", outcome("storage", storage));
+
+    Readiness {
+        ready: checks.values().all(|c| *c == "ok"),
+        checks,
+    }
+}
+
+fn outcome(check: &str, result: anyhow::Result<()>) -> &'static str {
+    match result {
+        Ok(()) => "ok",
+        Err(e) => {
+            log::warn!("Readiness check {} failed: {:#}", check, e);
+            "unavailable"
+        }
+    }
+}
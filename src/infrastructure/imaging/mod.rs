@@ -0,0 +1,61 @@
+use std::io::Cursor;
+
+use image::{DynamicImage, ImageDecoder, ImageFormat, ImageReader, Limits, imageops::FilterType};
+
+use crate::domain::error::DomainError;
+
+const ACCEPTED_FORMATS: [ImageFormat; 4] = [
+    ImageFormat::Png,
+    ImageFormat::Jpeg,
+    ImageFormat::Gif,
+    ImageFormat::WebP,
+];
+
+const MAX_DIMENSION: u32 = 8192;
+
+/// Decodes an uploaded image and renders one square PNG per requested size.
+///
+/// The format is sniffed from the magic bytes, never from the client supplied
+/// content type. Re-encoding from raw pixels drops every metadata chunk (EXIF,
+/// GPS, XMP…); the EXIF orientation is applied beforehand so the result is
+/// displayed the right way up.
+pub fn square_thumbnails(bytes: &[u8], sizes: &[u32]) -> Result<Vec<(u32, Vec<u8>)>, DomainError> {
+    let format = image::guess_format(bytes)
+        .map_err(|_| DomainError::UnsupportedMediaType("unknown".to_string()))?;
+
+    if !ACCEPTED_FORMATS.contains(&format) {
+        return Err(DomainError::UnsupportedMediaType(
+            format.to_mime_type().to_string(),
+        ));
+    }
+
+    let mut limits = Limits::default();
+    limits.max_image_width = Some(MAX_DIMENSION);
+    limits.max_image_height = Some(MAX_DIMENSION);
+
+    let mut reader = ImageReader::with_format(Cursor::new(bytes), format);
+    reader.limits(limits);
+
+    let mut decoder = reader
+        .into_decoder()
+        .map_err(|e| DomainError::InvalidImage(e.to_string()))?;
+    let orientation = decoder
+        .orientation()
+        .map_err(|e| DomainError::InvalidImage(e.to_string()))?;
+    let mut image = DynamicImage::from_decoder(decoder)
+        .map_err(|e| DomainError::InvalidImage(e.to_string()))?;
+    image.apply_orientation(orientation);
+
+    sizes
+        .iter()
+        .map(|&size| {
+            let thumbnail = image.resize_to_fill(size, size, FilterType::Lanczos3);
+            let mut out = Cursor::new(Vec::new());
+            DynamicImage::ImageRgba8(thumbnail.to_rgba8())
+                .write_to(&mut out, ImageFormat::Png)
+                .map_err(|e| DomainError::InvalidImage(e.to_string()))?;
+
+            Ok((size, out.into_inner()))
+        })
+        .collect()
+}
@@ -0,0 +1,82 @@
+use anyhow::Result;
+use opentelemetry::{global, trace::TracerProvider};
+use opentelemetry_otlp::{SpanExporter, WithExportConfig};
+use opentelemetry_sdk::{
+    Resource,
+    propagation::TraceContextPropagator,
+    trace::{Sampler, SdkTracerProvider},
+};
+use tracing_subscriber::{EnvFilter, fmt, layer::SubscriberExt, util::SubscriberInitExt};
+
+use crate::{config::TracingSettings, infrastructure::logging::redact::RedactingWriter};
+
+pub mod redact;
+pub mod request_id;
+
+/// Keeps the span exporter alive; `shutdown` flushes the spans not sent yet.
+pub struct Telemetry {
+    provider: Option<SdkTracerProvider>,
+}
+
+impl Telemetry {
+    pub fn shutdown(self) -> Result<()> {
+        if let Some(provider) = self.provider {
+            provider.shutdown()?;
+        }
+
+        Ok(())
+    }
+}
+
+/// JSON lines on stdout, filtered by `RUST_LOG` (`info` by default). Events of
+/// the `log` crate (actix, SQLx…) are forwarded, and every line goes through
+/// the redaction of `redact`. Spans are also exported over OTLP when
+/// `TRACING__OTLP_ENDPOINT` is set.
+pub fn init_logging(settings: &TracingSettings) -> Result<Telemetry> {
+    global::set_text_map_propagator(TraceContextPropagator::new());
+
+    let provider = settings
+        .otlp_endpoint
+        .as_deref()
+        .map(|endpoint| otlp_provider(endpoint, settings))
+        .transpose()?;
+    let otel = provider.as_ref().map(|provider| {
+        tracing_opentelemetry::layer().with_tracer(provider.tracer(settings.service_name.clone()))
+    });
+
+    tracing_subscriber::registry()
+        .with(EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info")))
+        .with(
+            fmt::layer()
+                .json()
+                .with_current_span(false)
+                .with_span_list(true)
+                .with_writer(|| RedactingWriter::new(std::io::stdout())),
+        )
+        .with(otel)
+        .try_init()?;
+
+    Ok(Telemetry { provider })
+}
+
+fn otlp_provider(endpoint: &str, settings: &TracingSettings) -> Result<SdkTracerProvider> {
+    let exporter = SpanExporter::builder()
+        .with_http()
+        .with_endpoint(format!("{}/v1/traces", endpoint.trim_end_matches('/')))
+        .build()?;
+
+    Ok(SdkTracerProvider::builder()
+        .with_batch_exporter(exporter)
+        .with_sampler(sampler(settings.sample_ratio))
+        .with_resource(
+            Resource::builder()
+                .with_service_name(settings.service_name.clone())
+                .build(),
+        )
+        .build())
+}
+
+/// Head sampling on new traces, the parent's decision otherwise.
+pub fn sampler(ratio: f64) -> Sampler {
+    Sampler::ParentBased(Box::new(Sampler::TraceIdRatioBased(ratio)))
+}
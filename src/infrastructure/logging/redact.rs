@@ -0,0 +1,101 @@
+use std::io::{self, Write};
+
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde_json::Value;
+
+pub const REDACTED: &str = "[REDACTED]";
+
+/// Substrings marking a field name as sensitive, compared in lowercase.
+const SENSITIVE_KEYS: [&str; 6] = [
+    "password",
+    "token",
+    "secret",
+    "authorization",
+    "cookie",
+    "pepper",
+];
+
+/// `password: "…"`, `token=…`, `"secret": "…"` or `Bearer …` inside free text,
+/// e.g. a `Debug` rendering of a DTO embedded in a message.
+static INLINE_SECRET: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(
+        r#"(?i)((?:[a-z_]*(?:password|token|secret|authorization|cookie|pepper)[a-z_]*)"?\s*[:=]\s*(?:Some\()?)("(?:[^"\\]|\\.)*"|[^\s,})]+)|(Bearer\s+)[A-Za-z0-9._~+/=-]+"#,
+    )
+    .expect("valid redaction pattern")
+});
+
+/// Redacts one log line: values of sensitive keys when the line is JSON, and
+/// inline `key: value` pairs in every string.
+pub fn redact_line(line: &str) -> String {
+    match serde_json::from_str::<Value>(line) {
+        Ok(mut value) => {
+            redact_value(&mut value);
+            value.to_string()
+        }
+        Err(_) => redact_text(line),
+    }
+}
+
+fn redact_value(value: &mut Value) {
+    match value {
+        Value::Object(map) => {
+            for (key, field) in map.iter_mut() {
+                if is_sensitive(key) {
+                    *field = Value::String(REDACTED.to_string());
+                } else {
+                    redact_value(field);
+                }
+            }
+        }
+        Value::Array(items) => items.iter_mut().for_each(redact_value),
+        Value::String(text) => *text = redact_text(text),
+        _ => {}
+    }
+}
+
+fn is_sensitive(key: &str) -> bool {
+    let key = key.to_ascii_lowercase();
+    SENSITIVE_KEYS.iter().any(|s| key.contains(s))
+}
+
+fn redact_text(text: &str) -> String {
+    INLINE_SECRET
+        .replace_all(text, |caps: &regex::Captures| match caps.get(1) {
+            Some(prefix) => format!("{}{}", prefix.as_str(), REDACTED),
+            None => format!("{}{}", &caps[3], REDACTED),
+        })
+        .into_owned()
+}
+
+/// Writer handed to the subscriber: each event arrives as one complete buffer
+/// of newline-terminated lines.
+pub struct RedactingWriter<W> {
+    inner: W,
+}
+
+impl<W> RedactingWriter<W> {
+    pub fn new(inner: W) -> Self {
+        Self { inner }
+    }
+}
+
+impl<W: Write> Write for RedactingWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let text = String::from_utf8_lossy(buf);
+
+        for line in text.split_inclusive('\n') {
+            let (content, newline) = match line.strip_suffix('\n') {
+                Some(content) => (content, "\n"),
+                None => (line, ""),
+            };
+            write!(self.inner, "{}{}", redact_line(content), newline)?;
+        }
+
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
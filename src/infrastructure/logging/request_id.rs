@@ -0,0 +1,163 @@
+use std::{rc::Rc, time::Instant};
+
+use actix_web::{
+    Error, HttpMessage,
+    dev::{Service, ServiceRequest, ServiceResponse, Transform},
+    http::header::{HeaderMap, HeaderName, HeaderValue},
+};
+use futures_util::future::{LocalBoxFuture, Ready, ready};
+use opentelemetry::{global, propagation::Extractor};
+use tracing::{Instrument, field};
+use tracing_opentelemetry::OpenTelemetrySpanExt;
+use uuid::Uuid;
+
+pub static X_REQUEST_ID: HeaderName = HeaderName::from_static("x-request-id");
+
+tokio::task_local! {
+    static REQUEST_ID: String;
+    static REQUEST_PATH: String;
+}
+
+/// Id of the request being handled, for code without access to the request
+/// such as `ApiError::error_response`.
+pub fn current_request_id() -> Option<String> {
+    REQUEST_ID.try_with(|id| id.clone()).ok()
+}
+
+/// Path of the request being handled, the `instance` of problem documents.
+pub fn current_request_path() -> Option<String> {
+    REQUEST_PATH.try_with(|path| path.clone()).ok()
+}
+
+/// Reads the W3C `traceparent`/`tracestate` headers of the request.
+struct HeaderExtractor<'a>(&'a HeaderMap);
+
+impl Extractor for HeaderExtractor<'_> {
+    fn get(&self, key: &str) -> Option<&str> {
+        self.0.get(key).and_then(|value| value.to_str().ok())
+    }
+
+    fn keys(&self) -> Vec<&str> {
+        self.0.keys().map(HeaderName::as_str).collect()
+    }
+}
+
+/// Stored in the request extensions.
+#[derive(Debug, Clone)]
+pub struct RequestId(pub String);
+
+/// Incoming ids are kept when they look like an id, so that a proxy or a client
+/// can correlate its own logs; anything else is replaced.
+fn is_valid(id: &str) -> bool {
+    !id.is_empty()
+        && id.len() <= 128
+        && id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+}
+
+/// Gives every request an `X-Request-Id`, runs it inside an `http_request`
+/// span carrying that id (and the user id once authenticated) and child of the
+/// incoming `traceparent`, and logs one line per completed request except for
+/// the paths in `quiet`.
+pub struct RequestTracing {
+    quiet: &'static [&'static str],
+}
+
+impl RequestTracing {
+    pub fn new(quiet: &'static [&'static str]) -> Self {
+        Self { quiet }
+    }
+}
+
+impl<S, B> Transform<S, ServiceRequest> for RequestTracing
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type InitError = ();
+    type Transform = RequestTracingMiddleware<S>;
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(RequestTracingMiddleware {
+            service: Rc::new(service),
+            quiet: self.quiet,
+        }))
+    }
+}
+
+pub struct RequestTracingMiddleware<S> {
+    service: Rc<S>,
+    quiet: &'static [&'static str],
+}
+
+impl<S, B> Service<ServiceRequest> for RequestTracingMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(
+        &self,
+        ctx: &mut core::task::Context<'_>,
+    ) -> std::task::Poll<Result<(), Self::Error>> {
+        self.service.poll_ready(ctx)
+    }
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let srv = self.service.clone();
+        let quiet = self.quiet.contains(&req.path());
+
+        let id = req
+            .headers()
+            .get(&X_REQUEST_ID)
+            .and_then(|h| h.to_str().ok())
+            .filter(|id| is_valid(id))
+            .map(str::to_string)
+            .unwrap_or_else(|| Uuid::new_v4().to_string());
+        req.extensions_mut().insert(RequestId(id.clone()));
+        let scoped_id = id.clone();
+        let scoped_path = req.path().to_string();
+
+        let span = tracing::info_span!(
+            "http_request",
+            request_id = %id,
+            method = %req.method(),
+            path = %req.path(),
+            user_id = field::Empty,
+        );
+        // Continue the caller's trace, if any; a no-op when no exporter is set up.
+        let parent = global::get_text_map_propagator(|propagator| {
+            propagator.extract(&HeaderExtractor(req.headers()))
+        });
+        let _ = span.set_parent(parent);
+
+        let fut = async move {
+            let started = Instant::now();
+            let mut res = srv.call(req).await?;
+
+            if let Ok(value) = HeaderValue::from_str(&id) {
+                res.headers_mut().insert(X_REQUEST_ID.clone(), value);
+            }
+
+            if !quiet {
+                tracing::info!(
+                    status = res.status().as_u16(),
+                    latency_ms = started.elapsed().as_millis() as u64,
+                    "request completed"
+                );
+            }
+
+            Ok(res)
+        }
+        .instrument(span);
+
+        Box::pin(REQUEST_ID.scope(scoped_id, REQUEST_PATH.scope(scoped_path, fut)))
+    }
+}
@@ -0,0 +1,201 @@
+use std::{rc::Rc, time::Instant};
+
+use actix_web::{
+    Error,
+    dev::{Service, ServiceRequest, ServiceResponse, Transform},
+};
+use futures_util::future::{LocalBoxFuture, Ready, ready};
+use prometheus::{
+    Encoder, HistogramOpts, HistogramVec, IntCounter, IntCounterVec, IntGauge, Opts, Registry,
+    TextEncoder,
+};
+
+use crate::infrastructure::db::Database;
+
+/// Prometheus collectors of one application instance, rendered by `/metrics`.
+#[derive(Clone)]
+pub struct Metrics {
+    registry: Registry,
+    http_requests: IntCounterVec,
+    http_duration: HistogramVec,
+    logins: IntCounterVec,
+    pub posts_created: IntCounter,
+    pub users_registered: IntCounter,
+    db_connections: IntGauge,
+    db_idle_connections: IntGauge,
+}
+
+impl Metrics {
+    pub fn new() -> Self {
+        let registry =
+            Registry::new_custom(Some("blog_api".to_string()), None).expect("valid metrics prefix");
+
+        let http_requests = IntCounterVec::new(
+            Opts::new("http_requests_total", "HTTP requests handled"),
+            &["method", "route", "status"],
+        )
+        .expect("valid metric");
+        let http_duration = HistogramVec::new(
+            HistogramOpts::new(
+                "http_request_duration_seconds",
+                "Time spent handling HTTP requests",
+            ),
+            &["method", "route", "status"],
+        )
+        .expect("valid metric");
+        let logins = IntCounterVec::new(
+            Opts::new("logins_total", "Login attempts by outcome"),
+            &["outcome"],
+        )
+        .expect("valid metric");
+        let posts_created =
+            IntCounter::new("posts_created_total", "Posts created").expect("valid metric");
+        let users_registered =
+            IntCounter::new("users_registered_total", "Accounts created").expect("valid metric");
+        let db_connections = IntGauge::new(
+            "db_pool_connections",
+            "Connections currently opened by the pool",
+        )
+        .expect("valid metric");
+        let db_idle_connections =
+            IntGauge::new("db_pool_idle_connections", "Opened connections not in use")
+                .expect("valid metric");
+
+        for collector in [
+            Box::new(http_requests.clone()) as Box<dyn prometheus::core::Collector>,
+            Box::new(http_duration.clone()),
+            Box::new(logins.clone()),
+            Box::new(posts_created.clone()),
+            Box::new(users_registered.clone()),
+            Box::new(db_connections.clone()),
+            Box::new(db_idle_connections.clone()),
+        ] {
+            registry
+                .register(collector)
+                .expect("metric registered once");
+        }
+
+        Self {
+            registry,
+            http_requests,
+            http_duration,
+            logins,
+            posts_created,
+            users_registered,
+            db_connections,
+            db_idle_connections,
+        }
+    }
+
+    pub fn record_login(&self, success: bool) {
+        let outcome = if success { "success" } else { "failure" };
+        self.logins.with_label_values(&[outcome]).inc();
+    }
+
+    /// Text exposition format, with the pool gauges sampled at scrape time.
+    pub fn render(&self, db: &Database) -> String {
+        if let Some(stats) = db.pool_stats() {
+            self.db_connections.set(stats.size as i64);
+            self.db_idle_connections.set(stats.idle as i64);
+        }
+
+        let mut buffer = Vec::new();
+        TextEncoder::new()
+            .encode(&self.registry.gather(), &mut buffer)
+            .expect("text encoding of gathered metrics");
+
+        String::from_utf8(buffer).expect("text exposition format is UTF-8")
+    }
+}
+
+impl Default for Metrics {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Counts requests and measures their latency, labelled with the route pattern
+/// (`/api/posts/{id}`) rather than the path to keep the number of series bounded.
+pub struct RequestMetrics {
+    metrics: Metrics,
+}
+
+impl RequestMetrics {
+    pub fn new(metrics: Metrics) -> Self {
+        Self { metrics }
+    }
+}
+
+impl<S, B> Transform<S, ServiceRequest> for RequestMetrics
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type InitError = ();
+    type Transform = RequestMetricsMiddleware<S>;
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(RequestMetricsMiddleware {
+            service: Rc::new(service),
+            metrics: self.metrics.clone(),
+        }))
+    }
+}
+
+pub struct RequestMetricsMiddleware<S> {
+    service: Rc<S>,
+    metrics: Metrics,
+}
+
+impl<S, B> Service<ServiceRequest> for RequestMetricsMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(
+        &self,
+        ctx: &mut core::task::Context<'_>,
+    ) -> std::task::Poll<Result<(), Self::Error>> {
+        self.service.poll_ready(ctx)
+    }
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let srv = self.service.clone();
+        let metrics = self.metrics.clone();
+        let method = req.method().to_string();
+
+        Box::pin(async move {
+            let started = Instant::now();
+            let res = srv.call(req).await;
+
+            let (route, status) = match &res {
+                Ok(res) => (
+                    res.request()
+                        .match_pattern()
+                        .unwrap_or_else(|| "unmatched".to_string()),
+                    res.status().as_u16().to_string(),
+                ),
+                Err(err) => (
+                    "unmatched".to_string(),
+                    err.as_response_error().status_code().as_u16().to_string(),
+                ),
+            };
+            let labels = [method.as_str(), route.as_str(), status.as_str()];
+
+            metrics.http_requests.with_label_values(&labels).inc();
+            metrics
+                .http_duration
+                .with_label_values(&labels)
+                .observe(started.elapsed().as_secs_f64());
+
+            res
+        })
+    }
+}
@@ -0,0 +1,96 @@
+use crate::{
+    domain::{
+        error::DomainError, model::access_token::AccessToken, repository::AccessTokenRepository,
+    },
+    infrastructure::persistence::memory::database::InMemoryDatabase,
+};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use std::cmp::Reverse;
+use uuid::Uuid;
+
+#[derive(Clone)]
+pub struct InMemoryAccessTokenRepo {
+    db: InMemoryDatabase,
+}
+
+impl InMemoryAccessTokenRepo {
+    pub fn new(db: InMemoryDatabase) -> Self {
+        Self { db }
+    }
+}
+
+#[async_trait]
+impl AccessTokenRepository for InMemoryAccessTokenRepo {
+    async fn create(&self, token: AccessToken) -> Result<AccessToken, DomainError> {
+        let mut tables = self.db.write();
+
+        // Primary key, UNIQUE (token_hash) and the user foreign key.
+        if tables
+            .access_tokens
+            .iter()
+            .any(|t| t.id == token.id || t.token_hash == token.token_hash)
+            || !tables.users.iter().any(|u| u.id == token.user_id)
+        {
+            return Err(DomainError::InternalError);
+        }
+        tables.access_tokens.push(token.clone());
+
+        Ok(token)
+    }
+
+    async fn find_by_hash(&self, token_hash: &str) -> Result<Option<AccessToken>, DomainError> {
+        Ok(self
+            .db
+            .read()
+            .access_tokens
+            .iter()
+            .find(|t| t.token_hash == token_hash)
+            .cloned())
+    }
+
+    async fn list_by_user(&self, user_id: Uuid) -> Result<Vec<AccessToken>, DomainError> {
+        let mut tokens: Vec<AccessToken> = self
+            .db
+            .read()
+            .access_tokens
+            .iter()
+            .filter(|t| t.user_id == user_id)
+            .cloned()
+            .collect();
+        tokens.sort_by_key(|t| Reverse(t.created_at));
+
+        Ok(tokens)
+    }
+
+    async fn revoke(
+        &self,
+        id: Uuid,
+        user_id: Uuid,
+        revoked_at: DateTime<Utc>,
+    ) -> Result<AccessToken, DomainError> {
+        let mut tables = self.db.write();
+        let token = tables
+            .access_tokens
+            .iter_mut()
+            .find(|t| t.id == id && t.user_id == user_id)
+            .ok_or(DomainError::NotFound)?;
+        token.revoked_at.get_or_insert(revoked_at);
+
+        Ok(token.clone())
+    }
+
+    async fn touch(&self, id: Uuid, used_at: DateTime<Utc>) -> Result<(), DomainError> {
+        if let Some(token) = self
+            .db
+            .write()
+            .access_tokens
+            .iter_mut()
+            .find(|t| t.id == id)
+        {
+            token.last_used_at = Some(used_at);
+        }
+
+        Ok(())
+    }
+}
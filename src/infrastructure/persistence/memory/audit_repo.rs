@@ -0,0 +1,54 @@
+use crate::{
+    domain::{error::DomainError, model::audit::AuditEntry, repository::AuditRepository},
+    infrastructure::persistence::memory::database::InMemoryDatabase,
+};
+use async_trait::async_trait;
+use std::cmp::Reverse;
+use uuid::Uuid;
+
+#[derive(Clone)]
+pub struct InMemoryAuditRepo {
+    db: InMemoryDatabase,
+}
+
+impl InMemoryAuditRepo {
+    pub fn new(db: InMemoryDatabase) -> Self {
+        Self { db }
+    }
+}
+
+#[async_trait]
+impl AuditRepository for InMemoryAuditRepo {
+    async fn record(&self, entry: AuditEntry) -> Result<AuditEntry, DomainError> {
+        let mut tables = self.db.write();
+
+        if tables.audit_log.iter().any(|e| e.id == entry.id) {
+            return Err(DomainError::InternalError);
+        }
+        tables.audit_log.push(entry.clone());
+
+        Ok(entry)
+    }
+
+    async fn list(&self, limit: i64) -> Result<Vec<AuditEntry>, DomainError> {
+        let mut entries = self.db.read().audit_log.clone();
+        entries.sort_by_key(|e| Reverse(e.created_at));
+        entries.truncate(limit.max(0) as usize);
+
+        Ok(entries)
+    }
+
+    async fn list_by_session(&self, session_id: Uuid) -> Result<Vec<AuditEntry>, DomainError> {
+        let mut entries: Vec<AuditEntry> = self
+            .db
+            .read()
+            .audit_log
+            .iter()
+            .filter(|e| e.session_id == Some(session_id))
+            .cloned()
+            .collect();
+        entries.sort_by_key(|e| e.created_at);
+
+        Ok(entries)
+    }
+}
@@ -0,0 +1,68 @@
+use std::sync::{Arc, RwLock, RwLockReadGuard, RwLockWriteGuard};
+
+use uuid::Uuid;
+
+use crate::{
+    domain::model::{
+        access_token::AccessToken, audit::AuditEntry, identity::ExternalIdentity, media::Media,
+        post::Post, user::User,
+    },
+    infrastructure::{
+        db::Repositories,
+        persistence::memory::{
+            access_token_repo::InMemoryAccessTokenRepo, audit_repo::InMemoryAuditRepo,
+            identity_repo::InMemoryIdentityRepo, media_repo::InMemoryMediaRepo,
+            post_repo::InMemoryPostRepo, user_repo::InMemoryUserRepo,
+        },
+    },
+};
+
+pub(crate) struct PostMedia {
+    pub post_id: Uuid,
+    pub media_id: Uuid,
+    pub position: i64,
+}
+
+/// Rows of every table, kept in insertion order like an SQL heap.
+#[derive(Default)]
+pub(crate) struct Tables {
+    pub users: Vec<User>,
+    pub posts: Vec<Post>,
+    pub media: Vec<Media>,
+    pub post_media: Vec<PostMedia>,
+    pub access_tokens: Vec<AccessToken>,
+    pub identities: Vec<ExternalIdentity>,
+    pub audit_log: Vec<AuditEntry>,
+}
+
+/// A database living in the process memory, shared by the in-memory
+/// repositories so that joins and foreign keys behave as with SQLite.
+#[derive(Clone, Default)]
+pub struct InMemoryDatabase {
+    tables: Arc<RwLock<Tables>>,
+}
+
+impl InMemoryDatabase {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn repositories(&self) -> Repositories {
+        Repositories {
+            posts: Arc::new(InMemoryPostRepo::new(self.clone())),
+            users: Arc::new(InMemoryUserRepo::new(self.clone())),
+            media: Arc::new(InMemoryMediaRepo::new(self.clone())),
+            access_tokens: Arc::new(InMemoryAccessTokenRepo::new(self.clone())),
+            identities: Arc::new(InMemoryIdentityRepo::new(self.clone())),
+            audit_log: Arc::new(InMemoryAuditRepo::new(self.clone())),
+        }
+    }
+
+    pub(crate) fn read(&self) -> RwLockReadGuard<'_, Tables> {
+        self.tables.read().unwrap_or_else(|e| e.into_inner())
+    }
+
+    pub(crate) fn write(&self) -> RwLockWriteGuard<'_, Tables> {
+        self.tables.write().unwrap_or_else(|e| e.into_inner())
+    }
+}
@@ -0,0 +1,67 @@
+use crate::{
+    domain::{
+        error::DomainError, model::identity::ExternalIdentity, repository::IdentityRepository,
+    },
+    infrastructure::persistence::memory::database::InMemoryDatabase,
+};
+use async_trait::async_trait;
+use uuid::Uuid;
+
+#[derive(Clone)]
+pub struct InMemoryIdentityRepo {
+    db: InMemoryDatabase,
+}
+
+impl InMemoryIdentityRepo {
+    pub fn new(db: InMemoryDatabase) -> Self {
+        Self { db }
+    }
+}
+
+#[async_trait]
+impl IdentityRepository for InMemoryIdentityRepo {
+    async fn create(&self, identity: ExternalIdentity) -> Result<ExternalIdentity, DomainError> {
+        let mut tables = self.db.write();
+
+        // Primary key (issuer, subject) and the user foreign key.
+        if tables
+            .identities
+            .iter()
+            .any(|i| i.issuer == identity.issuer && i.subject == identity.subject)
+            || !tables.users.iter().any(|u| u.id == identity.user_id)
+        {
+            return Err(DomainError::InternalError);
+        }
+        tables.identities.push(identity.clone());
+
+        Ok(identity)
+    }
+
+    async fn find(
+        &self,
+        issuer: &str,
+        subject: &str,
+    ) -> Result<Option<ExternalIdentity>, DomainError> {
+        Ok(self
+            .db
+            .read()
+            .identities
+            .iter()
+            .find(|i| i.issuer == issuer && i.subject == subject)
+            .cloned())
+    }
+
+    async fn list_by_user(&self, user_id: Uuid) -> Result<Vec<ExternalIdentity>, DomainError> {
+        let mut identities: Vec<ExternalIdentity> = self
+            .db
+            .read()
+            .identities
+            .iter()
+            .filter(|i| i.user_id == user_id)
+            .cloned()
+            .collect();
+        identities.sort_by_key(|i| i.created_at);
+
+        Ok(identities)
+    }
+}
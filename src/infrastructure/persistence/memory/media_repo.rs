@@ -0,0 +1,169 @@
+use crate::{
+    domain::{error::DomainError, model::media::Media, repository::MediaRepository},
+    infrastructure::persistence::memory::database::{InMemoryDatabase, PostMedia},
+};
+use async_trait::async_trait;
+use std::cmp::Reverse;
+use uuid::Uuid;
+
+#[derive(Clone)]
+pub struct InMemoryMediaRepo {
+    db: InMemoryDatabase,
+}
+
+impl InMemoryMediaRepo {
+    pub fn new(db: InMemoryDatabase) -> Self {
+        Self { db }
+    }
+}
+
+#[async_trait]
+impl MediaRepository for InMemoryMediaRepo {
+    async fn create(&self, media: Media) -> Result<Media, DomainError> {
+        let mut tables = self.db.write();
+
+        // Primary key, UNIQUE (owner_id, sha256) and the owner foreign key.
+        if tables
+            .media
+            .iter()
+            .any(|m| m.id == media.id || (m.owner_id == media.owner_id && m.sha256 == media.sha256))
+            || !tables.users.iter().any(|u| u.id == media.owner_id)
+        {
+            return Err(DomainError::InternalError);
+        }
+        tables.media.push(media.clone());
+
+        Ok(media)
+    }
+
+    async fn find_by_id(&self, id: Uuid) -> Result<Option<Media>, DomainError> {
+        Ok(self.db.read().media.iter().find(|m| m.id == id).cloned())
+    }
+
+    async fn find_by_owner_and_hash(
+        &self,
+        owner_id: Uuid,
+        sha256: &str,
+    ) -> Result<Option<Media>, DomainError> {
+        Ok(self
+            .db
+            .read()
+            .media
+            .iter()
+            .find(|m| m.owner_id == owner_id && m.sha256 == sha256)
+            .cloned())
+    }
+
+    async fn list_by_owner(&self, owner_id: Uuid) -> Result<Vec<Media>, DomainError> {
+        let mut media: Vec<Media> = self
+            .db
+            .read()
+            .media
+            .iter()
+            .filter(|m| m.owner_id == owner_id)
+            .cloned()
+            .collect();
+        media.sort_by_key(|row| Reverse(row.created_at));
+
+        Ok(media)
+    }
+
+    async fn delete(&self, id: Uuid) -> Result<(), DomainError> {
+        let mut tables = self.db.write();
+
+        if !tables.media.iter().any(|m| m.id == id) {
+            return Err(DomainError::NotFound);
+        }
+        // post_media.media_id is ON DELETE RESTRICT, posts.cover_media_id is
+        // ON DELETE SET NULL.
+        if tables.post_media.iter().any(|pm| pm.media_id == id) {
+            return Err(DomainError::InternalError);
+        }
+        for post in tables.posts.iter_mut() {
+            if post.cover_media_id == Some(id) {
+                post.cover_media_id = None;
+            }
+        }
+        tables.media.retain(|m| m.id != id);
+
+        Ok(())
+    }
+
+    async fn count_by_hash(&self, sha256: &str) -> Result<i64, DomainError> {
+        Ok(self
+            .db
+            .read()
+            .media
+            .iter()
+            .filter(|m| m.sha256 == sha256)
+            .count() as i64)
+    }
+
+    async fn is_referenced(&self, id: Uuid) -> Result<bool, DomainError> {
+        let tables = self.db.read();
+
+        Ok(tables.post_media.iter().any(|pm| pm.media_id == id)
+            || tables.posts.iter().any(|p| p.cover_media_id == Some(id)))
+    }
+
+    async fn attach(&self, post_id: Uuid, media_id: Uuid) -> Result<(), DomainError> {
+        let mut tables = self.db.write();
+
+        if !tables.posts.iter().any(|p| p.id == post_id)
+            || !tables.media.iter().any(|m| m.id == media_id)
+        {
+            return Err(DomainError::InternalError);
+        }
+        if tables
+            .post_media
+            .iter()
+            .any(|pm| pm.post_id == post_id && pm.media_id == media_id)
+        {
+            return Ok(());
+        }
+
+        let position = tables
+            .post_media
+            .iter()
+            .filter(|pm| pm.post_id == post_id)
+            .map(|pm| pm.position)
+            .max()
+            .map_or(0, |max| max + 1);
+        tables.post_media.push(PostMedia {
+            post_id,
+            media_id,
+            position,
+        });
+
+        Ok(())
+    }
+
+    async fn detach(&self, post_id: Uuid, media_id: Uuid) -> Result<(), DomainError> {
+        let mut tables = self.db.write();
+        let before = tables.post_media.len();
+        tables
+            .post_media
+            .retain(|pm| !(pm.post_id == post_id && pm.media_id == media_id));
+
+        if tables.post_media.len() == before {
+            Err(DomainError::NotFound)
+        } else {
+            Ok(())
+        }
+    }
+
+    async fn list_for_post(&self, post_id: Uuid) -> Result<Vec<Media>, DomainError> {
+        let tables = self.db.read();
+        let mut links: Vec<&PostMedia> = tables
+            .post_media
+            .iter()
+            .filter(|pm| pm.post_id == post_id)
+            .collect();
+        links.sort_by_key(|pm| pm.position);
+
+        Ok(links
+            .into_iter()
+            .filter_map(|pm| tables.media.iter().find(|m| m.id == pm.media_id).cloned())
+            .collect())
+    }
+}
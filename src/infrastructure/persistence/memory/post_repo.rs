@@ -0,0 +1,145 @@
+use crate::{
+    domain::{
+        error::DomainError,
+        model::post::{Post, PostWithAuthor},
+        repository::PostRepository,
+    },
+    infrastructure::persistence::memory::database::{InMemoryDatabase, Tables},
+    interfaces::api::dto::user::UserPublic,
+};
+use async_trait::async_trait;
+use chrono::Utc;
+use std::cmp::Reverse;
+use uuid::Uuid;
+
+#[derive(Clone)]
+pub struct InMemoryPostRepo {
+    db: InMemoryDatabase,
+}
+
+impl InMemoryPostRepo {
+    pub fn new(db: InMemoryDatabase) -> Self {
+        Self { db }
+    }
+}
+
+/// Equivalent of the `posts JOIN users LEFT JOIN media` query of the SQL backends.
+fn with_author(tables: &Tables, post: &Post) -> Option<PostWithAuthor> {
+    let author = tables.users.iter().find(|u| u.id == post.user_id)?;
+    let cover_key = post.cover_media_id.and_then(|id| {
+        tables
+            .media
+            .iter()
+            .find(|m| m.id == id)
+            .map(|m| m.storage_key())
+    });
+
+    Some(PostWithAuthor {
+        id: post.id,
+        title: post.title.clone(),
+        content: post.content.clone(),
+        published: post.published,
+        created_at: post.created_at,
+        updated_at: post.updated_at,
+        cover_media_id: post.cover_media_id,
+        cover_key,
+        cover_url: None,
+        author: UserPublic::from(author.clone()),
+    })
+}
+
+#[async_trait]
+impl PostRepository for InMemoryPostRepo {
+    async fn list(&self) -> Result<Vec<PostWithAuthor>, DomainError> {
+        let tables = self.db.read();
+        let mut posts: Vec<PostWithAuthor> = tables
+            .posts
+            .iter()
+            .filter_map(|p| with_author(&tables, p))
+            .collect();
+        posts.sort_by_key(|row| Reverse(row.created_at));
+
+        Ok(posts)
+    }
+
+    async fn find_by_id(&self, id: Uuid) -> Result<Option<PostWithAuthor>, DomainError> {
+        let tables = self.db.read();
+
+        Ok(tables
+            .posts
+            .iter()
+            .find(|p| p.id == id)
+            .and_then(|p| with_author(&tables, p)))
+    }
+
+    async fn list_by_author(&self, user_id: Uuid) -> Result<Vec<Post>, DomainError> {
+        let mut posts: Vec<Post> = self
+            .db
+            .read()
+            .posts
+            .iter()
+            .filter(|p| p.user_id == user_id)
+            .cloned()
+            .collect();
+        posts.sort_by_key(|row| Reverse(row.created_at));
+
+        Ok(posts)
+    }
+
+    async fn create(&self, new_post: Post) -> Result<Post, DomainError> {
+        let mut tables = self.db.write();
+
+        // Primary key and foreign keys of the posts table.
+        if tables.posts.iter().any(|p| p.id == new_post.id)
+            || !tables.users.iter().any(|u| u.id == new_post.user_id)
+            || new_post
+                .cover_media_id
+                .is_some_and(|id| !tables.media.iter().any(|m| m.id == id))
+        {
+            return Err(DomainError::InternalError);
+        }
+        tables.posts.push(new_post.clone());
+
+        Ok(new_post)
+    }
+
+    async fn update(&self, post: Post) -> Result<Post, DomainError> {
+        let now = Utc::now();
+        let mut tables = self.db.write();
+
+        if !tables.users.iter().any(|u| u.id == post.user_id)
+            || post
+                .cover_media_id
+                .is_some_and(|id| !tables.media.iter().any(|m| m.id == id))
+        {
+            return Err(DomainError::InternalError);
+        }
+
+        let updated = Post {
+            updated_at: Some(now),
+            ..post
+        };
+        // Like an `UPDATE … WHERE id = ?`, an unknown id is not an error.
+        if let Some(row) = tables.posts.iter_mut().find(|p| p.id == updated.id) {
+            *row = Post {
+                created_at: row.created_at,
+                ..updated.clone()
+            };
+        }
+
+        Ok(updated)
+    }
+
+    async fn delete(&self, id: Uuid) -> Result<(), DomainError> {
+        let mut tables = self.db.write();
+
+        if !tables.posts.iter().any(|p| p.id == id) {
+            return Err(DomainError::NotFound);
+        }
+        // post_media.post_id is ON DELETE CASCADE.
+        tables.post_media.retain(|pm| pm.post_id != id);
+        tables.posts.retain(|p| p.id != id);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,180 @@
+use crate::{
+    domain::{error::DomainError, model::user::User, repository::UserRepository},
+    infrastructure::persistence::memory::database::InMemoryDatabase,
+};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use std::cmp::Reverse;
+use uuid::Uuid;
+
+#[derive(Clone)]
+pub struct InMemoryUserRepo {
+    db: InMemoryDatabase,
+}
+
+impl InMemoryUserRepo {
+    pub fn new(db: InMemoryDatabase) -> Self {
+        Self { db }
+    }
+}
+
+/// Mirrors the `UNIQUE` constraints on `users.username` and `users.email`.
+fn is_duplicate(users: &[User], candidate: &User) -> bool {
+    users.iter().any(|u| {
+        u.id != candidate.id && (u.username == candidate.username || u.email == candidate.email)
+    })
+}
+
+#[async_trait]
+impl UserRepository for InMemoryUserRepo {
+    async fn list(&self) -> Result<Vec<User>, DomainError> {
+        let mut users = self.db.read().users.clone();
+        users.sort_by_key(|row| Reverse(row.created_at));
+
+        Ok(users)
+    }
+
+    async fn create(&self, user: User) -> Result<User, DomainError> {
+        let mut tables = self.db.write();
+
+        if tables.users.iter().any(|u| u.id == user.id) || is_duplicate(&tables.users, &user) {
+            return Err(DomainError::DuplicateEmail);
+        }
+        tables.users.push(user.clone());
+
+        Ok(user)
+    }
+
+    async fn find_by_id(&self, id: Uuid) -> Result<Option<User>, DomainError> {
+        Ok(self.db.read().users.iter().find(|u| u.id == id).cloned())
+    }
+
+    async fn update(&self, user: User) -> Result<User, DomainError> {
+        let mut tables = self.db.write();
+
+        if is_duplicate(&tables.users, &user) {
+            return Err(DomainError::DuplicateEmail);
+        }
+        let row = tables
+            .users
+            .iter_mut()
+            .find(|u| u.id == user.id)
+            .ok_or(sqlx::Error::RowNotFound)?;
+
+        // Like the SQL backends, the creation date and erasure mark are kept.
+        *row = User {
+            created_at: row.created_at,
+            erased_at: row.erased_at,
+            updated_at: Some(Utc::now()),
+            ..user
+        };
+
+        Ok(row.clone())
+    }
+
+    async fn delete(&self, id: Uuid) -> Result<(), DomainError> {
+        let mut tables = self.db.write();
+
+        if !tables.users.iter().any(|u| u.id == id) {
+            return Err(DomainError::NotFound);
+        }
+        // posts.user_id is ON DELETE RESTRICT.
+        if tables.posts.iter().any(|p| p.user_id == id) {
+            return Err(DomainError::InternalError);
+        }
+
+        // media.owner_id is ON DELETE CASCADE.
+        let owned: Vec<Uuid> = tables
+            .media
+            .iter()
+            .filter(|m| m.owner_id == id)
+            .map(|m| m.id)
+            .collect();
+        // Detached first, as post_media.media_id is ON DELETE RESTRICT.
+        tables.post_media.retain(|pm| !owned.contains(&pm.media_id));
+        for post in tables.posts.iter_mut() {
+            if post.cover_media_id.is_some_and(|m| owned.contains(&m)) {
+                post.cover_media_id = None;
+            }
+        }
+        tables.media.retain(|m| m.owner_id != id);
+        // access_tokens.user_id is ON DELETE CASCADE.
+        tables.access_tokens.retain(|t| t.user_id != id);
+        // user_identities.user_id is ON DELETE CASCADE.
+        tables.identities.retain(|i| i.user_id != id);
+        tables.users.retain(|u| u.id != id);
+
+        Ok(())
+    }
+
+    async fn find_by_username(&self, username: &str) -> Result<Option<User>, DomainError> {
+        Ok(self
+            .db
+            .read()
+            .users
+            .iter()
+            .find(|u| u.username == username)
+            .cloned())
+    }
+
+    async fn find_by_email(&self, email: &str) -> Result<Option<User>, DomainError> {
+        Ok(self
+            .db
+            .read()
+            .users
+            .iter()
+            .find(|u| u.email.eq_ignore_ascii_case(email))
+            .cloned())
+    }
+
+    async fn count(&self) -> Result<i64, DomainError> {
+        Ok(self.db.read().users.len() as i64)
+    }
+
+    async fn anonymize(
+        &self,
+        id: Uuid,
+        pseudonym: &str,
+        email: &str,
+        erased_at: DateTime<Utc>,
+    ) -> Result<User, DomainError> {
+        let mut tables = self.db.write();
+        let row = tables
+            .users
+            .iter_mut()
+            .find(|u| u.id == id)
+            .ok_or(DomainError::NotFound)?;
+
+        row.username = pseudonym.to_string();
+        row.email = email.to_string();
+        row.password_hash = "!".to_string();
+        row.first_name = None;
+        row.last_name = None;
+        row.display_name = None;
+        row.bio = None;
+        row.website = None;
+        row.avatar_key = None;
+        row.updated_at = Some(erased_at);
+        row.erased_at = Some(erased_at);
+        row.tokens_valid_after = Some(erased_at);
+        let user = row.clone();
+
+        tables.identities.retain(|i| i.user_id != id);
+        tables.access_tokens.retain(|t| t.user_id != id);
+        let owned: Vec<Uuid> = tables
+            .media
+            .iter()
+            .filter(|m| m.owner_id == id)
+            .map(|m| m.id)
+            .collect();
+        for post in tables.posts.iter_mut() {
+            if post.cover_media_id.is_some_and(|m| owned.contains(&m)) {
+                post.cover_media_id = None;
+            }
+        }
+        tables.post_media.retain(|pm| !owned.contains(&pm.media_id));
+        tables.media.retain(|m| m.owner_id != id);
+
+        Ok(user)
+    }
+}
@@ -0,0 +1,144 @@
+use crate::domain::{
+    error::DomainError,
+    model::access_token::{AccessToken, Scope},
+    repository::AccessTokenRepository,
+};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use sqlx::{FromRow, PgPool};
+use uuid::Uuid;
+
+/// `scopes` is stored space separated.
+#[derive(FromRow)]
+struct AccessTokenRow {
+    id: Uuid,
+    user_id: Uuid,
+    name: String,
+    prefix: String,
+    token_hash: String,
+    scopes: String,
+    created_at: DateTime<Utc>,
+    expires_at: DateTime<Utc>,
+    last_used_at: Option<DateTime<Utc>>,
+    revoked_at: Option<DateTime<Utc>>,
+}
+
+impl From<AccessTokenRow> for AccessToken {
+    fn from(row: AccessTokenRow) -> Self {
+        Self {
+            id: row.id,
+            user_id: row.user_id,
+            name: row.name,
+            prefix: row.prefix,
+            token_hash: row.token_hash,
+            scopes: Scope::split(&row.scopes),
+            created_at: row.created_at,
+            expires_at: row.expires_at,
+            last_used_at: row.last_used_at,
+            revoked_at: row.revoked_at,
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct PgAccessTokenRepo {
+    pool: PgPool,
+}
+
+impl PgAccessTokenRepo {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl AccessTokenRepository for PgAccessTokenRepo {
+    async fn create(&self, token: AccessToken) -> Result<AccessToken, DomainError> {
+        sqlx::query(
+            r#"
+            INSERT INTO access_tokens (id, user_id, name, prefix, token_hash, scopes, created_at, expires_at, last_used_at, revoked_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
+            "#,
+        )
+        .bind(token.id)
+        .bind(token.user_id)
+        .bind(&token.name)
+        .bind(&token.prefix)
+        .bind(&token.token_hash)
+        .bind(Scope::join(&token.scopes))
+        .bind(token.created_at)
+        .bind(token.expires_at)
+        .bind(token.last_used_at)
+        .bind(token.revoked_at)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(token)
+    }
+
+    #[tracing::instrument(name = "PgAccessTokenRepo::find_by_hash", skip_all, fields(db.system = "postgresql"))]
+    async fn find_by_hash(&self, token_hash: &str) -> Result<Option<AccessToken>, DomainError> {
+        let row = sqlx::query_as::<_, AccessTokenRow>(
+            r#"
+            SELECT id, user_id, name, prefix, token_hash, scopes, created_at, expires_at, last_used_at, revoked_at
+            FROM access_tokens
+            WHERE token_hash = $1
+            "#,
+        )
+        .bind(token_hash)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(row.map(AccessToken::from))
+    }
+
+    async fn list_by_user(&self, user_id: Uuid) -> Result<Vec<AccessToken>, DomainError> {
+        let rows = sqlx::query_as::<_, AccessTokenRow>(
+            r#"
+            SELECT id, user_id, name, prefix, token_hash, scopes, created_at, expires_at, last_used_at, revoked_at
+            FROM access_tokens
+            WHERE user_id = $1
+            ORDER BY created_at DESC
+            "#,
+        )
+        .bind(user_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows.into_iter().map(AccessToken::from).collect())
+    }
+
+    async fn revoke(
+        &self,
+        id: Uuid,
+        user_id: Uuid,
+        revoked_at: DateTime<Utc>,
+    ) -> Result<AccessToken, DomainError> {
+        let row = sqlx::query_as::<_, AccessTokenRow>(
+            r#"
+            UPDATE access_tokens
+            SET revoked_at = COALESCE(revoked_at, $1)
+            WHERE id = $2 AND user_id = $3
+            RETURNING id, user_id, name, prefix, token_hash, scopes, created_at, expires_at, last_used_at, revoked_at
+            "#,
+        )
+        .bind(revoked_at)
+        .bind(id)
+        .bind(user_id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        row.map(AccessToken::from).ok_or(DomainError::NotFound)
+    }
+
+    #[tracing::instrument(name = "PgAccessTokenRepo::touch", skip_all, fields(db.system = "postgresql", %id))]
+    async fn touch(&self, id: Uuid, used_at: DateTime<Utc>) -> Result<(), DomainError> {
+        sqlx::query("UPDATE access_tokens SET last_used_at = $1 WHERE id = $2")
+            .bind(used_at)
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,96 @@
+use crate::domain::{error::DomainError, model::audit::AuditEntry, repository::AuditRepository};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use sqlx::{FromRow, PgPool};
+use uuid::Uuid;
+
+#[derive(FromRow)]
+struct AuditRow {
+    id: Uuid,
+    action: String,
+    actor_id: Uuid,
+    subject_id: Option<Uuid>,
+    session_id: Option<Uuid>,
+    created_at: DateTime<Utc>,
+}
+
+impl TryFrom<AuditRow> for AuditEntry {
+    type Error = DomainError;
+
+    fn try_from(row: AuditRow) -> Result<Self, Self::Error> {
+        Ok(Self {
+            id: row.id,
+            action: row.action.parse().map_err(|_| DomainError::InternalError)?,
+            actor_id: row.actor_id,
+            subject_id: row.subject_id,
+            session_id: row.session_id,
+            created_at: row.created_at,
+        })
+    }
+}
+
+#[derive(Clone)]
+pub struct PgAuditRepo {
+    pool: PgPool,
+}
+
+impl PgAuditRepo {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl AuditRepository for PgAuditRepo {
+    async fn record(&self, entry: AuditEntry) -> Result<AuditEntry, DomainError> {
+        sqlx::query(
+            r#"
+            INSERT INTO audit_log (id, action, actor_id, subject_id, session_id, created_at)
+            VALUES ($1, $2, $3, $4, $5, $6)
+            "#,
+        )
+        .bind(entry.id)
+        .bind(entry.action.as_str())
+        .bind(entry.actor_id)
+        .bind(entry.subject_id)
+        .bind(entry.session_id)
+        .bind(entry.created_at)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(entry)
+    }
+
+    async fn list(&self, limit: i64) -> Result<Vec<AuditEntry>, DomainError> {
+        let rows = sqlx::query_as::<_, AuditRow>(
+            r#"
+            SELECT id, action, actor_id, subject_id, session_id, created_at
+            FROM audit_log
+            ORDER BY created_at DESC
+            LIMIT $1
+            "#,
+        )
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.into_iter().map(AuditEntry::try_from).collect()
+    }
+
+    #[tracing::instrument(name = "PgAuditRepo::list_by_session", skip_all, fields(db.system = "postgresql"))]
+    async fn list_by_session(&self, session_id: Uuid) -> Result<Vec<AuditEntry>, DomainError> {
+        let rows = sqlx::query_as::<_, AuditRow>(
+            r#"
+            SELECT id, action, actor_id, subject_id, session_id, created_at
+            FROM audit_log
+            WHERE session_id = $1
+            ORDER BY created_at
+            "#,
+        )
+        .bind(session_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.into_iter().map(AuditEntry::try_from).collect()
+    }
+}
@@ -0,0 +1,97 @@
+use crate::domain::{
+    error::DomainError, model::identity::ExternalIdentity, repository::IdentityRepository,
+};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use sqlx::{FromRow, PgPool};
+use uuid::Uuid;
+
+#[derive(FromRow)]
+struct IdentityRow {
+    issuer: String,
+    subject: String,
+    user_id: Uuid,
+    email: Option<String>,
+    created_at: DateTime<Utc>,
+}
+
+impl From<IdentityRow> for ExternalIdentity {
+    fn from(row: IdentityRow) -> Self {
+        Self {
+            issuer: row.issuer,
+            subject: row.subject,
+            user_id: row.user_id,
+            email: row.email,
+            created_at: row.created_at,
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct PgIdentityRepo {
+    pool: PgPool,
+}
+
+impl PgIdentityRepo {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl IdentityRepository for PgIdentityRepo {
+    async fn create(&self, identity: ExternalIdentity) -> Result<ExternalIdentity, DomainError> {
+        sqlx::query(
+            r#"
+            INSERT INTO user_identities (issuer, subject, user_id, email, created_at)
+            VALUES ($1, $2, $3, $4, $5)
+            "#,
+        )
+        .bind(&identity.issuer)
+        .bind(&identity.subject)
+        .bind(identity.user_id)
+        .bind(&identity.email)
+        .bind(identity.created_at)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(identity)
+    }
+
+    #[tracing::instrument(name = "PgIdentityRepo::find", skip_all, fields(db.system = "postgresql"))]
+    async fn find(
+        &self,
+        issuer: &str,
+        subject: &str,
+    ) -> Result<Option<ExternalIdentity>, DomainError> {
+        let row = sqlx::query_as::<_, IdentityRow>(
+            r#"
+            SELECT issuer, subject, user_id, email, created_at
+            FROM user_identities
+            WHERE issuer = $1 AND subject = $2
+            "#,
+        )
+        .bind(issuer)
+        .bind(subject)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(row.map(ExternalIdentity::from))
+    }
+
+    async fn list_by_user(&self, user_id: Uuid) -> Result<Vec<ExternalIdentity>, DomainError> {
+        let rows = sqlx::query_as::<_, IdentityRow>(
+            r#"
+            SELECT issuer, subject, user_id, email, created_at
+            FROM user_identities
+            WHERE user_id = $1
+            ORDER BY created_at
+            "#,
+        )
+        .bind(user_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows.into_iter().map(ExternalIdentity::from).collect())
+    }
+}
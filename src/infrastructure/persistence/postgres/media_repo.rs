@@ -0,0 +1,201 @@
+use crate::domain::{error::DomainError, model::media::Media, repository::MediaRepository};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use sqlx::{FromRow, PgPool};
+use uuid::Uuid;
+
+#[derive(FromRow)]
+struct MediaRow {
+    id: Uuid,
+    owner_id: Uuid,
+    sha256: String,
+    mime_type: String,
+    size_bytes: i64,
+    original_name: Option<String>,
+    created_at: DateTime<Utc>,
+}
+
+impl From<MediaRow> for Media {
+    fn from(row: MediaRow) -> Self {
+        Self {
+            id: row.id,
+            owner_id: row.owner_id,
+            sha256: row.sha256,
+            mime_type: row.mime_type,
+            size_bytes: row.size_bytes,
+            original_name: row.original_name,
+            created_at: row.created_at,
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct PgMediaRepo {
+    pool: PgPool,
+}
+
+impl PgMediaRepo {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl MediaRepository for PgMediaRepo {
+    async fn create(&self, media: Media) -> Result<Media, DomainError> {
+        sqlx::query(
+            r#"
+            INSERT INTO media (id, owner_id, sha256, mime_type, size_bytes, original_name, created_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7)
+            "#,
+        )
+        .bind(media.id)
+        .bind(media.owner_id)
+        .bind(&media.sha256)
+        .bind(&media.mime_type)
+        .bind(media.size_bytes)
+        .bind(&media.original_name)
+        .bind(media.created_at)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(media)
+    }
+
+    async fn find_by_id(&self, id: Uuid) -> Result<Option<Media>, DomainError> {
+        let row = sqlx::query_as::<_, MediaRow>(
+            r#"
+            SELECT id, owner_id, sha256, mime_type, size_bytes, original_name, created_at
+            FROM media
+            WHERE id = $1
+            "#,
+        )
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(row.map(Media::from))
+    }
+
+    async fn find_by_owner_and_hash(
+        &self,
+        owner_id: Uuid,
+        sha256: &str,
+    ) -> Result<Option<Media>, DomainError> {
+        let row = sqlx::query_as::<_, MediaRow>(
+            r#"
+            SELECT id, owner_id, sha256, mime_type, size_bytes, original_name, created_at
+            FROM media
+            WHERE owner_id = $1 AND sha256 = $2
+            "#,
+        )
+        .bind(owner_id)
+        .bind(sha256)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(row.map(Media::from))
+    }
+
+    async fn list_by_owner(&self, owner_id: Uuid) -> Result<Vec<Media>, DomainError> {
+        let rows = sqlx::query_as::<_, MediaRow>(
+            r#"
+            SELECT id, owner_id, sha256, mime_type, size_bytes, original_name, created_at
+            FROM media
+            WHERE owner_id = $1
+            ORDER BY created_at DESC
+            "#,
+        )
+        .bind(owner_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows.into_iter().map(Media::from).collect())
+    }
+
+    async fn delete(&self, id: Uuid) -> Result<(), DomainError> {
+        let result = sqlx::query("DELETE FROM media WHERE id = $1")
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+
+        if result.rows_affected() == 0 {
+            Err(DomainError::NotFound)
+        } else {
+            Ok(())
+        }
+    }
+
+    async fn count_by_hash(&self, sha256: &str) -> Result<i64, DomainError> {
+        let count = sqlx::query_scalar::<_, i64>("SELECT COUNT(*) FROM media WHERE sha256 = $1")
+            .bind(sha256)
+            .fetch_one(&self.pool)
+            .await?;
+
+        Ok(count)
+    }
+
+    async fn is_referenced(&self, id: Uuid) -> Result<bool, DomainError> {
+        let referenced = sqlx::query_scalar::<_, bool>(
+            r#"
+            SELECT EXISTS (
+                SELECT 1 FROM post_media WHERE media_id = $1
+                UNION ALL
+                SELECT 1 FROM posts WHERE cover_media_id = $1
+            )
+            "#,
+        )
+        .bind(id)
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(referenced)
+    }
+
+    async fn attach(&self, post_id: Uuid, media_id: Uuid) -> Result<(), DomainError> {
+        sqlx::query(
+            r#"
+            INSERT INTO post_media (post_id, media_id, position)
+            VALUES ($1, $2, (SELECT COALESCE(MAX(position), -1) + 1 FROM post_media WHERE post_id = $1))
+            ON CONFLICT (post_id, media_id) DO NOTHING
+            "#,
+        )
+        .bind(post_id)
+        .bind(media_id)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn detach(&self, post_id: Uuid, media_id: Uuid) -> Result<(), DomainError> {
+        let result = sqlx::query("DELETE FROM post_media WHERE post_id = $1 AND media_id = $2")
+            .bind(post_id)
+            .bind(media_id)
+            .execute(&self.pool)
+            .await?;
+
+        if result.rows_affected() == 0 {
+            Err(DomainError::NotFound)
+        } else {
+            Ok(())
+        }
+    }
+
+    async fn list_for_post(&self, post_id: Uuid) -> Result<Vec<Media>, DomainError> {
+        let rows = sqlx::query_as::<_, MediaRow>(
+            r#"
+            SELECT m.id, m.owner_id, m.sha256, m.mime_type, m.size_bytes, m.original_name, m.created_at
+            FROM post_media pm
+            JOIN media m ON m.id = pm.media_id
+            WHERE pm.post_id = $1
+            ORDER BY pm.position
+            "#,
+        )
+        .bind(post_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows.into_iter().map(Media::from).collect())
+    }
+}
@@ -0,0 +1,239 @@
+use crate::{
+    domain::{
+        error::DomainError,
+        model::{
+            media::media_storage_key,
+            post::{Post, PostWithAuthor},
+        },
+        repository::PostRepository,
+    },
+    interfaces::api::dto::user::{UserPublic, avatar_links},
+};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use sqlx::{FromRow, PgPool};
+use uuid::Uuid;
+
+const POST_WITH_AUTHOR_SELECT: &str = r#"
+    SELECT
+    p.id AS post_id,
+    p.title,
+    p.content,
+    p.published,
+    p.created_at,
+    p.updated_at,
+    p.cover_media_id,
+    c.sha256 AS cover_sha256,
+    u.id AS user_id,
+    u.username,
+    u.first_name,
+    u.last_name,
+    u.display_name,
+    u.bio,
+    u.website,
+    u.avatar_key,
+    u.created_at AS user_created_at
+    FROM posts p
+    JOIN users u ON p.user_id = u.id
+    LEFT JOIN media c ON c.id = p.cover_media_id
+"#;
+
+const POST_COLUMNS: &str =
+    "id, user_id, title, content, published, created_at, updated_at, cover_media_id";
+
+#[derive(FromRow)]
+struct PostRow {
+    id: Uuid,
+    user_id: Uuid,
+    title: String,
+    content: String,
+    published: bool,
+    created_at: DateTime<Utc>,
+    updated_at: Option<DateTime<Utc>>,
+    cover_media_id: Option<Uuid>,
+}
+
+impl From<PostRow> for Post {
+    fn from(row: PostRow) -> Self {
+        Self {
+            id: row.id,
+            user_id: row.user_id,
+            title: row.title,
+            content: row.content,
+            published: row.published,
+            created_at: row.created_at,
+            updated_at: row.updated_at,
+            cover_media_id: row.cover_media_id,
+        }
+    }
+}
+
+#[derive(FromRow)]
+struct PostWithAuthorRow {
+    post_id: Uuid,
+    title: String,
+    content: String,
+    published: bool,
+    created_at: DateTime<Utc>,
+    updated_at: Option<DateTime<Utc>>,
+    cover_media_id: Option<Uuid>,
+    cover_sha256: Option<String>,
+    user_id: Uuid,
+    username: String,
+    first_name: Option<String>,
+    last_name: Option<String>,
+    display_name: Option<String>,
+    bio: Option<String>,
+    website: Option<String>,
+    avatar_key: Option<String>,
+    user_created_at: DateTime<Utc>,
+}
+
+impl From<PostWithAuthorRow> for PostWithAuthor {
+    fn from(row: PostWithAuthorRow) -> Self {
+        let (avatar_url, avatar_thumbnails) = avatar_links(row.avatar_key.as_deref());
+
+        Self {
+            id: row.post_id,
+            title: row.title,
+            content: row.content,
+            published: row.published,
+            created_at: row.created_at,
+            updated_at: row.updated_at,
+            cover_media_id: row.cover_media_id,
+            cover_key: row.cover_sha256.map(|sha| media_storage_key(&sha)),
+            cover_url: None,
+            author: UserPublic {
+                id: row.user_id,
+                username: row.username,
+                first_name: row.first_name,
+                last_name: row.last_name,
+                display_name: row.display_name,
+                bio: row.bio,
+                website: row.website,
+                avatar_url,
+                avatar_thumbnails,
+                created_at: row.user_created_at,
+            },
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct PgPostRepo {
+    pool: PgPool,
+}
+
+impl PgPostRepo {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl PostRepository for PgPostRepo {
+    #[tracing::instrument(name = "PgPostRepo::list", skip_all, fields(db.system = "postgresql"))]
+    async fn list(&self) -> Result<Vec<PostWithAuthor>, DomainError> {
+        let rows = sqlx::query_as::<_, PostWithAuthorRow>(&format!(
+            "{} ORDER BY p.created_at DESC",
+            POST_WITH_AUTHOR_SELECT
+        ))
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows.into_iter().map(PostWithAuthor::from).collect())
+    }
+
+    #[tracing::instrument(name = "PgPostRepo::find_by_id", skip_all, fields(db.system = "postgresql", %id))]
+    async fn find_by_id(&self, id: Uuid) -> Result<Option<PostWithAuthor>, DomainError> {
+        let row = sqlx::query_as::<_, PostWithAuthorRow>(&format!(
+            "{} WHERE p.id = $1",
+            POST_WITH_AUTHOR_SELECT
+        ))
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(row.map(PostWithAuthor::from))
+    }
+
+    #[tracing::instrument(name = "PgPostRepo::list_by_author", skip_all, fields(db.system = "postgresql", %user_id))]
+    async fn list_by_author(&self, user_id: Uuid) -> Result<Vec<Post>, DomainError> {
+        let rows = sqlx::query_as::<_, PostRow>(&format!(
+            "SELECT {} FROM posts WHERE user_id = $1 ORDER BY created_at DESC",
+            POST_COLUMNS
+        ))
+        .bind(user_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows.into_iter().map(Post::from).collect())
+    }
+
+    #[tracing::instrument(name = "PgPostRepo::create", skip_all, fields(db.system = "postgresql"))]
+    async fn create(&self, new_post: Post) -> Result<Post, DomainError> {
+        sqlx::query(
+            r#"
+            INSERT INTO posts (id, user_id, title, content, published, created_at, cover_media_id)
+            VALUES ($1, $2, $3, $4, $5, $6, $7)
+            "#,
+        )
+        .bind(new_post.id)
+        .bind(new_post.user_id)
+        .bind(&new_post.title)
+        .bind(&new_post.content)
+        .bind(new_post.published)
+        .bind(new_post.created_at)
+        .bind(new_post.cover_media_id)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(new_post)
+    }
+
+    #[tracing::instrument(name = "PgPostRepo::update", skip_all, fields(db.system = "postgresql"))]
+    async fn update(&self, post: Post) -> Result<Post, DomainError> {
+        let now = Utc::now();
+
+        sqlx::query(
+            r#"
+            UPDATE posts SET
+                user_id = $1,
+                title = $2,
+                content = $3,
+                published = $4,
+                cover_media_id = $5,
+                updated_at = $6
+            WHERE id = $7
+            "#,
+        )
+        .bind(post.user_id)
+        .bind(&post.title)
+        .bind(&post.content)
+        .bind(post.published)
+        .bind(post.cover_media_id)
+        .bind(now)
+        .bind(post.id)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(Post {
+            updated_at: Some(now),
+            ..post
+        })
+    }
+
+    #[tracing::instrument(name = "PgPostRepo::delete", skip_all, fields(db.system = "postgresql", %id))]
+    async fn delete(&self, id: Uuid) -> Result<(), DomainError> {
+        let result = sqlx::query("DELETE FROM posts WHERE id = $1")
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+
+        if result.rows_affected() == 0 {
+            Err(DomainError::NotFound)
+        } else {
+            Ok(())
+        }
+    }
+}
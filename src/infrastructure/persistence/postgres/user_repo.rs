@@ -0,0 +1,279 @@
+use std::str::FromStr;
+
+use crate::domain::{
+    error::DomainError,
+    model::user::{Role, User},
+    repository::UserRepository,
+};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use sqlx::{FromRow, PgPool};
+use uuid::Uuid;
+
+const USER_COLUMNS: &str = "id, username, role, password_hash, email, first_name, last_name, display_name, bio, website, avatar_key, created_at, updated_at, erased_at, tokens_valid_after";
+
+#[derive(FromRow)]
+struct UserRow {
+    id: Uuid,
+    username: String,
+    role: String,
+    password_hash: String,
+    email: String,
+    first_name: Option<String>,
+    last_name: Option<String>,
+    display_name: Option<String>,
+    bio: Option<String>,
+    website: Option<String>,
+    avatar_key: Option<String>,
+    created_at: DateTime<Utc>,
+    updated_at: Option<DateTime<Utc>>,
+    erased_at: Option<DateTime<Utc>>,
+    tokens_valid_after: Option<DateTime<Utc>>,
+}
+
+impl TryFrom<UserRow> for User {
+    type Error = DomainError;
+
+    fn try_from(row: UserRow) -> Result<Self, Self::Error> {
+        Ok(User {
+            id: row.id,
+            username: row.username,
+            role: Role::from_str(&row.role).map_err(|_| DomainError::InternalError)?,
+            password_hash: row.password_hash,
+            email: row.email,
+            first_name: row.first_name,
+            last_name: row.last_name,
+            display_name: row.display_name,
+            bio: row.bio,
+            website: row.website,
+            avatar_key: row.avatar_key,
+            created_at: row.created_at,
+            updated_at: row.updated_at,
+            erased_at: row.erased_at,
+            tokens_valid_after: row.tokens_valid_after,
+        })
+    }
+}
+
+/// Roles are stored with the same spelling as the SQLite backend.
+fn role_column(role: &Role) -> &'static str {
+    match role {
+        Role::User => "User",
+        Role::Admin => "Admin",
+    }
+}
+
+#[derive(Clone)]
+pub struct PgUserRepo {
+    pool: PgPool,
+}
+
+impl PgUserRepo {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl UserRepository for PgUserRepo {
+    #[tracing::instrument(name = "PgUserRepo::list", skip_all, fields(db.system = "postgresql"))]
+    async fn list(&self) -> Result<Vec<User>, DomainError> {
+        let rows = sqlx::query_as::<_, UserRow>(&format!(
+            "SELECT {} FROM users ORDER BY created_at DESC",
+            USER_COLUMNS
+        ))
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.into_iter().map(User::try_from).collect()
+    }
+
+    #[tracing::instrument(name = "PgUserRepo::create", skip_all, fields(db.system = "postgresql"))]
+    async fn create(&self, user: User) -> Result<User, DomainError> {
+        let res = sqlx::query_as::<_, UserRow>(&format!(
+            r#"
+            INSERT INTO users (id, username, role, password_hash, email, first_name, last_name, display_name, bio, website, avatar_key, created_at, updated_at, tokens_valid_after)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14)
+            RETURNING {}
+            "#,
+            USER_COLUMNS
+        ))
+        .bind(user.id)
+        .bind(&user.username)
+        .bind(role_column(&user.role))
+        .bind(&user.password_hash)
+        .bind(&user.email)
+        .bind(&user.first_name)
+        .bind(&user.last_name)
+        .bind(&user.display_name)
+        .bind(&user.bio)
+        .bind(&user.website)
+        .bind(&user.avatar_key)
+        .bind(user.created_at)
+        .bind(user.updated_at)
+        .bind(user.tokens_valid_after)
+        .fetch_one(&self.pool)
+        .await;
+
+        match res {
+            Ok(row) => row.try_into(),
+            Err(sqlx::Error::Database(db_err)) if db_err.is_unique_violation() => {
+                Err(DomainError::DuplicateEmail)
+            }
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    #[tracing::instrument(name = "PgUserRepo::find_by_id", skip_all, fields(db.system = "postgresql", %id))]
+    async fn find_by_id(&self, id: Uuid) -> Result<Option<User>, DomainError> {
+        let row = sqlx::query_as::<_, UserRow>(&format!(
+            "SELECT {} FROM users WHERE id = $1",
+            USER_COLUMNS
+        ))
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        row.map(User::try_from).transpose()
+    }
+
+    #[tracing::instrument(name = "PgUserRepo::find_by_username", skip_all, fields(db.system = "postgresql"))]
+    async fn find_by_username(&self, username: &str) -> Result<Option<User>, DomainError> {
+        let row = sqlx::query_as::<_, UserRow>(&format!(
+            "SELECT {} FROM users WHERE username = $1",
+            USER_COLUMNS
+        ))
+        .bind(username)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        row.map(User::try_from).transpose()
+    }
+
+    #[tracing::instrument(name = "PgUserRepo::find_by_email", skip_all, fields(db.system = "postgresql"))]
+    async fn find_by_email(&self, email: &str) -> Result<Option<User>, DomainError> {
+        let row = sqlx::query_as::<_, UserRow>(&format!(
+            "SELECT {} FROM users WHERE LOWER(email) = LOWER($1)",
+            USER_COLUMNS
+        ))
+        .bind(email)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        row.map(User::try_from).transpose()
+    }
+
+    #[tracing::instrument(name = "PgUserRepo::update", skip_all, fields(db.system = "postgresql"))]
+    async fn update(&self, user: User) -> Result<User, DomainError> {
+        let now = Utc::now();
+        let res = sqlx::query_as::<_, UserRow>(&format!(
+            r#"
+            UPDATE users
+            SET username = $1, password_hash = $2, email = $3, first_name = $4, last_name = $5, display_name = $6, bio = $7, website = $8, avatar_key = $9, updated_at = $10, role = $11, tokens_valid_after = $12
+            WHERE id = $13
+            RETURNING {}
+            "#,
+            USER_COLUMNS
+        ))
+        .bind(&user.username)
+        .bind(&user.password_hash)
+        .bind(&user.email)
+        .bind(&user.first_name)
+        .bind(&user.last_name)
+        .bind(&user.display_name)
+        .bind(&user.bio)
+        .bind(&user.website)
+        .bind(&user.avatar_key)
+        .bind(now)
+        .bind(role_column(&user.role))
+        .bind(user.tokens_valid_after)
+        .bind(user.id)
+        .fetch_one(&self.pool)
+        .await;
+
+        match res {
+            Ok(row) => row.try_into(),
+            Err(sqlx::Error::Database(db_err)) if db_err.is_unique_violation() => {
+                Err(DomainError::DuplicateEmail)
+            }
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    #[tracing::instrument(name = "PgUserRepo::count", skip_all, fields(db.system = "postgresql"))]
+    async fn count(&self) -> Result<i64, DomainError> {
+        let count = sqlx::query_scalar::<_, i64>("SELECT COUNT(*) FROM users")
+            .fetch_one(&self.pool)
+            .await?;
+
+        Ok(count)
+    }
+
+    #[tracing::instrument(name = "PgUserRepo::anonymize", skip_all, fields(db.system = "postgresql", %id))]
+    async fn anonymize(
+        &self,
+        id: Uuid,
+        pseudonym: &str,
+        email: &str,
+        erased_at: DateTime<Utc>,
+    ) -> Result<User, DomainError> {
+        let mut tx = self.pool.begin().await?;
+
+        let row = sqlx::query_as::<_, UserRow>(&format!(
+            r#"
+            UPDATE users
+            SET username = $1, email = $2, password_hash = '!', first_name = NULL, last_name = NULL, display_name = NULL, bio = NULL, website = NULL, avatar_key = NULL, updated_at = $3, erased_at = $3, tokens_valid_after = $3
+            WHERE id = $4
+            RETURNING {}
+            "#,
+            USER_COLUMNS
+        ))
+        .bind(pseudonym)
+        .bind(email)
+        .bind(erased_at)
+        .bind(id)
+        .fetch_optional(&mut *tx)
+        .await?;
+
+        let user = row.ok_or(DomainError::NotFound)?.try_into()?;
+
+        for statement in [
+            "DELETE FROM user_identities WHERE user_id = $1",
+            "DELETE FROM access_tokens WHERE user_id = $1",
+            "UPDATE posts SET cover_media_id = NULL WHERE cover_media_id IN (SELECT id FROM media WHERE owner_id = $1)",
+            "DELETE FROM post_media WHERE media_id IN (SELECT id FROM media WHERE owner_id = $1)",
+            "DELETE FROM media WHERE owner_id = $1",
+        ] {
+            sqlx::query(statement).bind(id).execute(&mut *tx).await?;
+        }
+
+        tx.commit().await?;
+
+        Ok(user)
+    }
+
+    #[tracing::instrument(name = "PgUserRepo::delete", skip_all, fields(db.system = "postgresql", %id))]
+    async fn delete(&self, id: Uuid) -> Result<(), DomainError> {
+        let mut tx = self.pool.begin().await?;
+
+        // post_media.media_id is ON DELETE RESTRICT: the media going with the
+        // user are detached first.
+        sqlx::query(
+            "DELETE FROM post_media WHERE media_id IN (SELECT id FROM media WHERE owner_id = $1)",
+        )
+        .bind(id)
+        .execute(&mut *tx)
+        .await?;
+        let result = sqlx::query("DELETE FROM users WHERE id = $1")
+            .bind(id)
+            .execute(&mut *tx)
+            .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(DomainError::NotFound);
+        }
+        tx.commit().await?;
+
+        Ok(())
+    }
+}
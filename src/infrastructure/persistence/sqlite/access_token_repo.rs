@@ -0,0 +1,149 @@
+use crate::domain::{
+    error::DomainError,
+    model::access_token::{AccessToken, Scope},
+    repository::AccessTokenRepository,
+};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use sqlx::SqlitePool;
+use uuid::Uuid;
+
+/// `scopes` is stored space separated.
+struct AccessTokenRow {
+    id: Uuid,
+    user_id: Uuid,
+    name: String,
+    prefix: String,
+    token_hash: String,
+    scopes: String,
+    created_at: DateTime<Utc>,
+    expires_at: DateTime<Utc>,
+    last_used_at: Option<DateTime<Utc>>,
+    revoked_at: Option<DateTime<Utc>>,
+}
+
+impl From<AccessTokenRow> for AccessToken {
+    fn from(row: AccessTokenRow) -> Self {
+        Self {
+            id: row.id,
+            user_id: row.user_id,
+            name: row.name,
+            prefix: row.prefix,
+            token_hash: row.token_hash,
+            scopes: Scope::split(&row.scopes),
+            created_at: row.created_at,
+            expires_at: row.expires_at,
+            last_used_at: row.last_used_at,
+            revoked_at: row.revoked_at,
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct SqliteAccessTokenRepo {
+    pool: SqlitePool,
+}
+
+impl SqliteAccessTokenRepo {
+    pub fn new(pool: SqlitePool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl AccessTokenRepository for SqliteAccessTokenRepo {
+    async fn create(&self, token: AccessToken) -> Result<AccessToken, DomainError> {
+        let scopes = Scope::join(&token.scopes);
+        sqlx::query!(
+            r#"
+            INSERT INTO access_tokens (id, user_id, name, prefix, token_hash, scopes, created_at, expires_at, last_used_at, revoked_at)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+            "#,
+            token.id,
+            token.user_id,
+            token.name,
+            token.prefix,
+            token.token_hash,
+            scopes,
+            token.created_at,
+            token.expires_at,
+            token.last_used_at,
+            token.revoked_at,
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(token)
+    }
+
+    #[tracing::instrument(name = "SqliteAccessTokenRepo::find_by_hash", skip_all, fields(db.system = "sqlite"))]
+    async fn find_by_hash(&self, token_hash: &str) -> Result<Option<AccessToken>, DomainError> {
+        let row = sqlx::query_as!(
+            AccessTokenRow,
+            r#"
+            SELECT id as "id: Uuid", user_id as "user_id: Uuid", name, prefix, token_hash, scopes, created_at as "created_at: DateTime<Utc>", expires_at as "expires_at: DateTime<Utc>", last_used_at as "last_used_at: DateTime<Utc>", revoked_at as "revoked_at: DateTime<Utc>"
+            FROM access_tokens
+            WHERE token_hash = ?
+            "#,
+            token_hash
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(row.map(AccessToken::from))
+    }
+
+    async fn list_by_user(&self, user_id: Uuid) -> Result<Vec<AccessToken>, DomainError> {
+        let rows = sqlx::query_as!(
+            AccessTokenRow,
+            r#"
+            SELECT id as "id: Uuid", user_id as "user_id: Uuid", name, prefix, token_hash, scopes, created_at as "created_at: DateTime<Utc>", expires_at as "expires_at: DateTime<Utc>", last_used_at as "last_used_at: DateTime<Utc>", revoked_at as "revoked_at: DateTime<Utc>"
+            FROM access_tokens
+            WHERE user_id = ?
+            ORDER BY created_at DESC
+            "#,
+            user_id
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows.into_iter().map(AccessToken::from).collect())
+    }
+
+    async fn revoke(
+        &self,
+        id: Uuid,
+        user_id: Uuid,
+        revoked_at: DateTime<Utc>,
+    ) -> Result<AccessToken, DomainError> {
+        let row = sqlx::query_as!(
+            AccessTokenRow,
+            r#"
+            UPDATE access_tokens
+            SET revoked_at = COALESCE(revoked_at, ?)
+            WHERE id = ? AND user_id = ?
+            RETURNING id as "id: Uuid", user_id as "user_id: Uuid", name, prefix, token_hash, scopes, created_at as "created_at: DateTime<Utc>", expires_at as "expires_at: DateTime<Utc>", last_used_at as "last_used_at: DateTime<Utc>", revoked_at as "revoked_at: DateTime<Utc>"
+            "#,
+            revoked_at,
+            id,
+            user_id
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+
+        row.map(AccessToken::from).ok_or(DomainError::NotFound)
+    }
+
+    #[tracing::instrument(name = "SqliteAccessTokenRepo::touch", skip_all, fields(db.system = "sqlite", %id))]
+    async fn touch(&self, id: Uuid, used_at: DateTime<Utc>) -> Result<(), DomainError> {
+        sqlx::query!(
+            "UPDATE access_tokens SET last_used_at = ? WHERE id = ?",
+            used_at,
+            id
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,98 @@
+use crate::domain::{error::DomainError, model::audit::AuditEntry, repository::AuditRepository};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use sqlx::SqlitePool;
+use uuid::Uuid;
+
+struct AuditRow {
+    id: Uuid,
+    action: String,
+    actor_id: Uuid,
+    subject_id: Option<Uuid>,
+    session_id: Option<Uuid>,
+    created_at: DateTime<Utc>,
+}
+
+impl TryFrom<AuditRow> for AuditEntry {
+    type Error = DomainError;
+
+    fn try_from(row: AuditRow) -> Result<Self, Self::Error> {
+        Ok(Self {
+            id: row.id,
+            action: row.action.parse().map_err(|_| DomainError::InternalError)?,
+            actor_id: row.actor_id,
+            subject_id: row.subject_id,
+            session_id: row.session_id,
+            created_at: row.created_at,
+        })
+    }
+}
+
+#[derive(Clone)]
+pub struct SqliteAuditRepo {
+    pool: SqlitePool,
+}
+
+impl SqliteAuditRepo {
+    pub fn new(pool: SqlitePool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl AuditRepository for SqliteAuditRepo {
+    async fn record(&self, entry: AuditEntry) -> Result<AuditEntry, DomainError> {
+        let action = entry.action.as_str();
+        sqlx::query!(
+            r#"
+            INSERT INTO audit_log (id, action, actor_id, subject_id, session_id, created_at)
+            VALUES (?, ?, ?, ?, ?, ?)
+            "#,
+            entry.id,
+            action,
+            entry.actor_id,
+            entry.subject_id,
+            entry.session_id,
+            entry.created_at,
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(entry)
+    }
+
+    async fn list(&self, limit: i64) -> Result<Vec<AuditEntry>, DomainError> {
+        let rows = sqlx::query_as!(
+            AuditRow,
+            r#"
+            SELECT id as "id: Uuid", action, actor_id as "actor_id: Uuid", subject_id as "subject_id: Uuid", session_id as "session_id: Uuid", created_at as "created_at: DateTime<Utc>"
+            FROM audit_log
+            ORDER BY created_at DESC
+            LIMIT ?
+            "#,
+            limit
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.into_iter().map(AuditEntry::try_from).collect()
+    }
+
+    #[tracing::instrument(name = "SqliteAuditRepo::list_by_session", skip_all, fields(db.system = "sqlite"))]
+    async fn list_by_session(&self, session_id: Uuid) -> Result<Vec<AuditEntry>, DomainError> {
+        let rows = sqlx::query_as!(
+            AuditRow,
+            r#"
+            SELECT id as "id: Uuid", action, actor_id as "actor_id: Uuid", subject_id as "subject_id: Uuid", session_id as "session_id: Uuid", created_at as "created_at: DateTime<Utc>"
+            FROM audit_log
+            WHERE session_id = ?
+            ORDER BY created_at
+            "#,
+            session_id
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.into_iter().map(AuditEntry::try_from).collect()
+    }
+}
@@ -0,0 +1,78 @@
+use crate::domain::{
+    error::DomainError, model::identity::ExternalIdentity, repository::IdentityRepository,
+};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use sqlx::SqlitePool;
+use uuid::Uuid;
+
+#[derive(Clone)]
+pub struct SqliteIdentityRepo {
+    pool: SqlitePool,
+}
+
+impl SqliteIdentityRepo {
+    pub fn new(pool: SqlitePool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl IdentityRepository for SqliteIdentityRepo {
+    async fn create(&self, identity: ExternalIdentity) -> Result<ExternalIdentity, DomainError> {
+        sqlx::query!(
+            r#"
+            INSERT INTO user_identities (issuer, subject, user_id, email, created_at)
+            VALUES (?, ?, ?, ?, ?)
+            "#,
+            identity.issuer,
+            identity.subject,
+            identity.user_id,
+            identity.email,
+            identity.created_at,
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(identity)
+    }
+
+    #[tracing::instrument(name = "SqliteIdentityRepo::find", skip_all, fields(db.system = "sqlite"))]
+    async fn find(
+        &self,
+        issuer: &str,
+        subject: &str,
+    ) -> Result<Option<ExternalIdentity>, DomainError> {
+        let identity = sqlx::query_as!(
+            ExternalIdentity,
+            r#"
+            SELECT issuer, subject, user_id as "user_id: Uuid", email, created_at as "created_at: DateTime<Utc>"
+            FROM user_identities
+            WHERE issuer = ? AND subject = ?
+            "#,
+            issuer,
+            subject
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(identity)
+    }
+
+    async fn list_by_user(&self, user_id: Uuid) -> Result<Vec<ExternalIdentity>, DomainError> {
+        let identities = sqlx::query_as!(
+            ExternalIdentity,
+            r#"
+            SELECT issuer, subject, user_id as "user_id: Uuid", email, created_at as "created_at: DateTime<Utc>"
+            FROM user_identities
+            WHERE user_id = ?
+            ORDER BY created_at
+            "#,
+            user_id
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(identities)
+    }
+}
@@ -0,0 +1,185 @@
+use crate::domain::{error::DomainError, model::media::Media, repository::MediaRepository};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use sqlx::SqlitePool;
+use uuid::Uuid;
+
+#[derive(Clone)]
+pub struct SqliteMediaRepo {
+    pool: SqlitePool,
+}
+
+impl SqliteMediaRepo {
+    pub fn new(pool: SqlitePool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl MediaRepository for SqliteMediaRepo {
+    async fn create(&self, media: Media) -> Result<Media, DomainError> {
+        sqlx::query!(
+            r#"
+            INSERT INTO media (id, owner_id, sha256, mime_type, size_bytes, original_name, created_at)
+            VALUES (?, ?, ?, ?, ?, ?, ?)
+            "#,
+            media.id,
+            media.owner_id,
+            media.sha256,
+            media.mime_type,
+            media.size_bytes,
+            media.original_name,
+            media.created_at,
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(media)
+    }
+
+    async fn find_by_id(&self, id: Uuid) -> Result<Option<Media>, DomainError> {
+        let media = sqlx::query_as!(
+            Media,
+            r#"
+            SELECT id as "id: Uuid", owner_id as "owner_id: Uuid", sha256, mime_type, size_bytes, original_name, created_at as "created_at: DateTime<Utc>"
+            FROM media
+            WHERE id = ?
+            "#,
+            id
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(media)
+    }
+
+    async fn find_by_owner_and_hash(
+        &self,
+        owner_id: Uuid,
+        sha256: &str,
+    ) -> Result<Option<Media>, DomainError> {
+        let media = sqlx::query_as!(
+            Media,
+            r#"
+            SELECT id as "id: Uuid", owner_id as "owner_id: Uuid", sha256, mime_type, size_bytes, original_name, created_at as "created_at: DateTime<Utc>"
+            FROM media
+            WHERE owner_id = ? AND sha256 = ?
+            "#,
+            owner_id,
+            sha256
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(media)
+    }
+
+    async fn list_by_owner(&self, owner_id: Uuid) -> Result<Vec<Media>, DomainError> {
+        let media = sqlx::query_as!(
+            Media,
+            r#"
+            SELECT id as "id: Uuid", owner_id as "owner_id: Uuid", sha256, mime_type, size_bytes, original_name, created_at as "created_at: DateTime<Utc>"
+            FROM media
+            WHERE owner_id = ?
+            ORDER BY created_at DESC
+            "#,
+            owner_id
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(media)
+    }
+
+    async fn delete(&self, id: Uuid) -> Result<(), DomainError> {
+        let result = sqlx::query!("DELETE FROM media WHERE id = ?", id)
+            .execute(&self.pool)
+            .await?;
+
+        if result.rows_affected() == 0 {
+            Err(DomainError::NotFound)
+        } else {
+            Ok(())
+        }
+    }
+
+    async fn count_by_hash(&self, sha256: &str) -> Result<i64, DomainError> {
+        let count = sqlx::query_scalar!(
+            r#"SELECT COUNT(*) as "count: i64" FROM media WHERE sha256 = ?"#,
+            sha256
+        )
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(count)
+    }
+
+    async fn is_referenced(&self, id: Uuid) -> Result<bool, DomainError> {
+        let referenced = sqlx::query_scalar!(
+            r#"
+            SELECT EXISTS (
+                SELECT 1 FROM post_media WHERE media_id = ?
+                UNION ALL
+                SELECT 1 FROM posts WHERE cover_media_id = ?
+            ) as "referenced: bool"
+            "#,
+            id,
+            id
+        )
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(referenced)
+    }
+
+    async fn attach(&self, post_id: Uuid, media_id: Uuid) -> Result<(), DomainError> {
+        sqlx::query!(
+            r#"
+            INSERT INTO post_media (post_id, media_id, position)
+            VALUES (?, ?, (SELECT COALESCE(MAX(position), -1) + 1 FROM post_media WHERE post_id = ?))
+            ON CONFLICT (post_id, media_id) DO NOTHING
+            "#,
+            post_id,
+            media_id,
+            post_id
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn detach(&self, post_id: Uuid, media_id: Uuid) -> Result<(), DomainError> {
+        let result = sqlx::query!(
+            "DELETE FROM post_media WHERE post_id = ? AND media_id = ?",
+            post_id,
+            media_id
+        )
+        .execute(&self.pool)
+        .await?;
+
+        if result.rows_affected() == 0 {
+            Err(DomainError::NotFound)
+        } else {
+            Ok(())
+        }
+    }
+
+    async fn list_for_post(&self, post_id: Uuid) -> Result<Vec<Media>, DomainError> {
+        let media = sqlx::query_as!(
+            Media,
+            r#"
+            SELECT m.id as "id: Uuid", m.owner_id as "owner_id: Uuid", m.sha256, m.mime_type, m.size_bytes, m.original_name, m.created_at as "created_at: DateTime<Utc>"
+            FROM post_media pm
+            JOIN media m ON m.id = pm.media_id
+            WHERE pm.post_id = ?
+            ORDER BY pm.position
+            "#,
+            post_id
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(media)
+    }
+}
@@ -1,10 +1,13 @@
 use crate::{
     domain::{
         error::DomainError,
-        model::post::{Post, PostWithAuthor},
+        model::{
+            media::media_storage_key,
+            post::{Post, PostWithAuthor},
+        },
         repository::PostRepository,
     },
-    interfaces::api::dto::user::UserPublic,
+    interfaces::api::dto::user::{UserPublic, avatar_links},
 };
 use async_trait::async_trait;
 use chrono::{DateTime, Utc};
@@ -24,6 +27,7 @@ impl SqlitePostRepo {
 
 #[async_trait]
 impl PostRepository for SqlitePostRepo {
+    #[tracing::instrument(name = "SqlitePostRepo::list", skip_all, fields(db.system = "sqlite"))]
     async fn list(&self) -> Result<Vec<PostWithAuthor>, DomainError> {
         let rows = sqlx::query!(
             r#"
@@ -34,13 +38,21 @@ impl PostRepository for SqlitePostRepo {
                 p.published as "published: bool",
                 p.created_at as "created_at: DateTime<Utc>",
                 p.updated_at as "updated_at: DateTime<Utc>",
+                p.cover_media_id as "cover_media_id?: Uuid",
+                c.sha256 as "cover_sha256?",
 
                 u.id as "user_id: Uuid",
                 u.username,
-                u.email,
+                u.first_name,
+                u.last_name,
+                u.display_name,
+                u.bio,
+                u.website,
+                u.avatar_key,
                 u.created_at as "user_created_at: DateTime<Utc>"
                 FROM posts p
                 JOIN users u ON p.user_id = u.id
+                LEFT JOIN media c ON c.id = p.cover_media_id
                 ORDER BY p.created_at DESC
             "#
         )
@@ -49,25 +61,39 @@ impl PostRepository for SqlitePostRepo {
 
         let posts = rows
             .into_iter()
-            .map(|row| PostWithAuthor {
-                id: row.post_id.expect("Post ID is required"),
-                title: row.title.expect("Title is required"),
-                content: row.content.expect("Content is required"),
-                published: row.published.expect("Published status is required"),
-                created_at: row.created_at.expect("Created at is required"),
-                updated_at: row.updated_at,
-                author: UserPublic {
-                    id: row.user_id,
-                    username: row.username.clone(),
-                    email: row.email.clone(),
-                    created_at: row.user_created_at,
-                },
+            .map(|row| {
+                let (avatar_url, avatar_thumbnails) = avatar_links(row.avatar_key.as_deref());
+
+                PostWithAuthor {
+                    id: row.post_id.expect("Post ID is required"),
+                    title: row.title.expect("Title is required"),
+                    content: row.content.expect("Content is required"),
+                    published: row.published.expect("Published status is required"),
+                    created_at: row.created_at.expect("Created at is required"),
+                    updated_at: row.updated_at,
+                    cover_media_id: row.cover_media_id,
+                    cover_key: row.cover_sha256.map(|sha| media_storage_key(&sha)),
+                    cover_url: None,
+                    author: UserPublic {
+                        id: row.user_id,
+                        username: row.username.clone(),
+                        first_name: row.first_name,
+                        last_name: row.last_name,
+                        display_name: row.display_name,
+                        bio: row.bio,
+                        website: row.website,
+                        avatar_url,
+                        avatar_thumbnails,
+                        created_at: row.user_created_at,
+                    },
+                }
             })
             .collect();
 
         Ok(posts)
     }
 
+    #[tracing::instrument(name = "SqlitePostRepo::find_by_id", skip_all, fields(db.system = "sqlite", %id))]
     async fn find_by_id(&self, id: Uuid) -> Result<Option<PostWithAuthor>, DomainError> {
         let row = sqlx::query!(
             r#"
@@ -77,12 +103,20 @@ impl PostRepository for SqlitePostRepo {
             p.published as "published: bool", 
             p.created_at as "created_at: DateTime<Utc>", 
             p.updated_at as "updated_at: DateTime<Utc>",
+            p.cover_media_id as "cover_media_id?: Uuid",
+            c.sha256 as "cover_sha256?",
             u.id as "user_id: Uuid",
             u.username,
-            u.email,
+            u.first_name,
+            u.last_name,
+            u.display_name,
+            u.bio,
+            u.website,
+            u.avatar_key,
             u.created_at as "user_created_at: DateTime<Utc>"
             FROM posts p
             JOIN users u ON p.user_id = u.id
+            LEFT JOIN media c ON c.id = p.cover_media_id
             WHERE p.id = ?
             "#,
             id
@@ -90,30 +124,70 @@ impl PostRepository for SqlitePostRepo {
         .fetch_optional(&self.pool)
         .await?;
 
-        let post_with_author = row.map(|row| PostWithAuthor {
-            id: row.post_id,
-            title: row.title,
-            content: row.content,
-            published: row.published,
-            created_at: row.created_at,
-            updated_at: row.updated_at,
-            author: UserPublic {
-                id: row.user_id,
-                username: row.username.clone(),
-                email: row.email.clone(),
-                created_at: row.user_created_at,
-            },
+        let post_with_author = row.map(|row| {
+            let (avatar_url, avatar_thumbnails) = avatar_links(row.avatar_key.as_deref());
+
+            PostWithAuthor {
+                id: row.post_id,
+                title: row.title,
+                content: row.content,
+                published: row.published,
+                created_at: row.created_at,
+                updated_at: row.updated_at,
+                cover_media_id: row.cover_media_id,
+                cover_key: row.cover_sha256.map(|sha| media_storage_key(&sha)),
+                cover_url: None,
+                author: UserPublic {
+                    id: row.user_id,
+                    username: row.username.clone(),
+                    first_name: row.first_name,
+                    last_name: row.last_name,
+                    display_name: row.display_name,
+                    bio: row.bio,
+                    website: row.website,
+                    avatar_url,
+                    avatar_thumbnails,
+                    created_at: row.user_created_at,
+                },
+            }
         });
 
         Ok(post_with_author)
     }
 
+    #[tracing::instrument(name = "SqlitePostRepo::list_by_author", skip_all, fields(db.system = "sqlite", %user_id))]
+    async fn list_by_author(&self, user_id: Uuid) -> Result<Vec<Post>, DomainError> {
+        let posts = sqlx::query_as!(
+            Post,
+            r#"
+            SELECT
+            id as "id: Uuid",
+            user_id as "user_id: Uuid",
+            title,
+            content,
+            published as "published: bool",
+            created_at as "created_at: DateTime<Utc>",
+            updated_at as "updated_at: DateTime<Utc>",
+            cover_media_id as "cover_media_id: Uuid"
+            FROM posts
+            WHERE user_id = ?
+            ORDER BY created_at DESC
+            "#,
+            user_id
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(posts)
+    }
+
+    #[tracing::instrument(name = "SqlitePostRepo::create", skip_all, fields(db.system = "sqlite"))]
     async fn create(&self, new_post: Post) -> Result<Post, DomainError> {
         sqlx::query_as!(
             Post,
             r#"
-            INSERT INTO posts (id, user_id, title, content, published, created_at)
-            VALUES (?, ?, ?, ?, ?, ?)
+            INSERT INTO posts (id, user_id, title, content, published, created_at, cover_media_id)
+            VALUES (?, ?, ?, ?, ?, ?, ?)
             "#,
             new_post.id,
             new_post.user_id,
@@ -121,6 +195,7 @@ impl PostRepository for SqlitePostRepo {
             new_post.content,
             new_post.published,
             new_post.created_at,
+            new_post.cover_media_id,
         )
         .execute(&self.pool)
         .await?;
@@ -128,6 +203,7 @@ impl PostRepository for SqlitePostRepo {
         Ok(new_post)
     }
 
+    #[tracing::instrument(name = "SqlitePostRepo::update", skip_all, fields(db.system = "sqlite"))]
     async fn update(&self, post: Post) -> Result<Post, DomainError> {
         let now = Utc::now();
 
@@ -138,6 +214,7 @@ impl PostRepository for SqlitePostRepo {
                 title = ?,
                 content = ?,
                 published = ?,
+                cover_media_id = ?,
                 updated_at = ?
             WHERE id = ?
             "#,
@@ -145,6 +222,7 @@ impl PostRepository for SqlitePostRepo {
             post.title,
             post.content,
             post.published,
+            post.cover_media_id,
             now,
             post.id
         )
@@ -157,6 +235,7 @@ impl PostRepository for SqlitePostRepo {
         })
     }
 
+    #[tracing::instrument(name = "SqlitePostRepo::delete", skip_all, fields(db.system = "sqlite", %id))]
     async fn delete(&self, id: Uuid) -> Result<(), DomainError> {
         let result = sqlx::query!("DELETE FROM posts WHERE id = ?", id)
             .execute(&self.pool)
@@ -21,11 +21,12 @@ impl SqliteUserRepo {
 
 #[async_trait]
 impl UserRepository for SqliteUserRepo {
+    #[tracing::instrument(name = "SqliteUserRepo::list", skip_all, fields(db.system = "sqlite"))]
     async fn list(&self) -> Result<Vec<User>, DomainError> {
         let rows = sqlx::query_as!(
             User,
             r#"
-            SELECT id as "id: Uuid", username, role as "role: Role", password_hash, email, created_at as "created_at: DateTime<Utc>", updated_at as "updated_at: DateTime<Utc>"
+            SELECT id as "id: Uuid", username, role as "role: Role", password_hash, email, first_name, last_name, display_name, bio, website, avatar_key, created_at as "created_at: DateTime<Utc>", updated_at as "updated_at: DateTime<Utc>", erased_at as "erased_at: DateTime<Utc>", tokens_valid_after as "tokens_valid_after: DateTime<Utc>"
             FROM users
             ORDER BY created_at DESC
             "#
@@ -35,21 +36,29 @@ impl UserRepository for SqliteUserRepo {
         Ok(rows)
     }
 
+    #[tracing::instrument(name = "SqliteUserRepo::create", skip_all, fields(db.system = "sqlite"))]
     async fn create(&self, user: User) -> Result<User, DomainError> {
         let res = sqlx::query_as!(
             User,
             r#"
-            INSERT INTO users (id, username, role, password_hash, email, created_at, updated_at)
-            VALUES (?, ?, ?, ?, ?, ?, ?)
-            RETURNING id as "id: Uuid", username, email, role as "role: Role", password_hash, created_at as "created_at: DateTime<Utc>", updated_at as "updated_at: DateTime<Utc>"
+            INSERT INTO users (id, username, role, password_hash, email, first_name, last_name, display_name, bio, website, avatar_key, created_at, updated_at, tokens_valid_after)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+            RETURNING id as "id: Uuid", username, role as "role: Role", password_hash, email, first_name, last_name, display_name, bio, website, avatar_key, created_at as "created_at: DateTime<Utc>", updated_at as "updated_at: DateTime<Utc>", erased_at as "erased_at: DateTime<Utc>", tokens_valid_after as "tokens_valid_after: DateTime<Utc>"
             "#,
             user.id,
             user.username,
             user.role,
             user.password_hash,
             user.email,
+            user.first_name,
+            user.last_name,
+            user.display_name,
+            user.bio,
+            user.website,
+            user.avatar_key,
             user.created_at,
             user.updated_at,
+            user.tokens_valid_after,
         )
         .fetch_one(&self.pool)
         .await;
@@ -63,11 +72,12 @@ impl UserRepository for SqliteUserRepo {
         }
     }
 
+    #[tracing::instrument(name = "SqliteUserRepo::find_by_id", skip_all, fields(db.system = "sqlite", %id))]
     async fn find_by_id(&self, id: Uuid) -> Result<Option<User>, DomainError> {
         let user = sqlx::query_as!(
             User,
             r#"
-            SELECT id as "id: Uuid", username, role as "role: Role",password_hash, email, created_at as "created_at: DateTime<Utc>", updated_at as "updated_at: DateTime<Utc>"
+            SELECT id as "id: Uuid", username, role as "role: Role", password_hash, email, first_name, last_name, display_name, bio, website, avatar_key, created_at as "created_at: DateTime<Utc>", updated_at as "updated_at: DateTime<Utc>", erased_at as "erased_at: DateTime<Utc>", tokens_valid_after as "tokens_valid_after: DateTime<Utc>"
             FROM users
             WHERE id = ?
             "#,
@@ -79,11 +89,12 @@ impl UserRepository for SqliteUserRepo {
         Ok(user)
     }
 
+    #[tracing::instrument(name = "SqliteUserRepo::find_by_username", skip_all, fields(db.system = "sqlite"))]
     async fn find_by_username(&self, username: &str) -> Result<Option<User>, DomainError> {
         let user = sqlx::query_as!(
             User,
             r#"
-            SELECT id as "id: Uuid", username, role as "role: Role", password_hash, email, created_at as "created_at: DateTime<Utc>", updated_at as "updated_at: DateTime<Utc>"
+            SELECT id as "id: Uuid", username, role as "role: Role", password_hash, email, first_name, last_name, display_name, bio, website, avatar_key, created_at as "created_at: DateTime<Utc>", updated_at as "updated_at: DateTime<Utc>", erased_at as "erased_at: DateTime<Utc>", tokens_valid_after as "tokens_valid_after: DateTime<Utc>"
             FROM users
             WHERE username = ?
             "#,
@@ -95,21 +106,46 @@ impl UserRepository for SqliteUserRepo {
         Ok(user)
     }
 
+    #[tracing::instrument(name = "SqliteUserRepo::find_by_email", skip_all, fields(db.system = "sqlite"))]
+    async fn find_by_email(&self, email: &str) -> Result<Option<User>, DomainError> {
+        let user = sqlx::query_as!(
+            User,
+            r#"
+            SELECT id as "id: Uuid", username, role as "role: Role", password_hash, email, first_name, last_name, display_name, bio, website, avatar_key, created_at as "created_at: DateTime<Utc>", updated_at as "updated_at: DateTime<Utc>", erased_at as "erased_at: DateTime<Utc>", tokens_valid_after as "tokens_valid_after: DateTime<Utc>"
+            FROM users
+            WHERE email = ? COLLATE NOCASE
+            "#,
+            email
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(user)
+    }
+
+    #[tracing::instrument(name = "SqliteUserRepo::update", skip_all, fields(db.system = "sqlite"))]
     async fn update(&self, user: User) -> Result<User, DomainError> {
         let now = Utc::now();
         let res = sqlx::query_as!(
             User,
             r#"
             UPDATE users
-            SET username = ?, password_hash = ?, email = ?, updated_at = ?, role = ?
+            SET username = ?, password_hash = ?, email = ?, first_name = ?, last_name = ?, display_name = ?, bio = ?, website = ?, avatar_key = ?, updated_at = ?, role = ?, tokens_valid_after = ?
             WHERE id = ?
-            RETURNING id as "id: Uuid", username, role as "role: Role", password_hash, email, created_at as "created_at: DateTime<Utc>", updated_at as "updated_at: DateTime<Utc>"
+            RETURNING id as "id: Uuid", username, role as "role: Role", password_hash, email, first_name, last_name, display_name, bio, website, avatar_key, created_at as "created_at: DateTime<Utc>", updated_at as "updated_at: DateTime<Utc>", erased_at as "erased_at: DateTime<Utc>", tokens_valid_after as "tokens_valid_after: DateTime<Utc>"
             "#,
             user.username,
             user.password_hash,
             user.email,
+            user.first_name,
+            user.last_name,
+            user.display_name,
+            user.bio,
+            user.website,
+            user.avatar_key,
             now,
             user.role,
+            user.tokens_valid_after,
             user.id
         )
         .fetch_one(&self.pool)
@@ -124,15 +160,93 @@ impl UserRepository for SqliteUserRepo {
         }
     }
 
+    #[tracing::instrument(name = "SqliteUserRepo::count", skip_all, fields(db.system = "sqlite"))]
+    async fn count(&self) -> Result<i64, DomainError> {
+        let count = sqlx::query_scalar!(r#"SELECT COUNT(*) as "count: i64" FROM users"#)
+            .fetch_one(&self.pool)
+            .await?;
+
+        Ok(count)
+    }
+
+    #[tracing::instrument(name = "SqliteUserRepo::anonymize", skip_all, fields(db.system = "sqlite", %id))]
+    async fn anonymize(
+        &self,
+        id: Uuid,
+        pseudonym: &str,
+        email: &str,
+        erased_at: DateTime<Utc>,
+    ) -> Result<User, DomainError> {
+        let mut tx = self.pool.begin().await?;
+
+        let user = sqlx::query_as!(
+            User,
+            r#"
+            UPDATE users
+            SET username = ?, email = ?, password_hash = '!', first_name = NULL, last_name = NULL, display_name = NULL, bio = NULL, website = NULL, avatar_key = NULL, updated_at = ?, erased_at = ?, tokens_valid_after = ?
+            WHERE id = ?
+            RETURNING id as "id: Uuid", username, role as "role: Role", password_hash, email, first_name, last_name, display_name, bio, website, avatar_key, created_at as "created_at: DateTime<Utc>", updated_at as "updated_at: DateTime<Utc>", erased_at as "erased_at: DateTime<Utc>", tokens_valid_after as "tokens_valid_after: DateTime<Utc>"
+            "#,
+            pseudonym,
+            email,
+            erased_at,
+            erased_at,
+            erased_at,
+            id
+        )
+        .fetch_optional(&mut *tx)
+        .await?;
+
+        let user = user.ok_or(DomainError::NotFound)?;
+
+        sqlx::query!("DELETE FROM user_identities WHERE user_id = ?", id)
+            .execute(&mut *tx)
+            .await?;
+        sqlx::query!("DELETE FROM access_tokens WHERE user_id = ?", id)
+            .execute(&mut *tx)
+            .await?;
+        sqlx::query!(
+            "UPDATE posts SET cover_media_id = NULL WHERE cover_media_id IN (SELECT id FROM media WHERE owner_id = ?)",
+            id
+        )
+        .execute(&mut *tx)
+        .await?;
+        sqlx::query!(
+            "DELETE FROM post_media WHERE media_id IN (SELECT id FROM media WHERE owner_id = ?)",
+            id
+        )
+        .execute(&mut *tx)
+        .await?;
+        sqlx::query!("DELETE FROM media WHERE owner_id = ?", id)
+            .execute(&mut *tx)
+            .await?;
+
+        tx.commit().await?;
+
+        Ok(user)
+    }
+
+    #[tracing::instrument(name = "SqliteUserRepo::delete", skip_all, fields(db.system = "sqlite", %id))]
     async fn delete(&self, id: Uuid) -> Result<(), DomainError> {
+        let mut tx = self.pool.begin().await?;
+
+        // post_media.media_id is ON DELETE RESTRICT: the media going with the
+        // user are detached first.
+        sqlx::query!(
+            "DELETE FROM post_media WHERE media_id IN (SELECT id FROM media WHERE owner_id = ?)",
+            id
+        )
+        .execute(&mut *tx)
+        .await?;
         let result = sqlx::query!("DELETE FROM users WHERE id = ?", id)
-            .execute(&self.pool)
+            .execute(&mut *tx)
             .await?;
 
         if result.rows_affected() == 0 {
-            Err(DomainError::NotFound)
-        } else {
-            Ok(())
+            return Err(DomainError::NotFound);
         }
+        tx.commit().await?;
+
+        Ok(())
     }
 }
@@ -4,6 +4,8 @@ use actix_cors::Cors;
 use actix_web::http::header::{AUTHORIZATION, CONTENT_TYPE, HeaderName};
 use regex::Regex;
 
+use crate::infrastructure::auth::session::CSRF_HEADER;
+
 pub fn build_cors(origins: &str) -> Cors {
     let regexes: Vec<Regex> = origins
         .split(';')
@@ -23,6 +25,7 @@ pub fn build_cors(origins: &str) -> Cors {
             AUTHORIZATION,
             CONTENT_TYPE,
             HeaderName::from_static("x-requested-with"),
+            HeaderName::from_static(CSRF_HEADER),
         ])
         .max_age(21_600)
         .allowed_origin_fn(move |origin, _req_head| {
@@ -1,4 +1,6 @@
+use hmac::{Hmac, Mac};
 use jsonwebtoken::{DecodingKey, EncodingKey};
+use sha2::{Digest, Sha256};
 
 #[derive(Clone)]
 pub struct Keys {
@@ -13,4 +15,24 @@ impl Keys {
             decoding: DecodingKey::from_secret(secret),
         }
     }
+
+    /// Keys for `purpose` only: what they sign is never accepted as an
+    /// access token, nor the other way round.
+    pub fn derived(secret: &[u8], purpose: &str) -> Self {
+        Self::new(&derive_secret(secret, purpose))
+    }
+}
+
+/// HMAC-SHA256 of `purpose` under `secret`, so that a single configured
+/// secret can back several independent keys.
+pub fn derive_secret(secret: &[u8], purpose: &str) -> Vec<u8> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret).expect("HMAC accepts keys of any size");
+    mac.update(purpose.as_bytes());
+    mac.finalize().into_bytes().to_vec()
+}
+
+/// Compares digests rather than the raw strings so that the comparison time
+/// does not depend on how much of a secret token was guessed.
+pub fn same_secret(expected: &str, candidate: &str) -> bool {
+    Sha256::digest(expected) == Sha256::digest(candidate)
 }
@@ -0,0 +1,98 @@
+use std::{
+    io::ErrorKind,
+    path::{Path, PathBuf},
+};
+
+use sha1::{Digest, Sha1};
+use tokio::fs;
+
+use crate::{
+    config::PasswordSettings,
+    domain::validation::{PasswordRequirements, PasswordViolation, password_violations},
+};
+
+/// The password rules of `PasswordSettings`, with the offline leaked password
+/// list when one is configured.
+#[derive(Clone)]
+pub struct PasswordPolicy {
+    requirements: PasswordRequirements,
+    breached: Option<BreachedPasswords>,
+}
+
+impl PasswordPolicy {
+    pub fn from_settings(settings: &PasswordSettings) -> Self {
+        Self {
+            requirements: PasswordRequirements {
+                min_length: settings.min_length,
+                max_length: settings.max_length,
+                require_uppercase: settings.require_uppercase,
+                require_lowercase: settings.require_lowercase,
+                require_digit: settings.require_digit,
+                require_special_char: settings.require_special_char,
+                min_score: settings.min_score,
+            },
+            breached: settings.breached_path.as_ref().map(BreachedPasswords::new),
+        }
+    }
+
+    /// Every rule `password` breaks; `personal` holds the username and email
+    /// of its owner.
+    pub async fn check(&self, password: &str, personal: &[&str]) -> Vec<PasswordViolation> {
+        let mut violations = password_violations(password, &self.requirements, personal);
+
+        if let Some(breached) = &self.breached
+            && breached.contains(password).await
+        {
+            violations.push(PasswordViolation::Breached);
+        }
+
+        violations
+    }
+}
+
+impl Default for PasswordPolicy {
+    fn default() -> Self {
+        Self::from_settings(&PasswordSettings::default())
+    }
+}
+
+/// k-anonymity style lookup: only the file of the first 5 hex characters of
+/// the SHA-1 is read, so the whole list never has to be loaded.
+#[derive(Clone)]
+pub struct BreachedPasswords {
+    dir: PathBuf,
+}
+
+impl BreachedPasswords {
+    pub fn new(dir: impl AsRef<Path>) -> Self {
+        Self {
+            dir: dir.as_ref().to_path_buf(),
+        }
+    }
+
+    /// A missing prefix file means no leaked password has that prefix; an
+    /// unreadable one is logged and lets the password through.
+    pub async fn contains(&self, password: &str) -> bool {
+        let hash = hex::encode_upper(Sha1::digest(password.as_bytes()));
+        let (prefix, suffix) = hash.split_at(5);
+
+        let content = match fs::read_to_string(self.dir.join(format!("{}.txt", prefix))).await {
+            Ok(content) => content,
+            Err(e) if e.kind() == ErrorKind::NotFound => return false,
+            Err(e) => {
+                log::warn!(
+                    "Failed to read the breached password list {}: {}",
+                    prefix,
+                    e
+                );
+                return false;
+            }
+        };
+
+        content.lines().any(|line| {
+            line.split(':')
+                .next()
+                .is_some_and(|candidate| candidate.trim().eq_ignore_ascii_case(suffix))
+        })
+    }
+}
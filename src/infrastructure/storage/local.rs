@@ -0,0 +1,119 @@
+use std::{
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::Utc;
+use tokio::fs;
+
+use crate::{
+    infrastructure::storage::{Blob, BlobStore, UrlSigner, validate_key},
+    interfaces::api::dto::file::file_url,
+};
+
+/// Stores blobs as plain files under `root`, with the content type kept in a
+/// `.meta` sidecar file. Files are served by `handlers::file`.
+#[derive(Clone)]
+pub struct LocalBlobStore {
+    root: PathBuf,
+    signer: UrlSigner,
+}
+
+impl LocalBlobStore {
+    pub fn new(root: impl Into<PathBuf>, signer: UrlSigner) -> Self {
+        Self {
+            root: root.into(),
+            signer,
+        }
+    }
+
+    fn path(&self, key: &str) -> Result<PathBuf> {
+        validate_key(key)?;
+        Ok(self.root.join(key))
+    }
+
+    fn meta_path(path: &Path) -> PathBuf {
+        let mut meta = path.as_os_str().to_owned();
+        meta.push(".meta");
+        PathBuf::from(meta)
+    }
+}
+
+#[async_trait]
+impl BlobStore for LocalBlobStore {
+    async fn put(&self, key: &str, bytes: Vec<u8>, content_type: &str) -> Result<()> {
+        let path = self.path(key)?;
+
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).await?;
+        }
+
+        fs::write(&path, bytes).await?;
+        fs::write(Self::meta_path(&path), content_type).await?;
+
+        Ok(())
+    }
+
+    async fn get(&self, key: &str) -> Result<Option<Blob>> {
+        let path = self.path(key)?;
+
+        let bytes = match fs::read(&path).await {
+            Ok(bytes) => bytes,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e.into()),
+        };
+
+        let content_type = fs::read_to_string(Self::meta_path(&path))
+            .await
+            .unwrap_or_else(|_| "application/octet-stream".to_string());
+
+        Ok(Some(Blob {
+            bytes,
+            content_type,
+        }))
+    }
+
+    async fn delete(&self, key: &str) -> Result<()> {
+        let path = self.path(key)?;
+
+        for p in [Self::meta_path(&path), path] {
+            match fs::remove_file(&p).await {
+                Ok(()) => {}
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+                Err(e) => return Err(e.into()),
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn delete_prefix(&self, prefix: &str) -> Result<()> {
+        let path = self.path(prefix.trim_end_matches('/'))?;
+
+        match fs::remove_dir_all(&path).await {
+            Ok(()) => Ok(()),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    async fn signed_url(&self, key: &str, expires_in: Duration) -> Result<String> {
+        validate_key(key)?;
+
+        let expires = Utc::now().timestamp() + expires_in.as_secs() as i64;
+        let signature = self.signer.sign(key, expires);
+
+        Ok(format!(
+            "{}?expires={}&signature={}",
+            file_url(key),
+            expires,
+            signature
+        ))
+    }
+
+    fn verify_signature(&self, key: &str, expires: i64, signature: &str) -> bool {
+        self.signer.verify(key, expires, signature)
+    }
+}
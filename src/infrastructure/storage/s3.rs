@@ -0,0 +1,350 @@
+use std::time::Duration;
+
+use anyhow::{Context, Result, bail};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac};
+use regex::Regex;
+use reqwest::{Client, Method, StatusCode, header::CONTENT_TYPE};
+use sha2::{Digest, Sha256};
+use url::Url;
+
+use crate::{
+    config::S3Settings,
+    infrastructure::storage::{Blob, BlobStore, validate_key},
+};
+
+/// Longest validity AWS accepts for a presigned URL.
+const MAX_PRESIGN_SECS: u64 = 7 * 24 * 3600;
+/// Validity of the URLs the store presigns for its own requests.
+const REQUEST_PRESIGN_SECS: u64 = 300;
+
+/// S3 compatible object storage (AWS, MinIO, Garage…), addressed path-style,
+/// `{endpoint}/{bucket}/{key}`, unless `path_style` is turned off.
+///
+/// Every call, including the ones made by the API itself, goes through a
+/// SigV4 presigned URL so a single signing routine covers both uses.
+#[derive(Clone)]
+pub struct S3BlobStore {
+    client: Client,
+    endpoint: Url,
+    region: String,
+    bucket: String,
+    access_key: String,
+    secret_key: String,
+    path_style: bool,
+}
+
+impl S3BlobStore {
+    pub fn new(settings: &S3Settings) -> Result<Self> {
+        let endpoint = Url::parse(&settings.endpoint)
+            .with_context(|| format!("Invalid S3 endpoint '{}'", settings.endpoint))?;
+
+        Ok(Self {
+            client: Client::new(),
+            endpoint,
+            region: settings.region.clone(),
+            bucket: settings.bucket.clone(),
+            access_key: settings.access_key.clone(),
+            secret_key: settings.secret_key.clone(),
+            path_style: settings.path_style,
+        })
+    }
+
+    /// SigV4 query-string signature of a request on `key`, or on the bucket
+    /// itself when `None`, made at `now`.
+    pub fn presign(
+        &self,
+        method: &Method,
+        key: Option<&str>,
+        extra_query: &[(&str, &str)],
+        expires_in: Duration,
+        now: DateTime<Utc>,
+    ) -> Result<Url> {
+        let mut host = match self.endpoint.port() {
+            Some(port) => format!("{}:{}", self.endpoint.host_str().unwrap_or_default(), port),
+            None => self.endpoint.host_str().unwrap_or_default().to_string(),
+        };
+
+        let mut path = self.endpoint.path().trim_end_matches('/').to_string();
+        if self.path_style {
+            path.push('/');
+            path.push_str(&uri_encode(&self.bucket, true));
+        } else {
+            host = format!("{}.{}", self.bucket, host);
+        }
+        match key {
+            Some(key) => {
+                path.push('/');
+                path.push_str(&uri_encode(key, false));
+            }
+            None if path.is_empty() => path.push('/'),
+            None => {}
+        }
+
+        let amz_date = now.format("%Y%m%dT%H%M%SZ").to_string();
+        let date = now.format("%Y%m%d").to_string();
+        let scope = format!("{}/{}/s3/aws4_request", date, self.region);
+        let expires = expires_in.as_secs().clamp(1, MAX_PRESIGN_SECS).to_string();
+        let credential = format!("{}/{}", self.access_key, scope);
+
+        let mut query: Vec<(String, String)> = vec![
+            ("X-Amz-Algorithm".into(), "AWS4-HMAC-SHA256".into()),
+            ("X-Amz-Credential".into(), credential),
+            ("X-Amz-Date".into(), amz_date.clone()),
+            ("X-Amz-Expires".into(), expires),
+            ("X-Amz-SignedHeaders".into(), "host".into()),
+        ];
+        query.extend(
+            extra_query
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.to_string())),
+        );
+        let mut encoded: Vec<(String, String)> = query
+            .iter()
+            .map(|(k, v)| (uri_encode(k, true), uri_encode(v, true)))
+            .collect();
+        encoded.sort();
+        let canonical_query = encoded
+            .iter()
+            .map(|(k, v)| format!("{}={}", k, v))
+            .collect::<Vec<_>>()
+            .join("&");
+
+        let canonical_request = format!(
+            "{}\n{}\n{}\nhost:{}\n\nhost\nUNSIGNED-PAYLOAD",
+            method.as_str(),
+            path,
+            canonical_query,
+            host
+        );
+        let string_to_sign = format!(
+            "AWS4-HMAC-SHA256\n{}\n{}\n{}",
+            amz_date,
+            scope,
+            hex::encode(Sha256::digest(canonical_request.as_bytes()))
+        );
+
+        let k_date = hmac(format!("AWS4{}", self.secret_key).as_bytes(), &date);
+        let k_region = hmac(&k_date, &self.region);
+        let k_service = hmac(&k_region, "s3");
+        let k_signing = hmac(&k_service, "aws4_request");
+        let signature = hex::encode(hmac(&k_signing, &string_to_sign));
+
+        let url = format!(
+            "{}://{}{}?{}&X-Amz-Signature={}",
+            self.endpoint.scheme(),
+            host,
+            path,
+            canonical_query,
+            signature
+        );
+
+        Ok(Url::parse(&url)?)
+    }
+
+    fn request_url(
+        &self,
+        method: &Method,
+        key: Option<&str>,
+        query: &[(&str, &str)],
+    ) -> Result<Url> {
+        self.presign(
+            method,
+            key,
+            query,
+            Duration::from_secs(REQUEST_PRESIGN_SECS),
+            Utc::now(),
+        )
+    }
+
+    async fn list_keys(&self, prefix: &str) -> Result<Vec<String>> {
+        let key_re = Regex::new(r"<Key>([^<]*)</Key>")?;
+        let token_re = Regex::new(r"<NextContinuationToken>([^<]*)</NextContinuationToken>")?;
+
+        let mut keys = Vec::new();
+        let mut token: Option<String> = None;
+
+        loop {
+            let mut query = vec![("list-type", "2"), ("prefix", prefix)];
+            if let Some(token) = token.as_deref() {
+                query.push(("continuation-token", token));
+            }
+
+            let url = self.request_url(&Method::GET, None, &query)?;
+            let response = self.client.get(url).send().await?;
+            if !response.status().is_success() {
+                bail!("S3 list failed with status {}", response.status());
+            }
+            let body = response.text().await?;
+
+            keys.extend(key_re.captures_iter(&body).map(|c| xml_unescape(&c[1])));
+
+            match token_re.captures(&body) {
+                Some(c) => token = Some(xml_unescape(&c[1])),
+                None => break,
+            }
+        }
+
+        Ok(keys)
+    }
+}
+
+#[async_trait]
+impl BlobStore for S3BlobStore {
+    async fn put(&self, key: &str, bytes: Vec<u8>, content_type: &str) -> Result<()> {
+        validate_key(key)?;
+
+        let url = self.request_url(&Method::PUT, Some(key), &[])?;
+        let response = self
+            .client
+            .put(url)
+            .header(CONTENT_TYPE, content_type)
+            .body(bytes)
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            bail!(
+                "S3 upload of '{}' failed with status {}",
+                key,
+                response.status()
+            );
+        }
+
+        Ok(())
+    }
+
+    async fn get(&self, key: &str) -> Result<Option<Blob>> {
+        validate_key(key)?;
+
+        let url = self.request_url(&Method::GET, Some(key), &[])?;
+        let response = self.client.get(url).send().await?;
+
+        match response.status() {
+            StatusCode::NOT_FOUND => Ok(None),
+            status if status.is_success() => {
+                let content_type = response
+                    .headers()
+                    .get(CONTENT_TYPE)
+                    .and_then(|v| v.to_str().ok())
+                    .unwrap_or("application/octet-stream")
+                    .to_string();
+                let bytes = response.bytes().await?.to_vec();
+
+                Ok(Some(Blob {
+                    bytes,
+                    content_type,
+                }))
+            }
+            status => bail!("S3 download of '{}' failed with status {}", key, status),
+        }
+    }
+
+    async fn delete(&self, key: &str) -> Result<()> {
+        validate_key(key)?;
+
+        let url = self.request_url(&Method::DELETE, Some(key), &[])?;
+        let response = self.client.delete(url).send().await?;
+
+        // S3 answers 204 whether or not the object existed.
+        if !response.status().is_success() && response.status() != StatusCode::NOT_FOUND {
+            bail!(
+                "S3 delete of '{}' failed with status {}",
+                key,
+                response.status()
+            );
+        }
+
+        Ok(())
+    }
+
+    async fn delete_prefix(&self, prefix: &str) -> Result<()> {
+        let prefix = format!("{}/", prefix.trim_end_matches('/'));
+        validate_key(prefix.trim_end_matches('/'))?;
+
+        for key in self.list_keys(&prefix).await? {
+            self.delete(&key).await?;
+        }
+
+        Ok(())
+    }
+
+    async fn signed_url(&self, key: &str, expires_in: Duration) -> Result<String> {
+        validate_key(key)?;
+
+        Ok(self
+            .presign(&Method::GET, Some(key), &[], expires_in, Utc::now())?
+            .to_string())
+    }
+}
+
+fn hmac(key: &[u8], data: &str) -> Vec<u8> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC accepts keys of any size");
+    mac.update(data.as_bytes());
+    mac.finalize().into_bytes().to_vec()
+}
+
+/// URI encoding as specified by SigV4: unreserved characters are kept, `/` is
+/// kept in paths only.
+fn uri_encode(input: &str, encode_slash: bool) -> String {
+    let mut out = String::with_capacity(input.len());
+
+    for byte in input.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                out.push(byte as char)
+            }
+            b'/' if !encode_slash => out.push('/'),
+            _ => out.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+
+    out
+}
+
+/// Decodes the predefined entities and the character references of XML
+/// text in a single pass, so that `&amp;lt;` stays `&lt;`. Anything else
+/// starting with `&` is kept as is.
+fn xml_unescape(input: &str) -> String {
+    let mut out = String::with_capacity(input.len());
+    let mut rest = input;
+
+    while let Some(start) = rest.find('&') {
+        out.push_str(&rest[..start]);
+        rest = &rest[start..];
+
+        let decoded = rest
+            .find(';')
+            .and_then(|end| xml_entity(&rest[1..end]).map(|c| (c, end)));
+        match decoded {
+            Some((c, end)) => {
+                out.push(c);
+                rest = &rest[end + 1..];
+            }
+            None => {
+                out.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    out.push_str(rest);
+
+    out
+}
+
+fn xml_entity(name: &str) -> Option<char> {
+    let code = match name {
+        "lt" => return Some('<'),
+        "gt" => return Some('>'),
+        "amp" => return Some('&'),
+        "quot" => return Some('"'),
+        "apos" => return Some('\''),
+        _ => match name.strip_prefix("#x").or_else(|| name.strip_prefix("#X")) {
+            Some(hex) => u32::from_str_radix(hex, 16).ok()?,
+            None => name.strip_prefix('#')?.parse().ok()?,
+        },
+    };
+
+    char::from_u32(code)
+}
@@ -0,0 +1,96 @@
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::{
+    domain::model::{
+        access_token::{AccessToken, Scope},
+        user::Role,
+    },
+    interfaces::api::{
+        error::ApiError,
+        validation::{FieldErrors, require_field},
+    },
+};
+
+/// Lifetime of a token created without `expires_in_days`.
+const DEFAULT_LIFETIME_DAYS: u32 = 30;
+
+/// A personal access token as listed to its owner, never with its secret.
+#[derive(Debug, Serialize)]
+pub struct AccessTokenView {
+    pub id: Uuid,
+    pub name: String,
+    pub prefix: String,
+    pub scopes: Vec<Scope>,
+    #[serde(rename = "createdAt")]
+    pub created_at: DateTime<Utc>,
+    #[serde(rename = "expiresAt")]
+    pub expires_at: DateTime<Utc>,
+    #[serde(rename = "lastUsedAt")]
+    pub last_used_at: Option<DateTime<Utc>>,
+    #[serde(rename = "revokedAt")]
+    pub revoked_at: Option<DateTime<Utc>>,
+}
+
+impl From<AccessToken> for AccessTokenView {
+    fn from(token: AccessToken) -> Self {
+        Self {
+            id: token.id,
+            name: token.name,
+            prefix: token.prefix,
+            scopes: token.scopes,
+            created_at: token.created_at,
+            expires_at: token.expires_at,
+            last_used_at: token.last_used_at,
+            revoked_at: token.revoked_at,
+        }
+    }
+}
+
+/// Answer to the creation, the only one holding the secret.
+#[derive(Debug, Serialize)]
+pub struct CreatedAccessToken {
+    #[serde(flatten)]
+    pub view: AccessTokenView,
+    pub token: String,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct NewAccessToken {
+    #[validate(length(min = 1, max = 100), required)]
+    pub name: Option<String>,
+    pub scopes: Option<Vec<Scope>>,
+    #[validate(range(min = 1, max = 365))]
+    pub expires_in_days: Option<u32>,
+}
+
+impl NewAccessToken {
+    /// `role` is the one of the owner: only administrators may grant `admin`.
+    pub fn validate_and_into_domain(
+        self,
+        role: &Role,
+    ) -> Result<(String, Vec<Scope>, DateTime<Utc>), ApiError> {
+        let mut errors = FieldErrors::of(&self);
+        match &self.scopes {
+            None => errors.add("scopes", "required", &[]),
+            Some(scopes) if scopes.is_empty() => errors.add("scopes", "required", &[]),
+            Some(scopes) if scopes.contains(&Scope::Admin) && *role != Role::Admin => {
+                errors.add(
+                    "scopes",
+                    "scope_not_allowed",
+                    &[("scope", Scope::Admin.to_string())],
+                );
+            }
+            Some(_) => {}
+        }
+        errors.into_result()?;
+
+        let name = require_field(self.name, "name")?;
+        let scopes = require_field(self.scopes, "scopes")?;
+        let days = self.expires_in_days.unwrap_or(DEFAULT_LIFETIME_DAYS);
+
+        Ok((name, scopes, Utc::now() + Duration::days(days.into())))
+    }
+}
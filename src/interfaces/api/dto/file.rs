@@ -0,0 +1,4 @@
+/// Public URL under which a stored blob is served by `handlers::file`.
+pub fn file_url(key: &str) -> String {
+    format!("/api/files/{}", key)
+}
@@ -0,0 +1,57 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::domain::model::audit::{AuditAction, AuditEntry};
+
+/// Entries of `/api/audit` without `limit`, and the most it may ask for.
+const DEFAULT_AUDIT_LIMIT: u32 = 50;
+const MAX_AUDIT_LIMIT: u32 = 500;
+
+#[derive(Debug, Serialize)]
+pub struct ImpersonationToken {
+    pub token: String,
+    #[serde(rename = "expiresAt")]
+    pub expires_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AuditQuery {
+    pub limit: Option<u32>,
+}
+
+impl AuditQuery {
+    pub fn limit(&self) -> i64 {
+        self.limit
+            .unwrap_or(DEFAULT_AUDIT_LIMIT)
+            .clamp(1, MAX_AUDIT_LIMIT)
+            .into()
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct AuditEntryView {
+    pub id: Uuid,
+    pub action: AuditAction,
+    #[serde(rename = "actorId")]
+    pub actor_id: Uuid,
+    #[serde(rename = "subjectId")]
+    pub subject_id: Option<Uuid>,
+    #[serde(rename = "sessionId")]
+    pub session_id: Option<Uuid>,
+    #[serde(rename = "createdAt")]
+    pub created_at: DateTime<Utc>,
+}
+
+impl From<AuditEntry> for AuditEntryView {
+    fn from(entry: AuditEntry) -> Self {
+        Self {
+            id: entry.id,
+            action: entry.action,
+            actor_id: entry.actor_id,
+            subject_id: entry.subject_id,
+            session_id: entry.session_id,
+            created_at: entry.created_at,
+        }
+    }
+}
@@ -0,0 +1,40 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::domain::model::media::Media;
+
+#[derive(Debug, Serialize)]
+pub struct MediaPublic {
+    pub id: Uuid,
+    pub url: String,
+    #[serde(rename = "mimeType")]
+    pub mime_type: String,
+    #[serde(rename = "sizeBytes")]
+    pub size_bytes: i64,
+    #[serde(rename = "originalName")]
+    pub original_name: Option<String>,
+    pub sha256: String,
+    #[serde(rename = "createdAt")]
+    pub created_at: DateTime<Utc>,
+}
+
+impl MediaPublic {
+    /// `url` is a signed, expiring download URL obtained from the media service.
+    pub fn new(media: Media, url: String) -> Self {
+        Self {
+            id: media.id,
+            url,
+            mime_type: media.mime_type,
+            size_bytes: media.size_bytes,
+            original_name: media.original_name,
+            sha256: media.sha256,
+            created_at: media.created_at,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SetCover {
+    pub media_id: Option<Uuid>,
+}
@@ -8,22 +8,12 @@ use validator::Validate;
 
 #[derive(Debug, Deserialize, Validate)]
 pub struct NewPost {
-    #[validate(
-        length(
-            min = 2,
-            max = 255,
-            message = "Title must be between 2 and 255 characters long"
-        ),
-        required(message = "Title is required")
-    )]
+    #[validate(length(min = 2, max = 255), required)]
     pub title: Option<String>,
-    #[validate(
-        length(min = 2, message = "Content must be at least 2 characters long"),
-        required(message = "Content is required")
-    )]
+    #[validate(length(min = 2), required)]
     pub content: Option<String>,
     pub published: bool,
-    #[validate(required(message = "User ID is required"))]
+    #[validate(required)]
     pub user_id: Option<Uuid>,
 }
 
@@ -34,6 +24,8 @@ impl NewPost {
     }
 
     pub fn validate_and_into_domain(self) -> Result<(String, String, bool, Uuid), ApiError> {
+        validate_dto(&self)?;
+
         let title = require_field(self.title, "title")?;
         let content = require_field(self.content, "content")?;
         let user_id = require_field(self.user_id, "user_id")?;
@@ -45,13 +37,9 @@ impl NewPost {
 
 #[derive(Debug, Deserialize, Validate)]
 pub struct UpdatePost {
-    #[validate(length(
-        min = 2,
-        max = 255,
-        message = "Title must be between 2 and 255 characters long"
-    ))]
+    #[validate(length(min = 2, max = 255))]
     pub title: Option<String>,
-    #[validate(length(min = 2, message = "Content must be at least 2 characters long"))]
+    #[validate(length(min = 2))]
     pub content: Option<String>,
     pub published: Option<bool>,
     pub user_id: Option<Uuid>,
@@ -1,180 +1,356 @@
+use std::collections::BTreeMap;
+
 use crate::{
-    domain::model::user::{Role, User},
+    domain::model::user::{AVATAR_SIZES, Role, USERNAME_MAX, USERNAME_MIN, User},
+    infrastructure::security::password_policy::PasswordPolicy,
     interfaces::api::{
+        dto::file::file_url,
         error::ApiError,
-        validation::{require_field, require_password, validate_dto},
+        validation::{FieldErrors, require_field, validate_dto},
     },
 };
 use chrono::{DateTime, Utc};
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize};
+use url::Url;
 use uuid::Uuid;
 use validator::Validate;
 
+/// What any authenticated reader may see about a user, e.g. a post author.
 #[derive(Debug, Serialize)]
 pub struct UserPublic {
     pub id: Uuid,
     pub username: String,
-    pub email: String,
+    #[serde(rename = "firstName")]
+    pub first_name: Option<String>,
+    #[serde(rename = "lastName")]
+    pub last_name: Option<String>,
+    #[serde(rename = "displayName")]
+    pub display_name: Option<String>,
+    pub bio: Option<String>,
+    pub website: Option<String>,
+    #[serde(rename = "avatarUrl")]
+    pub avatar_url: Option<String>,
+    #[serde(rename = "avatarThumbnails")]
+    pub avatar_thumbnails: BTreeMap<u32, String>,
     #[serde(rename = "createdAt")]
     pub created_at: DateTime<Utc>,
 }
 
+/// Builds the avatar URL (largest thumbnail) and the URL of every thumbnail
+/// from the storage prefix kept on the user row.
+pub fn avatar_links(avatar_key: Option<&str>) -> (Option<String>, BTreeMap<u32, String>) {
+    let Some(key) = avatar_key else {
+        return (None, BTreeMap::new());
+    };
+
+    let thumbnails: BTreeMap<u32, String> = AVATAR_SIZES
+        .iter()
+        .map(|size| (*size, file_url(&format!("{}/{}.png", key, size))))
+        .collect();
+    let url = thumbnails.values().next_back().cloned();
+
+    (url, thumbnails)
+}
+
 impl From<User> for UserPublic {
     fn from(user: User) -> Self {
+        let (avatar_url, avatar_thumbnails) = avatar_links(user.avatar_key.as_deref());
+
         Self {
             id: user.id,
             username: user.username,
-            email: user.email,
+            first_name: user.first_name,
+            last_name: user.last_name,
+            display_name: user.display_name,
+            bio: user.bio,
+            website: user.website,
+            avatar_url,
+            avatar_thumbnails,
             created_at: user.created_at,
         }
     }
 }
 
+/// The account as seen by its owner.
+#[derive(Debug, Serialize)]
+pub struct UserProfile {
+    #[serde(flatten)]
+    pub public: UserPublic,
+    pub email: String,
+    pub role: Role,
+    #[serde(rename = "updatedAt")]
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+impl From<User> for UserProfile {
+    fn from(user: User) -> Self {
+        let email = user.email.clone();
+        let role = user.role.clone();
+        let updated_at = user.updated_at;
+
+        Self {
+            public: UserPublic::from(user),
+            email,
+            role,
+            updated_at,
+        }
+    }
+}
+
+/// The account as seen by administrators.
+#[derive(Debug, Serialize)]
+pub struct UserAdminView {
+    #[serde(flatten)]
+    pub profile: UserProfile,
+    #[serde(rename = "erasedAt")]
+    pub erased_at: Option<DateTime<Utc>>,
+}
+
+impl From<User> for UserAdminView {
+    fn from(user: User) -> Self {
+        let erased_at = user.erased_at;
+
+        Self {
+            profile: UserProfile::from(user),
+            erased_at,
+        }
+    }
+}
+
 #[derive(Debug, Deserialize, Validate, Clone)]
 pub struct NewUser {
-    #[validate(
-        length(
-            min = 3,
-            max = 50,
-            message = "Username must be between 3 and 50 characters"
-        ),
-        required(message = "Username obligatoire")
-    )]
+    #[validate(length(min = USERNAME_MIN, max = USERNAME_MAX), required)]
     pub username: Option<String>,
-    #[validate(required(message = "Password obligatoire"))]
+    #[validate(required)]
     pub password: Option<String>,
-    #[validate(
-        email(message = "Email Invalide"),
-        required(message = "Email obligatoire")
-    )]
+    #[validate(email, required)]
     pub email: Option<String>,
 }
 
 impl NewUser {
-    pub fn validate_user(&self) -> Result<(), ApiError> {
-        validate_dto(self)?;
+    async fn field_errors(&self, policy: &PasswordPolicy) -> FieldErrors {
+        let mut errors = FieldErrors::of(self);
+        if let Some(password) = &self.password {
+            let personal = personal_info([self.username.as_deref(), self.email.as_deref()]);
+            errors
+                .check_password("password", password, policy, &personal)
+                .await;
+        }
 
-        let _pwd = require_password(self.password.clone())?;
+        errors
+    }
 
-        Ok(())
+    pub async fn validate_user(&self, policy: &PasswordPolicy) -> Result<(), ApiError> {
+        self.field_errors(policy).await.into_result()
     }
 
-    pub fn validate_and_into_domain(self) -> Result<(String, String, String), ApiError> {
-        validate_dto(&self)?;
+    pub async fn validate_and_into_domain(
+        self,
+        policy: &PasswordPolicy,
+    ) -> Result<(String, String, String), ApiError> {
+        self.validate_user(policy).await?;
 
         let username = require_field(self.username, "username")?;
         let email = require_field(self.email, "email")?;
-        let password = require_password(self.password)?;
+        let password = require_field(self.password, "password")?;
 
-        Ok((username, email, password))
+        Ok((username, password, email))
+    }
+}
+
+/// Body of `/api/setup`: the first administrator and the token printed at startup.
+#[derive(Debug, Deserialize)]
+pub struct SetupRequest {
+    pub token: Option<String>,
+    #[serde(flatten)]
+    pub admin: NewUser,
+}
+
+impl SetupRequest {
+    pub async fn validate_and_into_domain(
+        self,
+        policy: &PasswordPolicy,
+    ) -> Result<(String, (String, String, String)), ApiError> {
+        let mut errors = self.admin.field_errors(policy).await;
+        if self.token.is_none() {
+            errors.add("token", "required", &[]);
+        }
+        errors.into_result()?;
+
+        let token = require_field(self.token, "token")?;
+
+        Ok((token, self.admin.validate_and_into_domain(policy).await?))
     }
 }
 
 #[derive(Debug, Deserialize, Validate)]
 pub struct UpdateUser {
-    #[validate(length(
-        min = 3,
-        max = 50,
-        message = "Username must be between 3 and 50 characters"
-    ))]
+    #[validate(length(min = USERNAME_MIN, max = USERNAME_MAX))]
     pub username: Option<String>,
     pub password: Option<String>,
-    #[validate(email(message = "Email Invalide"))]
+    #[validate(email)]
     pub email: Option<String>,
     pub role: Option<Role>,
 }
 
+#[derive(Default)]
 pub struct UpdateUserPayload {
     pub username: Option<String>,
     pub password: Option<String>,
     pub email: Option<String>,
     pub role: Option<Role>,
+    /// `Some(None)` clears the field.
+    pub first_name: Option<Option<String>>,
+    pub last_name: Option<Option<String>>,
+    pub display_name: Option<Option<String>>,
+    pub bio: Option<Option<String>>,
+    pub website: Option<Option<String>>,
 }
 
 impl UpdateUser {
-    pub fn validate_user(&self) -> Result<(), ApiError> {
-        validate_dto(self)?;
-
-        if self.password.is_some() {
-            let _pwd = require_password(self.password.clone())?;
+    /// `current` is the account being updated, whose username and email the
+    /// new password must not contain either.
+    pub async fn validate_user(
+        &self,
+        policy: &PasswordPolicy,
+        current: &User,
+    ) -> Result<(), ApiError> {
+        let mut errors = FieldErrors::of(self);
+        if let Some(password) = &self.password {
+            let personal = personal_info([
+                self.username.as_deref(),
+                self.email.as_deref(),
+                Some(&current.username),
+                Some(&current.email),
+            ]);
+            errors
+                .check_password("password", password, policy, &personal)
+                .await;
         }
 
-        Ok(())
+        errors.into_result()
     }
 
-    pub fn validate_and_into_domain(self) -> Result<UpdateUserPayload, ApiError> {
-        validate_dto(&self)?;
-
-        let password = if self.password.is_some() {
-            Some(require_password(self.password.clone())?)
-        } else {
-            None
-        };
+    pub async fn validate_and_into_domain(
+        self,
+        policy: &PasswordPolicy,
+        current: &User,
+    ) -> Result<UpdateUserPayload, ApiError> {
+        self.validate_user(policy, current).await?;
 
         Ok(UpdateUserPayload {
             username: self.username,
-            password,
+            password: self.password,
             email: self.email,
             role: self.role,
+            first_name: None,
+            last_name: None,
+            display_name: None,
+            bio: None,
+            website: None,
         })
     }
 }
 
+/// A missing field is left as is; `null` clears the optional ones.
 #[derive(Debug, Deserialize, Validate)]
 pub struct UpdateProfile {
-    #[validate(length(
-        min = 3,
-        max = 255,
-        message = "Username must be between 3 and 255 characters"
-    ))]
+    #[validate(length(min = USERNAME_MIN, max = USERNAME_MAX))]
     pub username: Option<String>,
-    #[validate(email(message = "Email Invalide"))]
+    #[validate(email)]
     pub email: Option<String>,
+    #[serde(default, deserialize_with = "nullable")]
+    #[validate(length(min = 1, max = 100))]
+    pub first_name: Option<Option<String>>,
+    #[serde(default, deserialize_with = "nullable")]
+    #[validate(length(min = 1, max = 100))]
+    pub last_name: Option<Option<String>>,
+    #[serde(default, deserialize_with = "nullable")]
+    #[validate(length(min = 1, max = 100))]
+    pub display_name: Option<Option<String>>,
+    #[serde(default, deserialize_with = "nullable")]
+    #[validate(length(max = 1000))]
+    pub bio: Option<Option<String>>,
+    #[serde(default, deserialize_with = "nullable")]
+    #[validate(url, length(max = 255))]
+    pub website: Option<Option<String>>,
     pub plain_password: Option<String>,
     pub confirm_password: Option<String>,
 }
 
 impl UpdateProfile {
-    pub fn validate_user(&self) -> Result<(), ApiError> {
-        validate_dto(self)?;
+    /// Whether the update touches what the owner signs in with.
+    pub fn changes_credentials(&self) -> bool {
+        self.username.is_some()
+            || self.email.is_some()
+            || self.plain_password.is_some()
+            || self.confirm_password.is_some()
+    }
+
+    pub async fn validate_user(
+        &self,
+        policy: &PasswordPolicy,
+        current: &User,
+    ) -> Result<(), ApiError> {
+        let mut errors = FieldErrors::of(self);
+
+        // Shown as a link to every reader: no `javascript:` or `data:`.
+        if let Some(Some(website)) = &self.website {
+            let web = Url::parse(website).is_ok_and(|url| matches!(url.scheme(), "http" | "https"));
+            if !web {
+                errors.add("website", "url_scheme", &[]);
+            }
+        }
 
         if self.plain_password.is_some() || self.confirm_password.is_some() {
             if self.plain_password != self.confirm_password {
-                return Err(ApiError::BadRequest("Passwords do not match".to_string()));
+                errors.add("confirm_password", "password_mismatch", &[]);
+            }
+            match &self.plain_password {
+                Some(password) => {
+                    let personal = personal_info([
+                        self.username.as_deref(),
+                        self.email.as_deref(),
+                        Some(&current.username),
+                        Some(&current.email),
+                    ]);
+                    errors
+                        .check_password("plain_password", password, policy, &personal)
+                        .await
+                }
+                None => errors.add("plain_password", "required", &[]),
             }
-            let _pwd = require_password(self.plain_password.clone())?;
         }
 
-        Ok(())
+        errors.into_result()
     }
 
-    pub fn validate_and_into_domain(self) -> Result<UpdateUserPayload, ApiError> {
-        validate_dto(&self)?;
-
-        let password = if self.plain_password.is_some() {
-            if self.plain_password != self.confirm_password {
-                return Err(ApiError::BadRequest("Passwords do not match".to_string()));
-            }
-
-            Some(require_password(self.plain_password)?)
-        } else {
-            None
-        };
+    pub async fn validate_and_into_domain(
+        self,
+        policy: &PasswordPolicy,
+        current: &User,
+    ) -> Result<UpdateUserPayload, ApiError> {
+        self.validate_user(policy, current).await?;
 
         Ok(UpdateUserPayload {
             username: self.username,
-            password,
+            password: self.plain_password,
             email: self.email,
             role: None,
+            first_name: self.first_name,
+            last_name: self.last_name,
+            display_name: self.display_name,
+            bio: self.bio,
+            website: self.website,
         })
     }
 }
 
 #[derive(Debug, Deserialize, Validate)]
 pub struct RawLoginRequest {
-    #[validate(required(message = "Username obligatoire"))]
+    #[validate(required)]
     pub username: Option<String>,
-    #[validate(required(message = "Password obligatoire"))]
+    #[validate(required)]
     pub password: Option<String>,
 }
 
@@ -200,3 +376,16 @@ impl TryFrom<RawLoginRequest> for LoginUser {
         })
     }
 }
+
+fn personal_info<const N: usize>(values: [Option<&str>; N]) -> Vec<&str> {
+    values.into_iter().flatten().collect()
+}
+
+/// Tells an explicit `null`, `Some(None)`, from a missing field, `None`
+/// through `#[serde(default)]`.
+fn nullable<'de, D>(deserializer: D) -> Result<Option<Option<String>>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    Option::<String>::deserialize(deserializer).map(Some)
+}
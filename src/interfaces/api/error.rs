@@ -1,64 +1,232 @@
-use actix_web::{HttpResponse, ResponseError, http::StatusCode};
+use actix_web::{
+    HttpRequest, HttpResponse, ResponseError,
+    error::QueryPayloadError,
+    http::{
+        StatusCode,
+        header::{CONTENT_LANGUAGE, ContentType},
+    },
+};
 use serde::Serialize;
 use thiserror::Error;
 
-use crate::domain::error::DomainError;
+use crate::{
+    domain::error::DomainError,
+    infrastructure::{
+        auth::oidc::OidcError,
+        logging::request_id::{current_request_id, current_request_path},
+    },
+    interfaces::api::{
+        i18n::{current_locale, localize},
+        validation::FieldError,
+    },
+};
 
 #[derive(Error, Debug)]
 pub enum ApiError {
-    #[error("Ressource not found")]
+    #[error("Resource not found")]
     NotFound,
-    #[error("{0}")]
-    BadRequest(String),
+    #[error("Invalid UUID format")]
+    InvalidId,
+    #[error("Invalid JSON body: {0}")]
+    InvalidBody(String),
+    #[error("Invalid query string: {0}")]
+    InvalidQuery(String),
+    #[error("Invalid multipart body: {0}")]
+    InvalidMultipart(String),
     #[error("Internal server error")]
     InternalError,
+    #[error("Invalid setup token")]
+    InvalidSetupToken,
+    #[error("Invalid metrics token")]
+    InvalidMetricsToken,
+    #[error("Request body exceeds the limit of {0} bytes")]
+    PayloadTooLarge(usize),
+    #[error("Unsupported content type: {0}")]
+    UnsupportedMediaType(String),
+    #[error("Some fields are invalid")]
+    Validation(Vec<FieldError>),
+    #[error("The sign-in was not started here or has expired")]
+    OidcState,
+    #[error("The identity provider refused the sign-in: {0}")]
+    OidcDenied(String),
+    #[error("{0}")]
+    Oidc(OidcError),
     #[error("{0}")]
-    Unauthorized(String),
+    Domain(DomainError),
+}
+
+impl From<OidcError> for ApiError {
+    fn from(err: OidcError) -> Self {
+        ApiError::Oidc(err)
+    }
 }
 
 impl From<DomainError> for ApiError {
     fn from(err: DomainError) -> Self {
-        match err {
-            DomainError::NotFound => ApiError::NotFound,
-            DomainError::InternalError => ApiError::InternalError,
-            DomainError::Unauthorized(msg) => ApiError::Unauthorized(msg),
-            DomainError::DatabaseError(_) => ApiError::InternalError,
-            DomainError::DuplicateEmail => ApiError::BadRequest("Email already exists".to_string()),
-            DomainError::EmptyContent => {
-                ApiError::BadRequest("Content cannot be empty".to_string())
-            }
-            DomainError::InvalidMaxLentgthTitle => {
-                ApiError::BadRequest("Title exceeds maximum length".to_string())
+        ApiError::Domain(err)
+    }
+}
+
+fn domain_status(err: &DomainError) -> StatusCode {
+    match err {
+        DomainError::NotFound => StatusCode::NOT_FOUND,
+        DomainError::Unauthorized(_) => StatusCode::UNAUTHORIZED,
+        DomainError::DuplicateEmail
+        | DomainError::EmptyContent
+        | DomainError::InvalidMaxLentgthTitle
+        | DomainError::InvalidMinLentgthTitle
+        | DomainError::InvalidUserId
+        | DomainError::InvalidImage(_) => StatusCode::BAD_REQUEST,
+        DomainError::UnsupportedMediaType(_) => StatusCode::UNSUPPORTED_MEDIA_TYPE,
+        DomainError::FileTooLarge(_) => StatusCode::PAYLOAD_TOO_LARGE,
+        DomainError::MediaInUse => StatusCode::CONFLICT,
+        DomainError::EmailNotVerified
+        | DomainError::UnknownAccount
+        | DomainError::ImpersonationForbidden
+        | DomainError::NotPostAuthor => StatusCode::FORBIDDEN,
+        DomainError::InternalError
+        | DomainError::DatabaseError(_)
+        | DomainError::PasswordHashingError(_)
+        | DomainError::StorageError(_) => StatusCode::INTERNAL_SERVER_ERROR,
+    }
+}
+
+impl ApiError {
+    /// Stable identifier of the error, the `code` member of the problem document
+    /// and the key of its message in the catalog.
+    pub fn code(&self) -> &'static str {
+        match self {
+            ApiError::NotFound => "not_found",
+            ApiError::InvalidId => "invalid_id",
+            ApiError::InvalidBody(_) => "invalid_body",
+            ApiError::InvalidQuery(_) => "invalid_query",
+            ApiError::InvalidMultipart(_) => "invalid_multipart",
+            ApiError::InternalError => "internal_error",
+            ApiError::InvalidSetupToken => "invalid_setup_token",
+            ApiError::InvalidMetricsToken => "invalid_metrics_token",
+            ApiError::PayloadTooLarge(_) => "payload_too_large",
+            ApiError::UnsupportedMediaType(_) => "unsupported_media_type",
+            ApiError::Validation(_) => "validation_failed",
+            ApiError::OidcState => "oidc_state_mismatch",
+            ApiError::OidcDenied(_) => "oidc_access_denied",
+            ApiError::Oidc(OidcError::Provider(_)) => "oidc_provider_error",
+            ApiError::Oidc(OidcError::InvalidIdToken(_)) => "invalid_id_token",
+            ApiError::Domain(err) => err.code(),
+        }
+    }
+
+    /// Values of the placeholders of the message.
+    fn params(&self) -> Vec<(&'static str, String)> {
+        match self {
+            ApiError::InvalidBody(detail)
+            | ApiError::InvalidQuery(detail)
+            | ApiError::InvalidMultipart(detail)
+            | ApiError::UnsupportedMediaType(detail)
+            | ApiError::OidcDenied(detail)
+            | ApiError::Domain(DomainError::InvalidImage(detail))
+            | ApiError::Domain(DomainError::UnsupportedMediaType(detail)) => {
+                vec![("detail", detail.clone())]
             }
-            DomainError::InvalidMinLentgthTitle => {
-                ApiError::BadRequest("Title is too short".to_string())
+            ApiError::PayloadTooLarge(max) | ApiError::Domain(DomainError::FileTooLarge(max)) => {
+                vec![("max", max.to_string())]
             }
-            DomainError::InvalidUserId => ApiError::BadRequest("Invalid user ID".to_string()),
-            DomainError::PasswordHashingError(_) => ApiError::InternalError,
+            _ => Vec::new(),
         }
     }
 }
 
+/// RFC 7807 problem document, the body of every error response.
 #[derive(Serialize)]
-struct ApiErrorResponse {
-    error: String,
+pub struct Problem {
+    #[serde(rename = "type")]
+    pub type_uri: String,
+    pub title: String,
+    pub status: u16,
+    pub detail: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub instance: Option<String>,
+    pub code: &'static str,
+    /// Same value as the `X-Request-Id` header, to be quoted when reporting an issue.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub request_id: Option<String>,
+    /// Invalid fields of a 422 response.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub errors: Vec<FieldError>,
+}
+
+impl Problem {
+    pub const CONTENT_TYPE: &'static str = "application/problem+json";
+
+    /// `detail` is the message of `code` in the locale of the request, and
+    /// `instance` and `request_id` are those of the request being handled.
+    pub fn new(status: StatusCode, code: &'static str, params: &[(&str, String)]) -> Self {
+        Self {
+            type_uri: format!("/problems/{}", code),
+            title: status.canonical_reason().unwrap_or("Error").to_string(),
+            status: status.as_u16(),
+            detail: localize(code, params),
+            instance: current_request_path(),
+            code,
+            request_id: current_request_id(),
+            errors: Vec::new(),
+        }
+    }
+
+    pub fn response(self) -> HttpResponse {
+        let status = StatusCode::from_u16(self.status).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR);
+
+        HttpResponse::build(status)
+            .insert_header(ContentType(Self::CONTENT_TYPE.parse().expect("valid mime")))
+            .insert_header((CONTENT_LANGUAGE, current_locale().as_str()))
+            .body(serde_json::to_string(&self).expect("problem serializes to JSON"))
+    }
 }
 
 impl ResponseError for ApiError {
     fn status_code(&self) -> StatusCode {
-        match *self {
+        match self {
             ApiError::NotFound => StatusCode::NOT_FOUND,
-            ApiError::BadRequest(_) => StatusCode::BAD_REQUEST,
+            ApiError::InvalidId
+            | ApiError::InvalidBody(_)
+            | ApiError::InvalidQuery(_)
+            | ApiError::InvalidMultipart(_) => StatusCode::BAD_REQUEST,
             ApiError::InternalError => StatusCode::INTERNAL_SERVER_ERROR,
-            ApiError::Unauthorized(_) => StatusCode::UNAUTHORIZED,
+            ApiError::InvalidSetupToken | ApiError::InvalidMetricsToken => StatusCode::UNAUTHORIZED,
+            ApiError::PayloadTooLarge(_) => StatusCode::PAYLOAD_TOO_LARGE,
+            ApiError::UnsupportedMediaType(_) => StatusCode::UNSUPPORTED_MEDIA_TYPE,
+            ApiError::Validation(_) => StatusCode::UNPROCESSABLE_ENTITY,
+            ApiError::OidcState => StatusCode::BAD_REQUEST,
+            ApiError::OidcDenied(_) | ApiError::Oidc(OidcError::InvalidIdToken(_)) => {
+                StatusCode::UNAUTHORIZED
+            }
+            ApiError::Oidc(OidcError::Provider(_)) => StatusCode::BAD_GATEWAY,
+            ApiError::Domain(err) => domain_status(err),
         }
     }
 
     fn error_response(&self) -> HttpResponse {
-        let body = ApiErrorResponse {
-            error: self.to_string(),
-        };
+        let status = self.status_code();
 
-        HttpResponse::build(self.status_code()).json(body)
+        if status.is_server_error() {
+            tracing::error!(code = self.code(), error = ?self, "request failed");
+        }
+
+        let mut problem = Problem::new(status, self.code(), &self.params());
+        if let ApiError::Validation(errors) = self {
+            problem.errors = errors.clone();
+        }
+
+        problem.response()
     }
 }
+
+/// Error handler of `web::QueryConfig`.
+pub fn query_error(err: QueryPayloadError, _: &HttpRequest) -> actix_web::Error {
+    ApiError::InvalidQuery(err.to_string()).into()
+}
+
+/// Default service of the application, so that unknown routes get a problem
+/// document too.
+pub async fn not_found() -> Result<HttpResponse, ApiError> {
+    Err(ApiError::NotFound)
+}
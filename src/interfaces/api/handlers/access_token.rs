@@ -0,0 +1,68 @@
+use uuid::Uuid;
+
+use crate::{
+    infrastructure::auth::Claims,
+    interfaces::api::{
+        dto::access_token::{AccessTokenView, CreatedAccessToken, NewAccessToken},
+        error::ApiError,
+        json::Json,
+        state::DynAccessTokenService,
+    },
+};
+use actix_web::{HttpResponse, web};
+
+/// Mounted under `/api/profile`: the personal access tokens of the caller.
+/// These routes refuse the tokens themselves, see `required_scope`.
+pub fn config(cfg: &mut web::ServiceConfig) {
+    cfg.route("/tokens", web::get().to(list_tokens))
+        .route("/tokens", web::post().to(create_token))
+        .route("/tokens/{id}", web::delete().to(revoke_token));
+}
+
+#[tracing::instrument(skip_all)]
+async fn list_tokens(
+    claims: Claims,
+    service: web::Data<DynAccessTokenService>,
+) -> Result<HttpResponse, ApiError> {
+    let tokens: Vec<AccessTokenView> = service
+        .list(claims.user_id()?)
+        .await?
+        .into_iter()
+        .map(AccessTokenView::from)
+        .collect();
+
+    Ok(HttpResponse::Ok().json(tokens))
+}
+
+#[tracing::instrument(skip_all)]
+async fn create_token(
+    claims: Claims,
+    dto: Json<NewAccessToken>,
+    service: web::Data<DynAccessTokenService>,
+) -> Result<HttpResponse, ApiError> {
+    // A token would outlive the impersonation session.
+    claims.not_impersonated()?;
+    let (name, scopes, expires_at) = dto.into_inner().validate_and_into_domain(&claims.role)?;
+
+    let (token, secret) = service
+        .create(claims.user_id()?, name, scopes, expires_at)
+        .await?;
+
+    Ok(HttpResponse::Created().json(CreatedAccessToken {
+        view: AccessTokenView::from(token),
+        token: secret,
+    }))
+}
+
+#[tracing::instrument(skip_all)]
+async fn revoke_token(
+    claims: Claims,
+    id: web::Path<String>,
+    service: web::Data<DynAccessTokenService>,
+) -> Result<HttpResponse, ApiError> {
+    let id = Uuid::parse_str(&id.into_inner()).map_err(|_| ApiError::InvalidId)?;
+
+    service.revoke(id, claims.user_id()?).await?;
+
+    Ok(HttpResponse::NoContent().finish())
+}
@@ -0,0 +1,55 @@
+use actix_web::{HttpResponse, http::header, web};
+use serde::Deserialize;
+
+use crate::{
+    infrastructure::storage::{BlobStore, is_public_key},
+    interfaces::api::error::ApiError,
+};
+
+pub fn config(cfg: &mut web::ServiceConfig) {
+    cfg.service(web::scope("/api/files").route("/{key:.*}", web::get().to(get_file)));
+}
+
+#[derive(Deserialize)]
+struct Signature {
+    expires: Option<i64>,
+    signature: Option<String>,
+}
+
+async fn get_file(
+    store: web::Data<dyn BlobStore>,
+    key: web::Path<String>,
+    query: web::Query<Signature>,
+) -> Result<HttpResponse, ApiError> {
+    let key = key.into_inner();
+
+    if !is_public_key(&key) {
+        let authorized = match (query.expires, query.signature.as_deref()) {
+            (Some(expires), Some(signature)) => store.verify_signature(&key, expires, signature),
+            _ => false,
+        };
+
+        // Same answer as a missing file, to avoid confirming the key exists.
+        if !authorized {
+            return Err(ApiError::NotFound);
+        }
+    }
+
+    let blob = store
+        .get(&key)
+        .await
+        .map_err(|_| ApiError::NotFound)?
+        .ok_or(ApiError::NotFound)?;
+
+    // Keys are never reused for different content.
+    let cache_control = if is_public_key(&key) {
+        "public, max-age=31536000, immutable"
+    } else {
+        "private, max-age=3600"
+    };
+
+    Ok(HttpResponse::Ok()
+        .content_type(blob.content_type)
+        .insert_header((header::CACHE_CONTROL, cache_control))
+        .body(blob.bytes))
+}
@@ -0,0 +1,37 @@
+use crate::infrastructure::{db::Database, health::check_readiness, storage::BlobStore};
+use actix_web::{HttpResponse, web};
+use serde_json::json;
+
+/// Probes and build info: no authentication, and left out of the access log.
+pub const PATHS: [&str; 3] = ["/health/live", "/health/ready", "/version"];
+
+pub fn config(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+        web::scope("/health")
+            .route("/live", web::get().to(live))
+            .route("/ready", web::get().to(ready)),
+    )
+    .route("/version", web::get().to(version));
+}
+
+async fn live() -> HttpResponse {
+    HttpResponse::Ok().json(json!({ "status": "ok" }))
+}
+
+async fn ready(db: web::Data<Database>, store: web::Data<dyn BlobStore>) -> HttpResponse {
+    let readiness = check_readiness(&db, store.get_ref()).await;
+
+    if readiness.ready {
+        HttpResponse::Ok().json(readiness)
+    } else {
+        HttpResponse::ServiceUnavailable().json(readiness)
+    }
+}
+
+async fn version() -> HttpResponse {
+    HttpResponse::Ok().json(json!({
+        "version": env!("CARGO_PKG_VERSION"),
+        "gitHash": env!("GIT_HASH"),
+        "buildTime": env!("BUILD_TIME"),
+    }))
+}
@@ -0,0 +1,65 @@
+use uuid::Uuid;
+
+use crate::{
+    infrastructure::auth::{Claims, admin::AdminMiddleware, jwt::JwtMiddleware},
+    interfaces::api::{
+        dto::impersonation::{AuditEntryView, AuditQuery, ImpersonationToken},
+        error::ApiError,
+        state::DynImpersonationService,
+    },
+};
+use actix_web::{HttpResponse, web};
+
+/// `POST /api/users/{id}/impersonate` is mounted with the other admin routes
+/// of `/api/users`.
+pub fn config(cfg: &mut web::ServiceConfig) {
+    cfg.route("/api/impersonation", web::delete().to(end_impersonation))
+        .service(
+            web::scope("/api/audit")
+                .wrap(JwtMiddleware::new())
+                .wrap(AdminMiddleware::new())
+                .route("", web::get().to(list_audit)),
+        );
+}
+
+#[tracing::instrument(skip_all)]
+pub async fn start_impersonation(
+    claims: Claims,
+    id: web::Path<String>,
+    service: web::Data<DynImpersonationService>,
+) -> Result<HttpResponse, ApiError> {
+    claims.not_impersonated()?;
+    claims.not_delegated()?;
+    let target_id = Uuid::parse_str(&id.into_inner()).map_err(|_| ApiError::InvalidId)?;
+
+    let (token, expires_at) = service.start(claims.user_id()?, target_id).await?;
+
+    Ok(HttpResponse::Created().json(ImpersonationToken { token, expires_at }))
+}
+
+/// Ends the impersonation session of the calling token; `404` for any other
+/// token.
+#[tracing::instrument(skip_all)]
+async fn end_impersonation(
+    claims: Claims,
+    service: web::Data<DynImpersonationService>,
+) -> Result<HttpResponse, ApiError> {
+    service.end(&claims).await?;
+
+    Ok(HttpResponse::NoContent().finish())
+}
+
+#[tracing::instrument(skip_all)]
+async fn list_audit(
+    query: web::Query<AuditQuery>,
+    service: web::Data<DynImpersonationService>,
+) -> Result<HttpResponse, ApiError> {
+    let entries: Vec<AuditEntryView> = service
+        .audit_log(query.limit())
+        .await?
+        .into_iter()
+        .map(AuditEntryView::from)
+        .collect();
+
+    Ok(HttpResponse::Ok().json(entries))
+}
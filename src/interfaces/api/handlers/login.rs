@@ -1,31 +1,67 @@
 use crate::{
-    application::user_service::UserService,
+    config::Settings,
     domain::error::DomainError,
-    infrastructure::persistence::sqlite::user_repo::SqliteUserRepo,
+    infrastructure::{
+        auth::session::{csrf_token, removal_cookies, session_cookies},
+        metrics::Metrics,
+    },
     interfaces::api::{
         dto::user::{LoginUser, RawLoginRequest},
         error::ApiError,
+        json::Json,
+        state::DynUserService,
     },
 };
-use actix_web::{HttpResponse, web};
+use actix_web::{HttpResponse, HttpResponseBuilder, web};
 use serde_json::json;
 
 pub fn config(cfg: &mut web::ServiceConfig) {
-    cfg.service(web::scope("/api/login").route("", web::post().to(login)));
+    cfg.service(web::scope("/api/login").route("", web::post().to(login)))
+        .service(web::scope("/api/logout").route("", web::post().to(logout)));
 }
 
+#[tracing::instrument(skip_all)]
 pub async fn login(
-    raw: web::Json<RawLoginRequest>,
-    service: web::Data<UserService<SqliteUserRepo>>,
+    raw: Json<RawLoginRequest>,
+    service: web::Data<DynUserService>,
+    metrics: web::Data<Metrics>,
+    settings: web::Data<Settings>,
 ) -> Result<HttpResponse, ApiError> {
     raw.validate_login()?;
 
     let LoginUser { username, password } = raw.into_inner().try_into()?;
 
-    let token = service
-        .login(&username, &password)
-        .await
-        .map_err(|e: DomainError| ApiError::from(e))?;
+    let token = service.login(&username, &password).await;
+    metrics.record_login(token.is_ok());
+    let token = token.map_err(|e: DomainError| ApiError::from(e))?;
+
+    Ok(signed_in(HttpResponse::Ok(), &settings, &token))
+}
+
+/// Body of a successful sign-in. In cookie mode the JWT only travels in the
+/// `HttpOnly` cookie, out of reach of scripts, and the body holds the CSRF
+/// token.
+pub fn signed_in(mut resp: HttpResponseBuilder, settings: &Settings, token: &str) -> HttpResponse {
+    if !settings.session.enabled {
+        return resp.json(json!({ "token": token }));
+    }
+
+    for cookie in session_cookies(settings, token) {
+        resp.cookie(cookie);
+    }
+    let csrf = csrf_token(&settings.jwt_secret, token);
+    resp.json(json!({ "csrfToken": csrf }))
+}
 
-    Ok(HttpResponse::Ok().json(json!({ "token": token })))
+/// Drops the session cookies. The JWT they held is not revoked and stays
+/// valid until it expires: revoking it through `tokens_valid_after` would
+/// end every session and personal access token of the user.
+pub async fn logout(settings: web::Data<Settings>) -> HttpResponse {
+    let mut resp = HttpResponse::NoContent();
+    if settings.session.enabled {
+        for cookie in removal_cookies(&settings.session) {
+            resp.cookie(cookie);
+        }
+    }
+    resp.finish()
 }
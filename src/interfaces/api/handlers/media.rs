@@ -0,0 +1,99 @@
+use std::str::FromStr;
+
+use crate::{
+    domain::model::media::Media,
+    infrastructure::auth::{Claims, jwt::JwtMiddleware},
+    interfaces::api::{
+        dto::media::MediaPublic, error::ApiError, multipart::read_file_field,
+        state::DynMediaService,
+    },
+};
+use actix_multipart::Multipart;
+use actix_web::{HttpResponse, web};
+use uuid::Uuid;
+
+pub fn config(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+        web::scope("/api/media")
+            .wrap(JwtMiddleware::new())
+            .route("", web::get().to(list_media))
+            .route("", web::post().to(upload_media))
+            .route("/{id}", web::get().to(get_media))
+            .route("/{id}", web::delete().to(delete_media)),
+    );
+}
+
+#[tracing::instrument(skip_all)]
+async fn list_media(
+    claims: Claims,
+    service: web::Data<DynMediaService>,
+) -> Result<HttpResponse, ApiError> {
+    let owner_id = claims.user_id()?;
+
+    let media = service.list(owner_id).await.map_err(ApiError::from)?;
+
+    Ok(HttpResponse::Ok().json(with_urls(&service, media).await?))
+}
+
+#[tracing::instrument(skip_all)]
+async fn upload_media(
+    claims: Claims,
+    payload: Multipart,
+    service: web::Data<DynMediaService>,
+) -> Result<HttpResponse, ApiError> {
+    let owner_id = claims.user_id()?;
+
+    let file = read_file_field(payload, "file", service.max_bytes()).await?;
+
+    let media = service
+        .upload(owner_id, file.bytes, file.filename)
+        .await
+        .map_err(ApiError::from)?;
+
+    let url = service.download_url(&media).await?;
+
+    Ok(HttpResponse::Created().json(MediaPublic::new(media, url)))
+}
+
+#[tracing::instrument(skip_all)]
+async fn get_media(
+    claims: Claims,
+    service: web::Data<DynMediaService>,
+    id: web::Path<String>,
+) -> Result<HttpResponse, ApiError> {
+    let owner_id = claims.user_id()?;
+    let id = Uuid::from_str(&id.into_inner()).map_err(|_| ApiError::InvalidId)?;
+
+    let media = service.find(owner_id, id).await.map_err(ApiError::from)?;
+    let url = service.download_url(&media).await?;
+
+    Ok(HttpResponse::Ok().json(MediaPublic::new(media, url)))
+}
+
+#[tracing::instrument(skip_all)]
+async fn delete_media(
+    claims: Claims,
+    service: web::Data<DynMediaService>,
+    id: web::Path<String>,
+) -> Result<HttpResponse, ApiError> {
+    let owner_id = claims.user_id()?;
+    let id = Uuid::from_str(&id.into_inner()).map_err(|_| ApiError::InvalidId)?;
+
+    service.delete(owner_id, id).await.map_err(ApiError::from)?;
+
+    Ok(HttpResponse::NoContent().finish())
+}
+
+pub(crate) async fn with_urls(
+    service: &DynMediaService,
+    media: Vec<Media>,
+) -> Result<Vec<MediaPublic>, ApiError> {
+    let mut public = Vec::with_capacity(media.len());
+
+    for media in media {
+        let url = service.download_url(&media).await?;
+        public.push(MediaPublic::new(media, url));
+    }
+
+    Ok(public)
+}
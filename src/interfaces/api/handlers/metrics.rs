@@ -0,0 +1,47 @@
+use crate::interfaces::api::error::ApiError;
+use crate::{
+    config::Settings,
+    infrastructure::{db::Database, metrics::Metrics, security::keys::same_secret},
+};
+use actix_web::{HttpRequest, HttpResponse, ResponseError, http::header::AUTHORIZATION, web};
+
+/// `/metrics` on the public API, behind `METRICS__TOKEN`; answers 404 when no
+/// token is configured.
+pub fn config(cfg: &mut web::ServiceConfig) {
+    cfg.route("/metrics", web::get().to(metrics_with_token));
+}
+
+/// `/metrics` without authentication, for the private listener of `METRICS__PORT`.
+pub fn config_private(cfg: &mut web::ServiceConfig) {
+    cfg.route("/metrics", web::get().to(render));
+}
+
+async fn metrics_with_token(
+    req: HttpRequest,
+    settings: web::Data<Settings>,
+    metrics: web::Data<Metrics>,
+    db: web::Data<Database>,
+) -> HttpResponse {
+    let Some(expected) = settings.metrics.token.as_deref() else {
+        return ApiError::NotFound.error_response();
+    };
+
+    let authorized = req
+        .headers()
+        .get(AUTHORIZATION)
+        .and_then(|h| h.to_str().ok())
+        .and_then(|h| h.strip_prefix("Bearer "))
+        .is_some_and(|token| same_secret(expected, token));
+
+    if !authorized {
+        return ApiError::InvalidMetricsToken.error_response();
+    }
+
+    render(metrics, db).await
+}
+
+async fn render(metrics: web::Data<Metrics>, db: web::Data<Database>) -> HttpResponse {
+    HttpResponse::Ok()
+        .content_type("text/plain; version=0.0.4")
+        .body(metrics.render(&db))
+}
@@ -0,0 +1,111 @@
+use actix_web::{
+    HttpRequest, HttpResponse,
+    cookie::{Cookie, SameSite, time},
+    http::header::LOCATION,
+    web,
+};
+use serde::Deserialize;
+
+use crate::{
+    config::Settings,
+    infrastructure::{auth::oidc::LOGIN_TIMEOUT, metrics::Metrics, security::keys::same_secret},
+    interfaces::api::{error::ApiError, handlers::login::signed_in, state::DynOidcService},
+};
+
+/// Signed `PendingLogin` of the authorization in progress.
+const PENDING_COOKIE: &str = "blog_oidc";
+const PENDING_PATH: &str = "/api/oidc";
+
+pub fn config(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+        web::scope("/api/oidc")
+            .route("/login", web::get().to(login))
+            .route("/callback", web::get().to(callback)),
+    );
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CallbackQuery {
+    pub code: Option<String>,
+    pub state: Option<String>,
+    pub error: Option<String>,
+}
+
+/// Redirects to the provider; `404` when OIDC is not configured.
+#[tracing::instrument(skip_all)]
+pub async fn login(service: Option<web::Data<DynOidcService>>) -> Result<HttpResponse, ApiError> {
+    let service = service.ok_or(ApiError::NotFound)?;
+
+    let (url, pending) = service.client().authorization_url().await?;
+    let pending = service.seal_pending(&pending)?;
+
+    Ok(HttpResponse::Found()
+        .insert_header((LOCATION, url.as_str()))
+        .cookie(pending_cookie(
+            pending,
+            time::Duration::seconds(LOGIN_TIMEOUT.num_seconds()),
+        ))
+        .finish())
+}
+
+/// Where the provider sends the browser back; answers like `/api/login`.
+#[tracing::instrument(skip_all)]
+pub async fn callback(
+    req: HttpRequest,
+    query: web::Query<CallbackQuery>,
+    service: Option<web::Data<DynOidcService>>,
+    settings: web::Data<Settings>,
+    metrics: web::Data<Metrics>,
+) -> Result<HttpResponse, ApiError> {
+    let service = service.ok_or(ApiError::NotFound)?;
+    let query = query.into_inner();
+
+    if let Some(error) = query.error {
+        return Err(ApiError::OidcDenied(error));
+    }
+
+    // The state ties the answer to the browser that started the sign-in.
+    let pending = req
+        .cookie(PENDING_COOKIE)
+        .and_then(|cookie| service.open_pending(cookie.value()))
+        .ok_or(ApiError::OidcState)?;
+    if !query
+        .state
+        .as_deref()
+        .is_some_and(|state| same_secret(&pending.state, state))
+    {
+        return Err(ApiError::OidcState);
+    }
+    let code = query
+        .code
+        .ok_or_else(|| ApiError::InvalidQuery("missing field `code`".to_string()))?;
+
+    let signed = match service.client().exchange(&code, &pending).await {
+        Ok(claims) => service.sign_in(&claims).await.map_err(ApiError::from),
+        Err(e) => {
+            log::warn!("OIDC sign-in failed: {}", e);
+            Err(ApiError::from(e))
+        }
+    };
+    metrics.record_login(signed.is_ok());
+    let signed = signed?;
+    if signed.provisioned {
+        metrics.users_registered.inc();
+    }
+
+    let mut resp = HttpResponse::Ok();
+    resp.cookie(pending_cookie(String::new(), time::Duration::ZERO));
+    Ok(signed_in(resp, &settings, &signed.token))
+}
+
+/// `Lax`, since the provider brings the browser back with a cross-site
+/// navigation.
+fn pending_cookie(value: String, max_age: time::Duration) -> Cookie<'static> {
+    Cookie::build(PENDING_COOKIE, value)
+        .path(PENDING_PATH)
+        .secure(true)
+        .http_only(true)
+        .same_site(SameSite::Lax)
+        .max_age(max_age)
+        .finish()
+}
@@ -1,10 +1,12 @@
 use std::str::FromStr;
 
-use crate::application::post_service::PostService;
-use crate::infrastructure::auth::jwt::JwtMiddleware;
-use crate::infrastructure::persistence::sqlite::post_repo::SqlitePostRepo;
-use crate::infrastructure::persistence::sqlite::user_repo::SqliteUserRepo;
+use crate::infrastructure::auth::{Claims, jwt::JwtMiddleware};
+use crate::infrastructure::metrics::Metrics;
+use crate::interfaces::api::dto::media::SetCover;
 use crate::interfaces::api::dto::post::{NewPost, UpdatePost};
+use crate::interfaces::api::handlers::media::with_urls;
+use crate::interfaces::api::json::Json;
+use crate::interfaces::api::state::{DynMediaService, DynPostService};
 use crate::{domain::error::DomainError, interfaces::api::error::ApiError};
 use actix_web::{HttpResponse, web};
 use uuid::Uuid;
@@ -17,35 +19,50 @@ pub fn config(cfg: &mut web::ServiceConfig) {
             .route("", web::post().to(create_post))
             .route("/{id}", web::get().to(get_post))
             .route("/{id}", web::patch().to(update_post))
-            .route("/{id}", web::delete().to(delete_post)),
+            .route("/{id}", web::delete().to(delete_post))
+            .route("/{id}/media", web::get().to(list_post_media))
+            .route("/{id}/media/{media_id}", web::put().to(attach_media))
+            .route("/{id}/media/{media_id}", web::delete().to(detach_media))
+            .route("/{id}/cover", web::put().to(set_cover)),
     );
 }
 
+#[tracing::instrument(skip_all)]
 async fn list_posts(
-    service: web::Data<PostService<SqlitePostRepo, SqliteUserRepo>>,
+    service: web::Data<DynPostService>,
+    media_service: web::Data<DynMediaService>,
 ) -> Result<HttpResponse, ApiError> {
-    let posts = service.list().await.map_err(ApiError::from)?;
+    let mut posts = service.list().await.map_err(ApiError::from)?;
+    media_service.sign_cover_urls(&mut posts).await?;
 
     Ok(HttpResponse::Ok().json(posts))
 }
 
+#[tracing::instrument(skip_all)]
 async fn get_post(
-    service: web::Data<PostService<SqlitePostRepo, SqliteUserRepo>>,
+    service: web::Data<DynPostService>,
+    media_service: web::Data<DynMediaService>,
     id: web::Path<String>,
 ) -> Result<HttpResponse, ApiError> {
-    let id = Uuid::from_str(&id.into_inner())
-        .map_err(|_| ApiError::BadRequest("Invalid UUID format".to_string()))?;
+    let id = Uuid::from_str(&id.into_inner()).map_err(|_| ApiError::InvalidId)?;
 
     match service.find_by_id(id).await {
-        Ok(Some(post)) => Ok(HttpResponse::Ok().json(post)),
+        Ok(Some(post)) => {
+            let mut posts = [post];
+            media_service.sign_cover_urls(&mut posts).await?;
+            let [post] = posts;
+            Ok(HttpResponse::Ok().json(post))
+        }
         Ok(None) => Err(ApiError::NotFound),
         Err(_) => Err(ApiError::InternalError),
     }
 }
 
+#[tracing::instrument(skip_all)]
 async fn create_post(
-    service: web::Data<PostService<SqlitePostRepo, SqliteUserRepo>>,
-    dto: web::Json<NewPost>,
+    service: web::Data<DynPostService>,
+    metrics: web::Data<Metrics>,
+    dto: Json<NewPost>,
 ) -> Result<HttpResponse, ApiError> {
     let (title, content, published, user_id) = dto.into_inner().validate_and_into_domain()?;
 
@@ -53,17 +70,18 @@ async fn create_post(
         .create(title, content, published, user_id)
         .await
         .map_err(ApiError::from)?;
+    metrics.posts_created.inc();
 
     Ok(HttpResponse::Created().json(post))
 }
 
+#[tracing::instrument(skip_all)]
 async fn update_post(
-    service: web::Data<PostService<SqlitePostRepo, SqliteUserRepo>>,
+    service: web::Data<DynPostService>,
     path: web::Path<String>,
-    dto: web::Json<UpdatePost>,
+    dto: Json<UpdatePost>,
 ) -> Result<HttpResponse, ApiError> {
-    let id = Uuid::from_str(&path.into_inner())
-        .map_err(|_| ApiError::BadRequest("Invalid UUID format".to_string()))?;
+    let id = Uuid::from_str(&path.into_inner()).map_err(|_| ApiError::InvalidId)?;
 
     let payload = dto.into_inner().validate_and_into_domain()?;
 
@@ -72,12 +90,12 @@ async fn update_post(
     Ok(HttpResponse::Ok().json(updated))
 }
 
+#[tracing::instrument(skip_all)]
 async fn delete_post(
-    service: web::Data<PostService<SqlitePostRepo, SqliteUserRepo>>,
+    service: web::Data<DynPostService>,
     id: web::Path<String>,
 ) -> Result<HttpResponse, ApiError> {
-    let id = Uuid::from_str(&id.into_inner())
-        .map_err(|_| ApiError::BadRequest("Invalid UUID format".to_string()))?;
+    let id = Uuid::from_str(&id.into_inner()).map_err(|_| ApiError::InvalidId)?;
 
     match service.delete(id).await {
         Ok(()) => Ok(HttpResponse::NoContent().finish()),
@@ -85,3 +103,74 @@ async fn delete_post(
         Err(_) => Err(ApiError::InternalError),
     }
 }
+
+#[tracing::instrument(skip_all)]
+async fn list_post_media(
+    service: web::Data<DynMediaService>,
+    id: web::Path<String>,
+) -> Result<HttpResponse, ApiError> {
+    let id = Uuid::from_str(&id.into_inner()).map_err(|_| ApiError::InvalidId)?;
+
+    let media = service.list_for_post(id).await.map_err(ApiError::from)?;
+
+    Ok(HttpResponse::Ok().json(with_urls(&service, media).await?))
+}
+
+#[tracing::instrument(skip_all)]
+async fn attach_media(
+    claims: Claims,
+    service: web::Data<DynMediaService>,
+    path: web::Path<(String, String)>,
+) -> Result<HttpResponse, ApiError> {
+    let user_id = claims.user_id()?;
+    let (post_id, media_id) = parse_post_media_path(path.into_inner())?;
+
+    service
+        .attach(user_id, &claims.role, post_id, media_id)
+        .await
+        .map_err(ApiError::from)?;
+
+    Ok(HttpResponse::NoContent().finish())
+}
+
+#[tracing::instrument(skip_all)]
+async fn detach_media(
+    claims: Claims,
+    service: web::Data<DynMediaService>,
+    path: web::Path<(String, String)>,
+) -> Result<HttpResponse, ApiError> {
+    let user_id = claims.user_id()?;
+    let (post_id, media_id) = parse_post_media_path(path.into_inner())?;
+
+    service
+        .detach(user_id, &claims.role, post_id, media_id)
+        .await
+        .map_err(ApiError::from)?;
+
+    Ok(HttpResponse::NoContent().finish())
+}
+
+#[tracing::instrument(skip_all)]
+async fn set_cover(
+    claims: Claims,
+    service: web::Data<DynMediaService>,
+    path: web::Path<String>,
+    dto: Json<SetCover>,
+) -> Result<HttpResponse, ApiError> {
+    let user_id = claims.user_id()?;
+    let id = Uuid::from_str(&path.into_inner()).map_err(|_| ApiError::InvalidId)?;
+
+    let updated = service
+        .set_cover(user_id, &claims.role, id, dto.into_inner().media_id)
+        .await
+        .map_err(ApiError::from)?;
+
+    Ok(HttpResponse::Ok().json(updated))
+}
+
+fn parse_post_media_path((post_id, media_id): (String, String)) -> Result<(Uuid, Uuid), ApiError> {
+    let post_id = Uuid::from_str(&post_id).map_err(|_| ApiError::InvalidId)?;
+    let media_id = Uuid::from_str(&media_id).map_err(|_| ApiError::InvalidId)?;
+
+    Ok((post_id, media_id))
+}
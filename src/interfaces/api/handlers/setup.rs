@@ -0,0 +1,54 @@
+use crate::{
+    domain::model::user::Role,
+    infrastructure::{
+        auth::setup::SetupToken, metrics::Metrics, security::password_policy::PasswordPolicy,
+    },
+    interfaces::api::{
+        dto::user::{SetupRequest, UserAdminView},
+        error::ApiError,
+        json::Json,
+        state::DynUserService,
+    },
+};
+use actix_web::{HttpResponse, web};
+
+pub fn config(cfg: &mut web::ServiceConfig) {
+    cfg.service(web::scope("/api/setup").route("", web::post().to(setup)));
+}
+
+/// Creates the first administrator with the one-time token printed at startup.
+/// Answers 404 once an account exists or when no token was issued.
+#[tracing::instrument(skip_all)]
+async fn setup(
+    dto: Json<SetupRequest>,
+    setup: web::Data<SetupToken>,
+    service: web::Data<DynUserService>,
+    policy: web::Data<PasswordPolicy>,
+    metrics: web::Data<Metrics>,
+) -> Result<HttpResponse, ApiError> {
+    let (token, (username, password, email)) =
+        dto.into_inner().validate_and_into_domain(&policy).await?;
+
+    let mut pending = setup.lock().await;
+
+    if !pending.is_pending() {
+        return Err(ApiError::NotFound);
+    }
+
+    if !pending.matches(&token) {
+        return Err(ApiError::InvalidSetupToken);
+    }
+
+    if service.count().await? > 0 {
+        pending.consume();
+        return Err(ApiError::NotFound);
+    }
+
+    let admin = service
+        .create_user_with_role(username, password, email, Role::Admin)
+        .await?;
+    pending.consume();
+    metrics.users_registered.inc();
+
+    Ok(HttpResponse::Created().json(UserAdminView::from(admin)))
+}
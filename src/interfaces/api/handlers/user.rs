@@ -1,18 +1,30 @@
 use std::str::FromStr;
 
 use crate::{
-    application::user_service::UserService,
     domain::error::DomainError,
     infrastructure::{
         auth::{Claims, admin::AdminMiddleware, jwt::JwtMiddleware},
-        persistence::sqlite::user_repo::SqliteUserRepo,
+        export::build_archive,
+        metrics::Metrics,
+        security::password_policy::PasswordPolicy,
     },
     interfaces::api::{
-        dto::user::{NewUser, UpdateProfile, UpdateUser, UpdateUserPayload, UserPublic},
+        dto::user::{
+            NewUser, UpdateProfile, UpdateUser, UpdateUserPayload, UserAdminView, UserProfile,
+        },
         error::ApiError,
+        handlers::{access_token, impersonation},
+        json::Json,
+        multipart::read_file_field,
+        state::{DynAvatarService, DynGdprService, DynUserService},
     },
 };
-use actix_web::{HttpResponse, web};
+use actix_multipart::Multipart;
+use actix_web::{
+    HttpResponse,
+    http::header::{ContentDisposition, DispositionParam, DispositionType},
+    web,
+};
 use uuid::Uuid;
 
 pub fn config(cfg: &mut web::ServiceConfig) {
@@ -24,76 +36,99 @@ pub fn config(cfg: &mut web::ServiceConfig) {
             .route("", web::post().to(create_user))
             .route("/{id}", web::get().to(get_user))
             .route("/{id}", web::patch().to(update_user))
-            .route("/{id}", web::delete().to(delete_user)),
+            .route("/{id}", web::delete().to(delete_user))
+            .route("/{id}/export", web::get().to(export_user))
+            .route("/{id}/erase", web::post().to(erase_user))
+            .route(
+                "/{id}/impersonate",
+                web::post().to(impersonation::start_impersonation),
+            ),
     )
     .service(
         web::scope("/api/profile")
+            .configure(access_token::config)
             .route("", web::get().to(get_profile))
-            .route("", web::patch().to(update_profile)),
+            .route("", web::patch().to(update_profile))
+            .route("/export", web::get().to(export_profile))
+            .route("/erase", web::post().to(erase_profile))
+            .route("/avatar", web::post().to(upload_avatar))
+            .route("/avatar", web::delete().to(delete_avatar)),
     );
 }
 
-async fn list_users(
-    service: web::Data<UserService<SqliteUserRepo>>,
-) -> Result<HttpResponse, ApiError> {
-    let users = service.list().await.map_err(ApiError::from)?;
+#[tracing::instrument(skip_all)]
+async fn list_users(service: web::Data<DynUserService>) -> Result<HttpResponse, ApiError> {
+    let users: Vec<UserAdminView> = service
+        .list()
+        .await
+        .map_err(ApiError::from)?
+        .into_iter()
+        .map(UserAdminView::from)
+        .collect();
 
     Ok(HttpResponse::Ok().json(users))
 }
 
+#[tracing::instrument(skip_all)]
 async fn get_user(
-    service: web::Data<UserService<SqliteUserRepo>>,
+    service: web::Data<DynUserService>,
     id: web::Path<String>,
 ) -> Result<HttpResponse, ApiError> {
     let id = id.into_inner();
-    let id =
-        Uuid::from_str(&id).map_err(|_| ApiError::BadRequest("Invalid UUID format".to_string()))?;
+    let id = Uuid::from_str(&id).map_err(|_| ApiError::InvalidId)?;
 
     match service.find_by_id(id).await {
-        Ok(Some(user)) => Ok(HttpResponse::Ok().json(user)),
+        Ok(Some(user)) => Ok(HttpResponse::Ok().json(UserAdminView::from(user))),
         Ok(None) => Err(ApiError::NotFound),
         Err(_) => Err(ApiError::InternalError),
     }
 }
 
+#[tracing::instrument(skip_all)]
 async fn create_user(
-    dto: web::Json<NewUser>,
-    service: web::Data<UserService<SqliteUserRepo>>,
+    dto: Json<NewUser>,
+    service: web::Data<DynUserService>,
+    policy: web::Data<PasswordPolicy>,
+    metrics: web::Data<Metrics>,
 ) -> Result<HttpResponse, ApiError> {
-    let (username, password, email) = dto.into_inner().validate_and_into_domain()?;
+    let (username, password, email) = dto.into_inner().validate_and_into_domain(&policy).await?;
 
     let user = service
         .create_user(username, password, email)
         .await
         .map_err(ApiError::from)?;
+    metrics.users_registered.inc();
 
-    let public = UserPublic::from(user);
-
-    Ok(HttpResponse::Created().json(public))
+    Ok(HttpResponse::Created().json(UserAdminView::from(user)))
 }
 
+#[tracing::instrument(skip_all)]
 async fn update_user(
     path: web::Path<String>,
-    dto: web::Json<UpdateUser>,
-    service: web::Data<UserService<SqliteUserRepo>>,
+    dto: Json<UpdateUser>,
+    service: web::Data<DynUserService>,
+    policy: web::Data<PasswordPolicy>,
 ) -> Result<HttpResponse, ApiError> {
-    let id = Uuid::parse_str(&path.into_inner())
-        .map_err(|_| ApiError::BadRequest("Invalid UUID format".to_string()))?;
+    let id = Uuid::parse_str(&path.into_inner()).map_err(|_| ApiError::InvalidId)?;
+    let current = service.find_by_id(id).await?.ok_or(ApiError::NotFound)?;
 
-    let payload = dto.into_inner().validate_and_into_domain()?;
+    let payload = dto
+        .into_inner()
+        .validate_and_into_domain(&policy, &current)
+        .await?;
 
     let updated = service.update(id, payload).await.map_err(ApiError::from)?;
 
-    Ok(HttpResponse::Ok().json(updated))
+    Ok(HttpResponse::Ok().json(UserAdminView::from(updated)))
 }
 
+#[tracing::instrument(skip_all)]
 async fn delete_user(
-    service: web::Data<UserService<SqliteUserRepo>>,
+    service: web::Data<DynGdprService>,
     id: web::Path<String>,
 ) -> Result<HttpResponse, ApiError> {
     let id = id.into_inner();
-    let id =
-        Uuid::from_str(&id).map_err(|_| ApiError::BadRequest("Invalid UUID format".to_string()))?;
+    let id = Uuid::from_str(&id).map_err(|_| ApiError::InvalidId)?;
 
     match service.delete(id).await {
         Ok(()) => Ok(HttpResponse::NoContent().finish()),
@@ -102,27 +137,135 @@ async fn delete_user(
     }
 }
 
+#[tracing::instrument(skip_all)]
 async fn get_profile(
     claims: Claims,
-    service: web::Data<UserService<SqliteUserRepo>>,
+    service: web::Data<DynUserService>,
 ) -> Result<HttpResponse, ApiError> {
     let id = claims.user_id()?;
 
     let user = service.find_by_id(id).await?.ok_or(ApiError::NotFound)?;
 
-    Ok(HttpResponse::Ok().json(UserPublic::from(user)))
+    Ok(HttpResponse::Ok().json(UserProfile::from(user)))
 }
 
+#[tracing::instrument(skip_all)]
 async fn update_profile(
     claims: Claims,
-    dto: web::Json<UpdateProfile>,
-    service: web::Data<UserService<SqliteUserRepo>>,
+    dto: Json<UpdateProfile>,
+    service: web::Data<DynUserService>,
+    policy: web::Data<PasswordPolicy>,
 ) -> Result<HttpResponse, ApiError> {
+    // The credentials stay with the owner of the account.
+    if dto.changes_credentials() {
+        claims.not_impersonated()?;
+    }
     let id = claims.user_id()?;
+    let current = service.find_by_id(id).await?.ok_or(ApiError::NotFound)?;
 
-    let payload: UpdateUserPayload = dto.into_inner().validate_and_into_domain()?;
+    let payload: UpdateUserPayload = dto
+        .into_inner()
+        .validate_and_into_domain(&policy, &current)
+        .await?;
 
     let updated = service.update(id, payload).await.map_err(ApiError::from)?;
 
-    Ok(HttpResponse::Ok().json(updated))
+    Ok(HttpResponse::Ok().json(UserProfile::from(updated)))
+}
+
+#[tracing::instrument(skip_all)]
+async fn export_user(
+    service: web::Data<DynGdprService>,
+    id: web::Path<String>,
+) -> Result<HttpResponse, ApiError> {
+    let id = Uuid::from_str(&id.into_inner()).map_err(|_| ApiError::InvalidId)?;
+
+    export_archive(&service, id).await
+}
+
+#[tracing::instrument(skip_all)]
+async fn erase_user(
+    service: web::Data<DynGdprService>,
+    id: web::Path<String>,
+) -> Result<HttpResponse, ApiError> {
+    let id = Uuid::from_str(&id.into_inner()).map_err(|_| ApiError::InvalidId)?;
+
+    service.erase(id).await.map_err(ApiError::from)?;
+
+    Ok(HttpResponse::NoContent().finish())
+}
+
+#[tracing::instrument(skip_all)]
+async fn export_profile(
+    claims: Claims,
+    service: web::Data<DynGdprService>,
+) -> Result<HttpResponse, ApiError> {
+    let id = claims.user_id()?;
+
+    export_archive(&service, id).await
+}
+
+#[tracing::instrument(skip_all)]
+async fn erase_profile(
+    claims: Claims,
+    service: web::Data<DynGdprService>,
+) -> Result<HttpResponse, ApiError> {
+    // Erasure is irreversible and replaces the credentials.
+    claims.not_impersonated()?;
+    let id = claims.user_id()?;
+
+    service.erase(id).await.map_err(ApiError::from)?;
+
+    Ok(HttpResponse::NoContent().finish())
+}
+
+#[tracing::instrument(skip_all, fields(%id))]
+async fn export_archive(service: &DynGdprService, id: Uuid) -> Result<HttpResponse, ApiError> {
+    let export = service.export(id).await.map_err(ApiError::from)?;
+
+    let archive = build_archive(&export).map_err(|_| ApiError::InternalError)?;
+
+    let filename = format!(
+        "export-{}-{}.zip",
+        export.user.id.simple(),
+        export.exported_at.format("%Y%m%d%H%M%S")
+    );
+
+    Ok(HttpResponse::Ok()
+        .content_type("application/zip")
+        .insert_header(ContentDisposition {
+            disposition: DispositionType::Attachment,
+            parameters: vec![DispositionParam::Filename(filename)],
+        })
+        .body(archive))
+}
+
+#[tracing::instrument(skip_all)]
+async fn upload_avatar(
+    claims: Claims,
+    payload: Multipart,
+    service: web::Data<DynAvatarService>,
+) -> Result<HttpResponse, ApiError> {
+    let id = claims.user_id()?;
+
+    let file = read_file_field(payload, "avatar", service.max_bytes()).await?;
+
+    let updated = service
+        .upload(id, file.bytes)
+        .await
+        .map_err(ApiError::from)?;
+
+    Ok(HttpResponse::Ok().json(UserProfile::from(updated)))
+}
+
+#[tracing::instrument(skip_all)]
+async fn delete_avatar(
+    claims: Claims,
+    service: web::Data<DynAvatarService>,
+) -> Result<HttpResponse, ApiError> {
+    let id = claims.user_id()?;
+
+    let updated = service.remove(id).await.map_err(ApiError::from)?;
+
+    Ok(HttpResponse::Ok().json(UserProfile::from(updated)))
 }
@@ -0,0 +1,317 @@
+use std::rc::Rc;
+
+use actix_web::{
+    Error,
+    dev::{Service, ServiceRequest, ServiceResponse, Transform},
+    http::header::{AcceptLanguage, Header, Preference},
+};
+use futures_util::future::{LocalBoxFuture, Ready, ready};
+use serde::Deserialize;
+
+/// Language of the messages returned to API clients.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Locale {
+    #[default]
+    En,
+    Fr,
+}
+
+impl Locale {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Locale::En => "en",
+            Locale::Fr => "fr",
+        }
+    }
+
+    /// First supported language of an `Accept-Language` header, by quality.
+    pub fn negotiate(header: &AcceptLanguage) -> Option<Self> {
+        header
+            .ranked()
+            .into_iter()
+            .find_map(|preference| match preference {
+                Preference::Specific(tag) => match tag.primary_language() {
+                    "en" => Some(Locale::En),
+                    "fr" => Some(Locale::Fr),
+                    _ => None,
+                },
+                Preference::Any => None,
+            })
+    }
+}
+
+tokio::task_local! {
+    static LOCALE: Locale;
+}
+
+/// Locale of the request being handled, `Locale::default()` outside a request.
+pub fn current_locale() -> Locale {
+    LOCALE.try_with(|locale| *locale).unwrap_or_default()
+}
+
+/// `(english, french)` templates of an error or field code. `{name}` is
+/// replaced by the parameter of the same name.
+fn templates(code: &str) -> Option<(&'static str, &'static str)> {
+    let pair = match code {
+        // Problem codes
+        "not_found" => ("Resource not found", "Ressource introuvable"),
+        "invalid_id" => ("Invalid UUID format", "Identifiant UUID invalide"),
+        "invalid_body" => (
+            "Invalid JSON body: {detail}",
+            "Corps JSON invalide : {detail}",
+        ),
+        "invalid_query" => (
+            "Invalid query string: {detail}",
+            "Paramètres de requête invalides : {detail}",
+        ),
+        "invalid_multipart" => (
+            "Invalid multipart body: {detail}",
+            "Corps multipart invalide : {detail}",
+        ),
+        "validation_failed" => ("Some fields are invalid", "Certains champs sont invalides"),
+        "payload_too_large" => (
+            "Request body exceeds the limit of {max} bytes",
+            "Le corps de la requête dépasse la limite de {max} octets",
+        ),
+        "unsupported_media_type" => (
+            "Unsupported content type: {detail}",
+            "Type de contenu non supporté : {detail}",
+        ),
+        "internal_error" | "database_error" | "storage_error" | "password_hashing_failed" => {
+            ("Internal server error", "Erreur interne du serveur")
+        }
+        "missing_credentials" => (
+            "Authorization header is missing",
+            "L’en-tête Authorization est absent",
+        ),
+        "invalid_token" => ("Invalid or expired token", "Jeton invalide ou expiré"),
+        "admin_required" => ("Admin access required", "Accès réservé aux administrateurs"),
+        "insufficient_scope" => (
+            "The scopes of this token do not allow this request",
+            "Les portées de ce jeton ne permettent pas cette requête",
+        ),
+        "oidc_state_mismatch" => (
+            "The sign-in was not started here or has expired",
+            "La connexion n’a pas été initiée ici ou a expiré",
+        ),
+        "oidc_access_denied" => (
+            "The identity provider refused the sign-in: {detail}",
+            "Le fournisseur d’identité a refusé la connexion : {detail}",
+        ),
+        "oidc_provider_error" => (
+            "The identity provider could not be reached or answered an error",
+            "Le fournisseur d’identité est injoignable ou a répondu par une erreur",
+        ),
+        "invalid_id_token" => (
+            "The ID token of the identity provider is invalid",
+            "Le jeton d’identité du fournisseur est invalide",
+        ),
+        "email_not_verified" => (
+            "The identity provider did not vouch for the email address",
+            "Le fournisseur d’identité ne garantit pas l’adresse e-mail",
+        ),
+        "unknown_account" => (
+            "No account matches this identity",
+            "Aucun compte ne correspond à cette identité",
+        ),
+        "not_post_author" => (
+            "Only the author of the post or an administrator may change it",
+            "Seul l’auteur du post ou un administrateur peut le modifier",
+        ),
+        "impersonation_forbidden" => (
+            "This action is not allowed while impersonating a user",
+            "Cette action n’est pas permise en agissant à la place d’un utilisateur",
+        ),
+        "csrf_failed" => (
+            "The CSRF token of the session is missing or invalid",
+            "Le jeton CSRF de la session est absent ou invalide",
+        ),
+        "invalid_credentials" => ("Invalid credentials", "Identifiants invalides"),
+        "invalid_setup_token" => ("Invalid setup token", "Jeton d’installation invalide"),
+        "invalid_metrics_token" => ("Invalid metrics token", "Jeton de métriques invalide"),
+        "title_too_short" => (
+            "Title must contain at least 3 characters",
+            "Un titre doit contenir au moins 3 caractères",
+        ),
+        "title_too_long" => (
+            "Title must contain at most 255 characters",
+            "Un titre doit contenir au maximum 255 caractères",
+        ),
+        "content_empty" => (
+            "Content cannot be empty",
+            "Le contenu ne doit pas être vide",
+        ),
+        "invalid_user_id" => ("Invalid user ID", "L’ID de l’utilisateur est invalide"),
+        "email_taken" => (
+            "Email already exists",
+            "Cette adresse e-mail est déjà utilisée",
+        ),
+        "invalid_image" => ("Invalid image: {detail}", "Image invalide : {detail}"),
+        "file_too_large" => (
+            "File exceeds the maximum size of {max} bytes",
+            "Fichier trop volumineux (maximum {max} octets)",
+        ),
+        "media_in_use" => (
+            "Media is still used by a post",
+            "Ce média est encore utilisé par un post",
+        ),
+        // Field codes
+        "required" => ("This field is required", "Ce champ est obligatoire"),
+        "length_between" => (
+            "Must be between {min} and {max} characters long",
+            "Doit contenir entre {min} et {max} caractères",
+        ),
+        "length_min" => (
+            "Must be at least {min} characters long",
+            "Doit contenir au moins {min} caractères",
+        ),
+        "length_max" => (
+            "Must be at most {max} characters long",
+            "Doit contenir au plus {max} caractères",
+        ),
+        "range" => (
+            "Must be between {min} and {max}",
+            "Doit être compris entre {min} et {max}",
+        ),
+        "scope_not_allowed" => (
+            "The scope {scope} cannot be granted to this account",
+            "La portée {scope} ne peut pas être accordée à ce compte",
+        ),
+        "email" => ("Invalid email address", "Adresse e-mail invalide"),
+        "url" => ("Must be a valid URL", "Doit être une URL valide"),
+        "url_scheme" => (
+            "Must be an http or https URL",
+            "Doit être une URL http ou https",
+        ),
+        "invalid_type" => ("Invalid value: {detail}", "Valeur invalide : {detail}"),
+        "password_too_short" => (
+            "Password must be at least {min} characters long",
+            "Le mot de passe doit contenir au moins {min} caractères",
+        ),
+        "password_too_long" => (
+            "Password must be at most {max} characters long",
+            "Le mot de passe doit contenir au plus {max} caractères",
+        ),
+        "password_missing_uppercase" => (
+            "Password must contain at least one uppercase letter",
+            "Le mot de passe doit contenir au moins une majuscule",
+        ),
+        "password_missing_lowercase" => (
+            "Password must contain at least one lowercase letter",
+            "Le mot de passe doit contenir au moins une minuscule",
+        ),
+        "password_missing_digit" => (
+            "Password must contain at least one digit",
+            "Le mot de passe doit contenir au moins un chiffre",
+        ),
+        "password_missing_special_char" => (
+            "Password must contain at least one special character",
+            "Le mot de passe doit contenir au moins un caractère spécial",
+        ),
+        "password_too_weak" => (
+            "Password is too easy to guess (strength below {min} out of 4)",
+            "Le mot de passe est trop facile à deviner (robustesse inférieure à {min} sur 4)",
+        ),
+        "password_contains_personal_info" => (
+            "Password must not contain the username or the email address",
+            "Le mot de passe ne doit pas contenir le nom d’utilisateur ni l’adresse e-mail",
+        ),
+        "password_breached" => (
+            "Password appears in a list of leaked passwords",
+            "Ce mot de passe figure dans une liste de mots de passe divulgués",
+        ),
+        "password_mismatch" => (
+            "Passwords do not match",
+            "Les mots de passe ne correspondent pas",
+        ),
+        _ => return None,
+    };
+
+    Some(pair)
+}
+
+/// Message of `code` in `locale`; the code itself when it has no translation.
+pub fn message(locale: Locale, code: &str, params: &[(&str, String)]) -> String {
+    let Some((en, fr)) = templates(code) else {
+        return code.to_string();
+    };
+    let template = match locale {
+        Locale::En => en,
+        Locale::Fr => fr,
+    };
+
+    params
+        .iter()
+        .fold(template.to_string(), |text, (name, value)| {
+            text.replace(&format!("{{{}}}", name), value)
+        })
+}
+
+/// Message of `code` in the locale of the current request.
+pub fn localize(code: &str, params: &[(&str, String)]) -> String {
+    message(current_locale(), code, params)
+}
+
+/// Picks the locale of each request from `Accept-Language`, falling back to
+/// `default`, and makes it available through `current_locale`.
+pub struct RequestLocale {
+    default: Locale,
+}
+
+impl RequestLocale {
+    pub fn new(default: Locale) -> Self {
+        Self { default }
+    }
+}
+
+impl<S, B> Transform<S, ServiceRequest> for RequestLocale
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type InitError = ();
+    type Transform = RequestLocaleMiddleware<S>;
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(RequestLocaleMiddleware {
+            service: Rc::new(service),
+            default: self.default,
+        }))
+    }
+}
+
+pub struct RequestLocaleMiddleware<S> {
+    service: Rc<S>,
+    default: Locale,
+}
+
+impl<S, B> Service<ServiceRequest> for RequestLocaleMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(
+        &self,
+        ctx: &mut core::task::Context<'_>,
+    ) -> std::task::Poll<Result<(), Self::Error>> {
+        self.service.poll_ready(ctx)
+    }
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let locale = AcceptLanguage::parse(&req)
+            .ok()
+            .and_then(|header| Locale::negotiate(&header))
+            .unwrap_or(self.default);
+        let fut = self.service.call(req);
+
+        Box::pin(LOCALE.scope(locale, fut))
+    }
+}
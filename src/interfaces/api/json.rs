@@ -0,0 +1,96 @@
+use std::ops::Deref;
+
+use actix_web::{
+    FromRequest, HttpRequest, dev::Payload, error::JsonPayloadError, http::header::CONTENT_TYPE,
+    web,
+};
+use futures_util::future::LocalBoxFuture;
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+
+use crate::interfaces::api::{error::ApiError, i18n::localize, validation::FieldError};
+
+/// Replacement for `web::Json` that reports which field of the body could not
+/// be deserialized. The body is first parsed as a `Value` through
+/// `web::Json`, so `JsonConfig` (limit, content type, `json_error`) still
+/// applies to malformed documents.
+#[derive(Debug)]
+pub struct Json<T>(pub T);
+
+impl<T> Json<T> {
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T> Deref for Json<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T: DeserializeOwned + 'static> FromRequest for Json<T> {
+    type Error = actix_web::Error;
+    type Future = LocalBoxFuture<'static, Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, payload: &mut Payload) -> Self::Future {
+        let body = web::Json::<Value>::from_request(req, payload);
+
+        Box::pin(async move {
+            let value = body.await?.into_inner();
+
+            serde_path_to_error::deserialize(value)
+                .map(Json)
+                .map_err(|err| ApiError::Validation(vec![field_error(err)]).into())
+        })
+    }
+}
+
+fn field_error(err: serde_path_to_error::Error<serde_json::Error>) -> FieldError {
+    let path = err.path().to_string();
+    let message = err.inner().to_string();
+
+    // serde reports a missing field on its parent, with the name in the message.
+    if let Some(name) = message
+        .strip_prefix("missing field `")
+        .and_then(|rest| rest.strip_suffix('`'))
+    {
+        let field = if path == "." {
+            name.to_string()
+        } else {
+            format!("{}.{}", path, name)
+        };
+
+        return FieldError {
+            field,
+            code: "required".into(),
+            message: localize("required", &[]),
+        };
+    }
+
+    FieldError {
+        field: if path == "." { String::new() } else { path },
+        code: "invalid_type".into(),
+        message: localize("invalid_type", &[("detail", message)]),
+    }
+}
+
+/// Error handler of `web::JsonConfig`, so that malformed bodies get a problem
+/// document like any other error.
+pub fn json_error(err: JsonPayloadError, req: &HttpRequest) -> actix_web::Error {
+    match err {
+        JsonPayloadError::ContentType => ApiError::UnsupportedMediaType(
+            req.headers()
+                .get(CONTENT_TYPE)
+                .and_then(|h| h.to_str().ok())
+                .unwrap_or("none")
+                .to_string(),
+        ),
+        JsonPayloadError::Overflow { limit }
+        | JsonPayloadError::OverflowKnownLength { limit, .. } => ApiError::PayloadTooLarge(limit),
+        err => ApiError::InvalidBody(err.to_string()),
+    }
+    .into()
+}
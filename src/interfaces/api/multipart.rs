@@ -0,0 +1,51 @@
+use actix_multipart::Multipart;
+use futures_util::TryStreamExt;
+
+use crate::{
+    domain::error::DomainError,
+    interfaces::api::{error::ApiError, validation::missing_field},
+};
+
+pub struct UploadedFile {
+    pub bytes: Vec<u8>,
+    pub filename: Option<String>,
+}
+
+/// Reads the file sent in the `field_name` part of a multipart body, refusing
+/// it as soon as it grows past `max_bytes`. Other parts are skipped.
+pub async fn read_file_field(
+    mut payload: Multipart,
+    field_name: &str,
+    max_bytes: usize,
+) -> Result<UploadedFile, ApiError> {
+    while let Some(mut field) = payload
+        .try_next()
+        .await
+        .map_err(|e| ApiError::InvalidMultipart(e.to_string()))?
+    {
+        if field.name() != Some(field_name) {
+            continue;
+        }
+
+        let filename = field
+            .content_disposition()
+            .and_then(|cd| cd.get_filename())
+            .map(str::to_owned);
+
+        let mut bytes = Vec::new();
+        while let Some(chunk) = field
+            .try_next()
+            .await
+            .map_err(|e| ApiError::InvalidMultipart(e.to_string()))?
+        {
+            if bytes.len() + chunk.len() > max_bytes {
+                return Err(DomainError::FileTooLarge(max_bytes).into());
+            }
+            bytes.extend_from_slice(&chunk);
+        }
+
+        return Ok(UploadedFile { bytes, filename });
+    }
+
+    Err(missing_field(field_name))
+}
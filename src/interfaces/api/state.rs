@@ -0,0 +1,119 @@
+use std::{sync::Arc, time::Duration};
+
+use crate::{
+    application::{
+        access_token_service::AccessTokenService, avatar_service::AvatarService,
+        gdpr_service::GdprService, impersonation_service::ImpersonationService,
+        media_service::MediaService, oidc_service::OidcService, post_service::PostService,
+        user_service::UserService,
+    },
+    config::Settings,
+    domain::repository::{
+        DynAccessTokenRepository, DynAuditRepository, DynIdentityRepository, DynMediaRepository,
+        DynPostRepository, DynUserRepository,
+    },
+    infrastructure::{
+        auth::{oidc::OidcClient, password::PasswordHashing, setup::SetupToken},
+        db::Database,
+        metrics::Metrics,
+        security::{keys::Keys, password_policy::PasswordPolicy},
+        storage::{BlobLocks, BlobStore},
+    },
+};
+
+pub type DynPostService = PostService<DynPostRepository, DynUserRepository>;
+pub type DynUserService = UserService<DynUserRepository>;
+pub type DynGdprService = GdprService<DynPostRepository, DynUserRepository, DynMediaRepository>;
+pub type DynAvatarService = AvatarService<DynUserRepository>;
+pub type DynMediaService = MediaService<DynMediaRepository, DynPostRepository>;
+pub type DynAccessTokenService = AccessTokenService<DynAccessTokenRepository>;
+pub type DynOidcService = OidcService<DynUserRepository, DynIdentityRepository>;
+pub type DynImpersonationService = ImpersonationService<DynUserRepository, DynAuditRepository>;
+
+/// Everything the handlers pull out of `app_data`, shared by every worker.
+#[derive(Clone)]
+pub struct AppState {
+    pub post_service: DynPostService,
+    pub user_service: DynUserService,
+    pub gdpr_service: DynGdprService,
+    pub avatar_service: DynAvatarService,
+    pub media_service: DynMediaService,
+    /// Also used by the authentication layer to accept personal access tokens.
+    pub access_token_service: DynAccessTokenService,
+    /// Present when `OIDC__*` is configured.
+    pub oidc_service: Option<DynOidcService>,
+    /// Also used by the authentication layer to refuse ended sessions.
+    pub impersonation_service: DynImpersonationService,
+    /// Looked up by the authentication layer to refuse revoked tokens.
+    pub users: DynUserRepository,
+    /// Probed by `/health/ready`.
+    pub db: Database,
+    pub setup: SetupToken,
+    pub metrics: Metrics,
+    pub store: Arc<dyn BlobStore>,
+    pub keys: Keys,
+    pub password_policy: PasswordPolicy,
+    pub settings: Settings,
+}
+
+impl AppState {
+    pub fn new(
+        db: &Database,
+        store: Arc<dyn BlobStore>,
+        hashing: PasswordHashing,
+        settings: Settings,
+    ) -> Self {
+        let repos = db.repositories();
+        let keys = Keys::new(settings.jwt_secret.as_bytes());
+        let blob_locks = BlobLocks::default();
+
+        Self {
+            post_service: PostService::new(repos.posts.clone(), repos.users.clone()),
+            user_service: UserService::new(repos.users.clone(), keys.clone(), hashing.clone()),
+            gdpr_service: GdprService::new(
+                repos.posts.clone(),
+                repos.users.clone(),
+                repos.media.clone(),
+                store.clone(),
+                blob_locks.clone(),
+            ),
+            avatar_service: AvatarService::new(
+                repos.users.clone(),
+                store.clone(),
+                settings.uploads.avatar_max_bytes,
+            ),
+            media_service: MediaService::new(
+                repos.media,
+                repos.posts,
+                store.clone(),
+                blob_locks,
+                settings.uploads.media_max_bytes,
+                Duration::from_secs(settings.storage.signed_url_ttl_secs),
+            ),
+            access_token_service: AccessTokenService::new(repos.access_tokens),
+            oidc_service: settings.oidc.clone().map(|oidc| {
+                OidcService::new(
+                    Arc::new(OidcClient::new(oidc)),
+                    repos.users.clone(),
+                    repos.identities,
+                    keys.clone(),
+                    Keys::derived(settings.jwt_secret.as_bytes(), "oidc-pending-login"),
+                    hashing,
+                )
+            }),
+            impersonation_service: ImpersonationService::new(
+                repos.users.clone(),
+                repos.audit_log,
+                keys.clone(),
+            ),
+            users: repos.users,
+            db: db.clone(),
+            setup: SetupToken::default(),
+            metrics: Metrics::new(),
+            store,
+            keys,
+            password_policy: PasswordPolicy::from_settings(&settings.password),
+            settings,
+        }
+    }
+}
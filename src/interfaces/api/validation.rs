@@ -1,22 +1,144 @@
+use std::borrow::Cow;
+
 use crate::{
-    domain::validation::{PasswordRequirements, validate_password},
-    interfaces::api::error::ApiError,
+    domain::validation::PasswordViolation,
+    infrastructure::security::password_policy::PasswordPolicy,
+    interfaces::api::{error::ApiError, i18n::localize},
 };
-use validator::Validate;
+use serde::Serialize;
+use validator::{Validate, ValidationError, ValidationErrors, ValidationErrorsKind};
 
-pub fn validate_dto<T: Validate>(dto: &T) -> Result<(), ApiError> {
-    dto.validate()
-        .map_err(|e| ApiError::BadRequest(e.to_string()))
+/// One entry of the `errors` member of a 422 problem document.
+#[derive(Debug, Clone, Serialize)]
+pub struct FieldError {
+    pub field: String,
+    pub code: Cow<'static, str>,
+    pub message: String,
 }
 
-pub fn require_field<T>(opt: Option<T>, name: &str) -> Result<T, ApiError> {
-    opt.ok_or_else(|| ApiError::BadRequest(format!(r#"{} is required"#, name)))
+/// Collects the invalid fields of a request so that they are all reported at once.
+#[derive(Debug, Default)]
+pub struct FieldErrors(Vec<FieldError>);
+
+impl FieldErrors {
+    /// Starts from the `#[validate]` rules of the DTO.
+    pub fn of<T: Validate>(dto: &T) -> Self {
+        let mut errors = Self::default();
+        if let Err(e) = dto.validate() {
+            errors.extend_from("", &e);
+        }
+
+        errors
+    }
+
+    fn extend_from(&mut self, prefix: &str, errors: &ValidationErrors) {
+        let mut fields: Vec<_> = errors.errors().iter().collect();
+        fields.sort_by(|a, b| a.0.cmp(b.0));
+
+        for (field, kind) in fields {
+            let path = if prefix.is_empty() {
+                field.to_string()
+            } else {
+                format!("{}.{}", prefix, field)
+            };
+
+            match kind {
+                ValidationErrorsKind::Field(errors) => {
+                    for error in errors {
+                        self.push_validator_error(&path, error);
+                    }
+                }
+                ValidationErrorsKind::Struct(errors) => self.extend_from(&path, errors),
+                ValidationErrorsKind::List(items) => {
+                    for (index, errors) in items {
+                        self.extend_from(&format!("{}[{}]", path, index), errors);
+                    }
+                }
+            }
+        }
+    }
+
+    /// The message is looked up in the catalog with the code of the rule; a
+    /// `length` rule picks the variant matching the bounds it was given.
+    fn push_validator_error(&mut self, field: &str, error: &ValidationError) {
+        let params: Vec<(&str, String)> = error
+            .params
+            .iter()
+            .filter(|(name, _)| *name != "value")
+            .map(|(name, value)| {
+                let value = value
+                    .as_str()
+                    .map(str::to_string)
+                    .unwrap_or_else(|| value.to_string());
+                (name.as_ref(), value)
+            })
+            .collect();
+
+        let key = match error.code.as_ref() {
+            "length" => match (error.params.get("min"), error.params.get("max")) {
+                (Some(_), Some(_)) => "length_between",
+                (Some(_), None) => "length_min",
+                _ => "length_max",
+            },
+            code => code,
+        };
+
+        self.0.push(FieldError {
+            field: field.to_string(),
+            code: error.code.clone(),
+            message: localize(key, &params),
+        });
+    }
+
+    /// Adds an error whose message is the one of `code` in the catalog.
+    pub fn add(&mut self, field: &str, code: &'static str, params: &[(&str, String)]) {
+        self.0.push(FieldError {
+            field: field.to_string(),
+            code: Cow::Borrowed(code),
+            message: localize(code, params),
+        });
+    }
+
+    /// Reports every password rule the value breaks, not only the first one.
+    /// `personal` holds the username and email of the account.
+    pub async fn check_password(
+        &mut self,
+        field: &str,
+        password: &str,
+        policy: &PasswordPolicy,
+        personal: &[&str],
+    ) {
+        for violation in policy.check(password, personal).await {
+            let params = match violation {
+                PasswordViolation::TooShort(min) => vec![("min", min.to_string())],
+                PasswordViolation::TooLong(max) => vec![("max", max.to_string())],
+                PasswordViolation::TooWeak(score) => vec![("min", score.to_string())],
+                _ => Vec::new(),
+            };
+            self.add(field, violation.code(), &params);
+        }
+    }
+
+    pub fn into_result(self) -> Result<(), ApiError> {
+        if self.0.is_empty() {
+            Ok(())
+        } else {
+            Err(ApiError::Validation(self.0))
+        }
+    }
+}
+
+pub fn validate_dto<T: Validate>(dto: &T) -> Result<(), ApiError> {
+    FieldErrors::of(dto).into_result()
 }
 
-pub fn require_password(opt: Option<String>) -> Result<String, ApiError> {
-    let pwd = require_field(opt, "password")?;
-    validate_password(&pwd, &PasswordRequirements::default())
-        .map_err(|e| ApiError::BadRequest(e.to_string()))?;
+pub fn missing_field(name: &str) -> ApiError {
+    let mut errors = FieldErrors::default();
+    errors.add(name, "required", &[]);
 
-    Ok(pwd)
+    ApiError::Validation(errors.0)
+}
+
+pub fn require_field<T>(opt: Option<T>, name: &str) -> Result<T, ApiError> {
+    opt.ok_or_else(|| missing_field(name))
 }
@@ -1,4 +1,26 @@
+use actix_web::{
+    App,
+    body::MessageBody,
+    dev::{ServiceFactory, ServiceRequest, ServiceResponse},
+    web,
+};
+
+use crate::{
+    infrastructure::{
+        logging::request_id::RequestTracing,
+        metrics::RequestMetrics,
+        security::{cors::build_cors, headers::secure_headers, hsts::Hsts},
+    },
+    interfaces::api::{i18n::RequestLocale, state::AppState},
+};
+
 pub mod application {
+    pub mod access_token_service;
+    pub mod avatar_service;
+    pub mod gdpr_service;
+    pub mod impersonation_service;
+    pub mod media_service;
+    pub mod oidc_service;
     pub mod post_service;
     pub mod user_service;
 }
@@ -7,6 +29,10 @@ pub mod config;
 
 pub mod domain {
     pub mod model {
+        pub mod access_token;
+        pub mod audit;
+        pub mod identity;
+        pub mod media;
         pub mod post;
         pub mod user;
     }
@@ -18,17 +44,47 @@ pub mod domain {
 pub mod infrastructure {
     pub mod auth;
     pub mod db;
+    pub mod export;
+    pub mod health;
+    pub mod imaging;
+    pub mod logging;
+    pub mod metrics;
 
     pub mod security {
         pub mod cors;
         pub mod headers;
         pub mod hsts;
         pub mod keys;
+        pub mod password_policy;
         pub mod tls;
     }
 
+    pub mod storage;
+
     pub mod persistence {
+        #[cfg(feature = "in-memory")]
+        pub mod memory {
+            pub mod access_token_repo;
+            pub mod audit_repo;
+            pub mod database;
+            pub mod identity_repo;
+            pub mod media_repo;
+            pub mod post_repo;
+            pub mod user_repo;
+        }
+        pub mod postgres {
+            pub mod access_token_repo;
+            pub mod audit_repo;
+            pub mod identity_repo;
+            pub mod media_repo;
+            pub mod post_repo;
+            pub mod user_repo;
+        }
         pub mod sqlite {
+            pub mod access_token_repo;
+            pub mod audit_repo;
+            pub mod identity_repo;
+            pub mod media_repo;
             pub mod post_repo;
             pub mod user_repo;
         }
@@ -38,15 +94,31 @@ pub mod infrastructure {
 pub mod interfaces {
     pub mod api {
         pub mod error;
+        pub mod i18n;
+        pub mod json;
+        pub mod multipart;
+        pub mod state;
         pub mod validation;
 
         pub mod dto {
+            pub mod access_token;
+            pub mod file;
+            pub mod impersonation;
+            pub mod media;
             pub mod post;
             pub mod user;
         }
         pub mod handlers {
+            pub mod access_token;
+            pub mod file;
+            pub mod health;
+            pub mod impersonation;
             pub mod login;
+            pub mod media;
+            pub mod metrics;
+            pub mod oidc;
             pub mod post;
+            pub mod setup;
             pub mod user;
         }
 
@@ -54,6 +126,81 @@ pub mod interfaces {
             handlers::user::config(cfg);
             handlers::post::config(cfg);
             handlers::login::config(cfg);
+            handlers::oidc::config(cfg);
+            handlers::impersonation::config(cfg);
+            handlers::media::config(cfg);
+            handlers::file::config(cfg);
+            handlers::setup::config(cfg);
+            handlers::health::config(cfg);
+            handlers::metrics::config(cfg);
         }
     }
 }
+
+/// Builds the application with its middleware stack and routes; shared by the
+/// server binary and the integration tests.
+pub fn build_app(
+    state: AppState,
+) -> App<
+    impl ServiceFactory<
+        ServiceRequest,
+        Config = (),
+        Response = ServiceResponse<impl MessageBody>,
+        Error = actix_web::Error,
+        InitError = (),
+    >,
+> {
+    let cors = build_cors(&state.settings.cors_origin);
+
+    App::new()
+        .wrap(Hsts)
+        .wrap(cors)
+        .wrap(secure_headers())
+        .wrap(RequestLocale::new(state.settings.default_locale))
+        .wrap(RequestMetrics::new(state.metrics.clone()))
+        .wrap(RequestTracing::new(
+            &interfaces::api::handlers::health::PATHS,
+        ))
+        .app_data(web::Data::new(state.post_service))
+        .app_data(web::Data::new(state.user_service))
+        .app_data(web::Data::new(state.gdpr_service))
+        .app_data(web::Data::new(state.avatar_service))
+        .app_data(web::Data::new(state.media_service))
+        .app_data(web::Data::new(state.access_token_service))
+        .configure(|cfg| {
+            if let Some(oidc_service) = state.oidc_service {
+                cfg.app_data(web::Data::new(oidc_service));
+            }
+        })
+        .app_data(web::Data::new(state.impersonation_service))
+        .app_data(web::Data::from(state.users))
+        .app_data(web::Data::new(state.setup))
+        .app_data(web::Data::new(state.db))
+        .app_data(web::Data::new(state.metrics))
+        .app_data(web::Data::from(state.store))
+        .app_data(web::Data::new(state.keys))
+        .app_data(web::Data::new(state.password_policy))
+        .app_data(web::Data::new(state.settings))
+        .app_data(web::JsonConfig::default().error_handler(interfaces::api::json::json_error))
+        .app_data(web::QueryConfig::default().error_handler(interfaces::api::error::query_error))
+        .configure(interfaces::api::config)
+        .default_service(web::to(interfaces::api::error::not_found))
+}
+
+/// The private listener of `METRICS__PORT`: only `/metrics`, without token.
+pub fn build_metrics_app(
+    state: AppState,
+) -> App<
+    impl ServiceFactory<
+        ServiceRequest,
+        Config = (),
+        Response = ServiceResponse<impl MessageBody>,
+        Error = actix_web::Error,
+        InitError = (),
+    >,
+> {
+    App::new()
+        .app_data(web::Data::new(state.db))
+        .app_data(web::Data::new(state.metrics))
+        .configure(interfaces::api::handlers::metrics::config_private)
+}
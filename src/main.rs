@@ -1,55 +1,83 @@
-use actix_cors::Cors;
-use actix_web::middleware::Logger;
-use actix_web::{App, HttpServer, web};
-use anyhow::Result;
-use api_back_trio::application::post_service::PostService;
-use api_back_trio::application::user_service::UserService;
+use actix_web::HttpServer;
+
+use anyhow::{Result, anyhow};
 use api_back_trio::config::Settings;
-use api_back_trio::infrastructure::security::headers::secure_headers;
+use api_back_trio::domain::model::user::Role;
+use api_back_trio::infrastructure::auth::password::PasswordHashing;
+use api_back_trio::infrastructure::logging::init_logging;
 use api_back_trio::infrastructure::security::tls::build_ssl_acceptor;
-use api_back_trio::infrastructure::{
-    db::init_db,
-    persistence::sqlite::{post_repo::SqlitePostRepo, user_repo::SqliteUserRepo},
-    security::cors::build_cors,
-    security::hsts::Hsts,
-    security::keys::Keys,
-};
-use api_back_trio::interfaces::api::config as api_config;
-use env_logger::Env;
+use api_back_trio::infrastructure::{db::init_db, storage::build_blob_store};
+use api_back_trio::interfaces::api::{dto::user::NewUser, state::AppState};
+use api_back_trio::{build_app, build_metrics_app};
+use tracing::{info, warn};
 
 #[actix_web::main]
 async fn main() -> Result<()> {
     let settings = Settings::from_env()?;
 
-    env_logger::Builder::from_env(Env::default()).init();
-    let pool = init_db(&settings.database_url).await?;
-    let post_repo = SqlitePostRepo::new(pool.clone());
-    let user_repo = SqliteUserRepo::new(pool.clone());
-    let keys = Keys::new(settings.jwt_secret.as_bytes());
-    let post_service = PostService::new(post_repo, user_repo.clone());
-    let user_service = UserService::new(user_repo, keys.clone());
+    let telemetry = init_logging(&settings.tracing)?;
+    let db = init_db(&settings.database_url).await?;
+    let store = build_blob_store(&settings.storage, &settings.jwt_secret)?;
+    let hashing = PasswordHashing::new(&settings.argon2)?;
     let ssl = build_ssl_acceptor(
         &settings.tls.as_ref().unwrap().cert_path,
         &settings.tls.as_ref().unwrap().key_path,
     )?;
     let server_settings = settings.server.clone();
-    HttpServer::new(move || {
-        let cors_middleware: Cors = build_cors(&settings.cors_origin);
-
-        App::new()
-            .wrap(Hsts)
-            .wrap(cors_middleware)
-            .wrap(Logger::default())
-            .wrap(secure_headers())
-            .app_data(web::Data::new(post_service.clone()))
-            .app_data(web::Data::new(user_service.clone()))
-            .app_data(web::Data::new(keys.clone()))
-            .app_data(web::Data::new(settings.clone()))
-            .configure(api_config)
-    })
-    .bind_openssl((server_settings.host, server_settings.port), ssl)?
-    .run()
-    .await?;
+    let metrics_settings = settings.metrics.clone();
+    let state = AppState::new(&db, store, hashing, settings);
+    bootstrap_admin(&state).await?;
+
+    let metrics_state = state.clone();
+    let api = HttpServer::new(move || build_app(state.clone()))
+        .bind_openssl((server_settings.host, server_settings.port), ssl)?
+        .run();
+
+    match metrics_settings.port {
+        Some(port) => {
+            let metrics = HttpServer::new(move || build_metrics_app(metrics_state.clone()))
+                .workers(1)
+                .bind((metrics_settings.host, port))?
+                .run();
+            futures_util::try_join!(api, metrics)?;
+        }
+        None => api.await?,
+    }
+
+    telemetry.shutdown()
+}
+
+/// Gives a fresh database its first administrator, from the `ADMIN__*` settings
+/// when present, otherwise through a one-time token for `/api/setup`.
+async fn bootstrap_admin(state: &AppState) -> Result<()> {
+    if state.user_service.count().await? > 0 {
+        return Ok(());
+    }
+
+    let Some(admin) = state.settings.admin.clone() else {
+        let token = state.setup.issue().await;
+        warn!("No user yet: POST /api/setup with this one-time token to create the admin: {token}");
+        return Ok(());
+    };
+
+    let dto = NewUser {
+        username: Some(admin.username),
+        password: Some(admin.password),
+        email: Some(admin.email),
+    };
+    let (username, password, email) = dto
+        .validate_and_into_domain(&state.password_policy)
+        .await
+        .map_err(|e| anyhow!("Invalid ADMIN__* settings: {e}"))?;
+
+    let user = state
+        .user_service
+        .create_user_with_role(username, password, email, Role::Admin)
+        .await?;
+    info!(
+        "Administrator {} created from ADMIN__* settings",
+        user.username
+    );
 
     Ok(())
 }
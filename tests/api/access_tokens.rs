@@ -0,0 +1,131 @@
+use actix_web::{http::StatusCode, test};
+use api_back_trio::{
+    application::access_token_service::AccessTokenService,
+    domain::model::{access_token::Scope, user::Role},
+};
+use chrono::{Duration, Utc};
+use serde_json::json;
+
+use crate::support::{authed, spawn_app};
+
+#[actix_web::test]
+async fn tokens_are_created_listed_and_used() {
+    let app = spawn_app().await;
+    let user = app.user().await;
+
+    let (status, created) = app
+        .create_access_token(&user, &["posts:read", "posts:write"])
+        .await;
+    assert_eq!(status, StatusCode::CREATED, "{}", created);
+    let token = created["token"].as_str().unwrap();
+    assert!(token.starts_with("blog_pat_"));
+    assert!(token.starts_with(created["prefix"].as_str().unwrap()));
+    assert_eq!(created["scopes"], json!(["posts:read", "posts:write"]));
+
+    let (status, post) = app
+        .json(
+            authed(test::TestRequest::post().uri("/api/posts"), token).set_json(json!({
+                "title": "From CI",
+                "content": "Published with a token",
+                "published": true,
+                "user_id": user.id,
+            })),
+        )
+        .await;
+    assert_eq!(status, StatusCode::CREATED, "{}", post);
+
+    let (status, tokens) = app
+        .json(authed(
+            test::TestRequest::get().uri("/api/profile/tokens"),
+            &user.token,
+        ))
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(tokens.as_array().unwrap().len(), 1);
+    assert_eq!(tokens[0]["name"], "ci");
+    assert!(tokens[0]["lastUsedAt"].is_string());
+    assert!(tokens[0].get("token").is_none());
+}
+
+#[actix_web::test]
+async fn scopes_limit_what_a_token_can_do() {
+    let app = spawn_app().await;
+    let user = app.user().await;
+    let (_, created) = app.create_access_token(&user, &["posts:read"]).await;
+    let token = created["token"].as_str().unwrap();
+
+    let (status, _) = app
+        .json(authed(test::TestRequest::get().uri("/api/posts"), token))
+        .await;
+    assert_eq!(status, StatusCode::OK);
+
+    for req in [
+        test::TestRequest::post().uri("/api/posts"),
+        test::TestRequest::get().uri("/api/profile"),
+        test::TestRequest::get().uri("/api/profile/tokens"),
+    ] {
+        let (status, body) = app.json(authed(req, token)).await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+        assert_eq!(body["code"], "insufficient_scope");
+    }
+}
+
+#[actix_web::test]
+async fn only_administrators_get_the_admin_scope() {
+    let app = spawn_app().await;
+    let user = app.user().await;
+    let admin = app.admin().await;
+
+    let (status, body) = app.create_access_token(&user, &["admin"]).await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+    assert_eq!(body["errors"][0]["code"], "scope_not_allowed");
+
+    let (status, created) = app.create_access_token(&admin, &["admin"]).await;
+    assert_eq!(status, StatusCode::CREATED);
+    let (status, _) = app
+        .json(authed(
+            test::TestRequest::get().uri("/api/users"),
+            created["token"].as_str().unwrap(),
+        ))
+        .await;
+    assert_eq!(status, StatusCode::OK);
+}
+
+#[actix_web::test]
+async fn revoked_and_expired_tokens_are_refused() {
+    let app = spawn_app().await;
+    let user = app.create_user("alice", Role::User).await;
+    let (_, created) = app.create_access_token(&user, &["posts:read"]).await;
+    let token = created["token"].as_str().unwrap();
+
+    let (status, _) = app
+        .json(authed(
+            test::TestRequest::delete().uri(&format!(
+                "/api/profile/tokens/{}",
+                created["id"].as_str().unwrap()
+            )),
+            &user.token,
+        ))
+        .await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+
+    let (status, body) = app
+        .json(authed(test::TestRequest::get().uri("/api/posts"), token))
+        .await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    assert_eq!(body["code"], "invalid_token");
+
+    let (_, expired) = AccessTokenService::new(app.repos.access_tokens.clone())
+        .create(
+            user.id,
+            "old".to_string(),
+            vec![Scope::PostsRead],
+            Utc::now() - Duration::minutes(1),
+        )
+        .await
+        .unwrap();
+    let (status, _) = app
+        .json(authed(test::TestRequest::get().uri("/api/posts"), &expired))
+        .await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+}
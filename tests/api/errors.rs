@@ -0,0 +1,109 @@
+use actix_web::{
+    http::{StatusCode, header},
+    test,
+};
+use serde_json::{Value, json};
+
+use crate::support::{PASSWORD, authed, spawn_app};
+
+async fn problem(
+    resp: actix_web::dev::ServiceResponse<impl actix_web::body::MessageBody>,
+) -> (StatusCode, Value) {
+    let status = resp.status();
+    assert_eq!(
+        resp.headers().get(header::CONTENT_TYPE).unwrap(),
+        "application/problem+json"
+    );
+    let body: Value = test::read_body_json(resp).await;
+    assert_eq!(body["status"], status.as_u16());
+
+    (status, body)
+}
+
+#[actix_web::test]
+async fn middleware_rejections_are_problem_documents() {
+    let app = spawn_app().await;
+    let user = app.user().await;
+
+    let (status, body) = problem(app.call(test::TestRequest::get().uri("/api/posts")).await).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    assert_eq!(body["code"], "missing_credentials");
+    assert_eq!(body["type"], "/problems/missing_credentials");
+    assert_eq!(body["title"], "Unauthorized");
+    assert_eq!(body["instance"], "/api/posts");
+    assert!(body["detail"].is_string());
+    assert!(body["request_id"].is_string());
+
+    let (status, body) = problem(
+        app.call(authed(
+            test::TestRequest::get().uri("/api/users"),
+            &user.token,
+        ))
+        .await,
+    )
+    .await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    assert_eq!(body["code"], "admin_required");
+}
+
+#[actix_web::test]
+async fn malformed_json_bodies_are_problem_documents() {
+    let app = spawn_app().await;
+    let user = app.user().await;
+
+    let (status, body) = problem(
+        app.call(
+            authed(test::TestRequest::post().uri("/api/posts"), &user.token)
+                .insert_header(header::ContentType::json())
+                .set_payload("{\"title\": "),
+        )
+        .await,
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["code"], "invalid_body");
+
+    let (status, body) = problem(
+        app.call(
+            authed(test::TestRequest::post().uri("/api/posts"), &user.token)
+                .insert_header(header::ContentType::plaintext())
+                .set_payload("hello"),
+        )
+        .await,
+    )
+    .await;
+    assert_eq!(status, StatusCode::UNSUPPORTED_MEDIA_TYPE);
+    assert_eq!(body["code"], "unsupported_media_type");
+}
+
+#[actix_web::test]
+async fn domain_errors_carry_their_code() {
+    let app = spawn_app().await;
+    let admin = app.admin().await;
+    let user = app.user().await;
+
+    let (status, body) = problem(
+        app.call(
+            authed(test::TestRequest::post().uri("/api/users"), &admin.token).set_json(json!({
+                "username": "someone",
+                "email": format!("{}@example.com", user.username),
+                "password": PASSWORD,
+            })),
+        )
+        .await,
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["code"], "email_taken");
+}
+
+#[actix_web::test]
+async fn unknown_routes_are_problem_documents() {
+    let app = spawn_app().await;
+
+    let (status, body) =
+        problem(app.call(test::TestRequest::get().uri("/api/nothing")).await).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    assert_eq!(body["code"], "not_found");
+    assert_eq!(body["instance"], "/api/nothing");
+}
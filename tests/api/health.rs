@@ -0,0 +1,96 @@
+use actix_web::{http::StatusCode, test};
+use api_back_trio::{
+    config::StorageSettings,
+    infrastructure::{
+        db::{Database, connect_db},
+        health::check_readiness,
+        storage::build_blob_store,
+    },
+};
+use uuid::Uuid;
+
+use crate::support::spawn_app;
+
+#[actix_web::test]
+async fn probes_need_no_token() {
+    let app = spawn_app().await;
+
+    let (status, body) = app.json(test::TestRequest::get().uri("/health/live")).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["status"], "ok");
+
+    let (status, body) = app
+        .json(test::TestRequest::get().uri("/health/ready"))
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["ready"], true);
+    assert_eq!(body["checks"]["database"], "ok");
+    assert_eq!(body["checks"]["migrations"], "ok");
+    assert_eq!(body["checks"]["storage"], "ok");
+}
+
+#[actix_web::test]
+async fn version_reports_the_build() {
+    let app = spawn_app().await;
+
+    let (status, body) = app.json(test::TestRequest::get().uri("/version")).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["version"], env!("CARGO_PKG_VERSION"));
+    assert!(body["gitHash"].as_str().is_some_and(|h| !h.is_empty()));
+    assert!(body["buildTime"].is_string());
+}
+
+#[actix_web::test]
+async fn readiness_reads_the_schema_without_migrating_it() {
+    let db = connect_db("sqlite::memory:").await.unwrap();
+    let uploads = std::env::temp_dir().join(format!("blog-api-test-{}", Uuid::new_v4()));
+    let store = build_blob_store(
+        &StorageSettings {
+            local_path: uploads.to_string_lossy().into_owned(),
+            ..StorageSettings::default()
+        },
+        "test-secret",
+    )
+    .unwrap();
+
+    let readiness = check_readiness(&db, store.as_ref()).await;
+    assert!(!readiness.ready);
+    assert_eq!(readiness.checks["migrations"], "pending");
+
+    let Database::Sqlite(pool) = &db else {
+        unreachable!()
+    };
+    let tables: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM sqlite_master WHERE type = 'table'")
+        .fetch_one(pool)
+        .await
+        .unwrap();
+    assert_eq!(tables, 0);
+
+    db.migrate().await.unwrap();
+    let readiness = check_readiness(&db, store.as_ref()).await;
+    assert!(readiness.ready);
+    assert_eq!(readiness.checks["migrations"], "ok");
+}
+
+#[actix_web::test]
+async fn failed_checks_do_not_leak_the_error() {
+    let db = connect_db("sqlite::memory:").await.unwrap();
+    db.migrate().await.unwrap();
+    // A file where the uploads directory should be.
+    let uploads = std::env::temp_dir().join(format!("blog-api-test-{}", Uuid::new_v4()));
+    std::fs::write(&uploads, b"").unwrap();
+    let store = build_blob_store(
+        &StorageSettings {
+            local_path: uploads.to_string_lossy().into_owned(),
+            ..StorageSettings::default()
+        },
+        "test-secret",
+    )
+    .unwrap();
+
+    let readiness = check_readiness(&db, store.as_ref()).await;
+    assert!(!readiness.ready);
+    assert_eq!(readiness.checks["database"], "ok");
+    assert_eq!(readiness.checks["storage"], "unavailable");
+    std::fs::remove_file(uploads).unwrap();
+}
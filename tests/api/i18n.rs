@@ -0,0 +1,92 @@
+use actix_web::{
+    http::{StatusCode, header},
+    test,
+};
+use api_back_trio::interfaces::api::i18n::Locale;
+use serde_json::{Value, json};
+
+use crate::support::{authed, spawn_app, spawn_app_with};
+
+#[actix_web::test]
+async fn messages_follow_accept_language() {
+    let app = spawn_app().await;
+
+    let resp = app
+        .call(
+            test::TestRequest::get()
+                .uri("/api/posts")
+                .insert_header((header::ACCEPT_LANGUAGE, "fr-FR,fr;q=0.9,en;q=0.8")),
+        )
+        .await;
+    assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+    assert_eq!(resp.headers().get(header::CONTENT_LANGUAGE).unwrap(), "fr");
+    let body: Value = test::read_body_json(resp).await;
+    assert_eq!(body["code"], "missing_credentials");
+    assert_eq!(body["detail"], "L’en-tête Authorization est absent");
+
+    let (_, body) = app.json(test::TestRequest::get().uri("/api/posts")).await;
+    assert_eq!(body["detail"], "Authorization header is missing");
+}
+
+#[actix_web::test]
+async fn the_best_ranked_supported_language_wins() {
+    let app = spawn_app().await;
+
+    let (_, body) = app
+        .json(
+            test::TestRequest::get()
+                .uri("/api/nothing")
+                .insert_header((header::ACCEPT_LANGUAGE, "de, en;q=0.5, fr;q=0.8")),
+        )
+        .await;
+    assert_eq!(body["detail"], "Ressource introuvable");
+}
+
+#[actix_web::test]
+async fn field_errors_are_translated() {
+    let app = spawn_app().await;
+    let admin = app.admin().await;
+
+    let (status, body) = app
+        .json(
+            authed(test::TestRequest::post().uri("/api/users"), &admin.token)
+                .insert_header((header::ACCEPT_LANGUAGE, "fr"))
+                .set_json(json!({
+                    "username": "ab",
+                    "password": "Sh0rt!",
+                    "email": "newcomer@example.com",
+                })),
+        )
+        .await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+    assert_eq!(body["detail"], "Certains champs sont invalides");
+    assert_eq!(
+        body["errors"],
+        json!([
+            {
+                "field": "username",
+                "code": "length",
+                "message": "Doit contenir entre 3 et 50 caractères",
+            },
+            {
+                "field": "password",
+                "code": "password_too_short",
+                "message": "Le mot de passe doit contenir au moins 8 caractères",
+            },
+        ])
+    );
+}
+
+#[actix_web::test]
+async fn unsupported_languages_fall_back_to_the_configured_default() {
+    let app = spawn_app_with(|settings| settings.default_locale = Locale::Fr).await;
+
+    let (_, body) = app
+        .json(
+            test::TestRequest::get()
+                .uri("/api/posts")
+                .insert_header((header::ACCEPT_LANGUAGE, "de-DE")),
+        )
+        .await;
+    assert_eq!(body["detail"], "L’en-tête Authorization est absent");
+}
@@ -0,0 +1,230 @@
+use actix_http::Request;
+use actix_web::{
+    Error,
+    body::MessageBody,
+    dev::{Service, ServiceResponse},
+    http::StatusCode,
+    test,
+};
+use api_back_trio::domain::model::user::Role;
+use serde_json::{Value, json};
+
+use crate::support::{PASSWORD, TestApp, TestUser, authed, spawn_app};
+
+async fn impersonate<S, B>(
+    app: &TestApp<S>,
+    admin: &TestUser,
+    target: &TestUser,
+) -> (StatusCode, Value)
+where
+    S: Service<Request, Response = ServiceResponse<B>, Error = Error>,
+    B: MessageBody,
+{
+    app.json(authed(
+        test::TestRequest::post().uri(&format!("/api/users/{}/impersonate", target.id)),
+        &admin.token,
+    ))
+    .await
+}
+
+#[actix_web::test]
+async fn an_admin_sees_what_the_user_sees_until_the_session_ends() {
+    let app = spawn_app().await;
+    let admin = app.admin().await;
+    let user = app.user().await;
+
+    let (status, body) = impersonate(&app, &admin, &user).await;
+    assert_eq!(status, StatusCode::CREATED, "{}", body);
+    assert!(body["expiresAt"].is_string());
+    let token = body["token"].as_str().unwrap();
+
+    let (status, profile) = app
+        .json(authed(test::TestRequest::get().uri("/api/profile"), token))
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(profile["id"], user.id.to_string());
+
+    // The session carries the rights of the user, not those of the admin.
+    let (status, _) = app
+        .json(authed(test::TestRequest::get().uri("/api/users"), token))
+        .await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+
+    let (status, _) = app
+        .json(authed(
+            test::TestRequest::delete().uri("/api/impersonation"),
+            token,
+        ))
+        .await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+
+    let (status, body) = app
+        .json(authed(test::TestRequest::get().uri("/api/profile"), token))
+        .await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    assert_eq!(body["code"], "invalid_token");
+
+    let (status, log) = app
+        .json(authed(
+            test::TestRequest::get().uri("/api/audit"),
+            &admin.token,
+        ))
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    let actions: Vec<&str> = log
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|entry| entry["action"].as_str().unwrap())
+        .collect();
+    assert_eq!(actions, ["impersonation_ended", "impersonation_started"]);
+    for entry in log.as_array().unwrap() {
+        assert_eq!(entry["actorId"], admin.id.to_string());
+        assert_eq!(entry["subjectId"], user.id.to_string());
+        assert_eq!(entry["sessionId"], log[0]["sessionId"]);
+    }
+}
+
+#[actix_web::test]
+async fn an_impersonated_session_cannot_touch_the_credentials() {
+    let app = spawn_app().await;
+    let admin = app.admin().await;
+    let user = app.user().await;
+    let (_, body) = impersonate(&app, &admin, &user).await;
+    let token = body["token"].as_str().unwrap();
+
+    for change in [
+        json!({ "username": "taken_over" }),
+        json!({ "email": "taken.over@example.com" }),
+        json!({ "plain_password": "N3w$ecret!", "confirm_password": "N3w$ecret!" }),
+    ] {
+        let (status, body) = app
+            .json(authed(test::TestRequest::patch().uri("/api/profile"), token).set_json(change))
+            .await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+        assert_eq!(body["code"], "impersonation_forbidden");
+    }
+
+    let (status, body) = app
+        .json(
+            authed(test::TestRequest::post().uri("/api/profile/tokens"), token)
+                .set_json(json!({ "name": "backdoor", "scopes": ["posts:read"] })),
+        )
+        .await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    assert_eq!(body["code"], "impersonation_forbidden");
+
+    let (status, body) = app
+        .json(authed(
+            test::TestRequest::post().uri("/api/profile/erase"),
+            token,
+        ))
+        .await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    assert_eq!(body["code"], "impersonation_forbidden");
+    let untouched = app.repos.users.find_by_id(user.id).await.unwrap().unwrap();
+    assert!(untouched.erased_at.is_none());
+    assert_eq!(untouched.username, user.username);
+    let (status, _) = app.login(&user.username, PASSWORD).await;
+    assert_eq!(status, StatusCode::OK);
+
+    // Everything else goes through, as it would for the user.
+    let (status, profile) = app
+        .json(
+            authed(test::TestRequest::patch().uri("/api/profile"), token)
+                .set_json(json!({ "bio": "Set by support" })),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK, "{}", profile);
+    assert_eq!(profile["bio"], "Set by support");
+}
+
+#[actix_web::test]
+async fn only_admins_impersonate_and_never_other_admins() {
+    let app = spawn_app().await;
+    let admin = app.admin().await;
+    let user = app.user().await;
+    let other_admin = app.create_user("root", Role::Admin).await;
+
+    let (status, _) = impersonate(&app, &user, &admin).await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+
+    for target in [&other_admin, &admin] {
+        let (status, body) = impersonate(&app, &admin, target).await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+        assert_eq!(body["code"], "impersonation_forbidden");
+    }
+
+    let (status, _) = app
+        .json(authed(
+            test::TestRequest::post()
+                .uri(&format!("/api/users/{}/impersonate", uuid::Uuid::new_v4())),
+            &admin.token,
+        ))
+        .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    // A plain session has no impersonation to end.
+    let (status, _) = app
+        .json(authed(
+            test::TestRequest::delete().uri("/api/impersonation"),
+            &user.token,
+        ))
+        .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    let (status, _) = app
+        .json(authed(
+            test::TestRequest::get().uri("/api/audit"),
+            &user.token,
+        ))
+        .await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+}
+
+#[actix_web::test]
+async fn access_tokens_cannot_start_an_impersonation() {
+    let app = spawn_app().await;
+    let admin = app.admin().await;
+    let user = app.user().await;
+
+    let (status, created) = app.create_access_token(&admin, &["admin"]).await;
+    assert_eq!(status, StatusCode::CREATED, "{}", created);
+    let pat = TestUser {
+        id: admin.id,
+        username: admin.username.clone(),
+        token: created["token"].as_str().unwrap().to_string(),
+    };
+
+    // The admin scope covers the rest of /api/users.
+    let (status, _) = app
+        .json(authed(
+            test::TestRequest::get().uri("/api/users"),
+            &pat.token,
+        ))
+        .await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (status, body) = impersonate(&app, &pat, &user).await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    assert_eq!(body["code"], "insufficient_scope");
+}
+
+#[actix_web::test]
+async fn demoting_the_admin_ends_the_session() {
+    let app = spawn_app().await;
+    let admin = app.admin().await;
+    let user = app.user().await;
+    let (_, body) = impersonate(&app, &admin, &user).await;
+    let token = body["token"].as_str().unwrap();
+
+    let mut demoted = app.repos.users.find_by_id(admin.id).await.unwrap().unwrap();
+    demoted.role = Role::User;
+    app.repos.users.update(demoted).await.unwrap();
+
+    let (status, body) = app
+        .json(authed(test::TestRequest::get().uri("/api/profile"), token))
+        .await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    assert_eq!(body["code"], "invalid_token");
+}
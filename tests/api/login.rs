@@ -0,0 +1,139 @@
+use actix_web::{http::StatusCode, test};
+use api_back_trio::{
+    config::Argon2Settings,
+    domain::{model::user::Role, repository::UserRepository},
+    infrastructure::auth::password::PasswordHashing,
+};
+use chrono::Utc;
+
+use crate::support::{PASSWORD, authed, spawn_app, spawn_app_with};
+
+#[actix_web::test]
+async fn login_returns_a_usable_token() {
+    let app = spawn_app().await;
+    let user = app.create_user("alice", Role::User).await;
+
+    let (status, body) = app.login("alice", PASSWORD).await;
+    assert_eq!(status, StatusCode::OK);
+    let token = body["token"].as_str().unwrap();
+
+    let (status, profile) = app
+        .json(authed(test::TestRequest::get().uri("/api/profile"), token))
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(profile["id"], user.id.to_string());
+}
+
+#[actix_web::test]
+async fn login_rejects_a_wrong_password() {
+    let app = spawn_app().await;
+    app.create_user("alice", Role::User).await;
+
+    let (status, body) = app.login("alice", "Wr0ng!password").await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    assert_eq!(body["code"], "invalid_credentials");
+}
+
+#[actix_web::test]
+async fn login_rejects_an_unknown_user() {
+    let app = spawn_app().await;
+
+    let (status, _) = app.login("nobody", PASSWORD).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+}
+
+#[actix_web::test]
+async fn login_requires_username_and_password() {
+    let app = spawn_app().await;
+
+    let (status, _) = app
+        .json(
+            test::TestRequest::post()
+                .uri("/api/login")
+                .set_json(serde_json::json!({ "username": "alice" })),
+        )
+        .await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+}
+
+#[actix_web::test]
+async fn revoked_tokens_are_refused() {
+    let app = spawn_app().await;
+    let user = app.user().await;
+    let admin = app.admin().await;
+
+    let mut row = app.repos.users.find_by_id(user.id).await.unwrap().unwrap();
+    row.tokens_valid_after = Some(Utc::now());
+    app.repos.users.update(row).await.unwrap();
+
+    let (status, body) = app
+        .json(authed(
+            test::TestRequest::get().uri("/api/posts"),
+            &user.token,
+        ))
+        .await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    assert_eq!(body["code"], "invalid_token");
+
+    let (status, _) = app
+        .json(authed(
+            test::TestRequest::get().uri("/api/profile"),
+            &user.token,
+        ))
+        .await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+
+    let (status, _) = app
+        .json(authed(
+            test::TestRequest::get().uri("/api/posts"),
+            &admin.token,
+        ))
+        .await;
+    assert_eq!(status, StatusCode::OK);
+}
+
+#[actix_web::test]
+async fn tokens_of_deleted_users_are_refused() {
+    let app = spawn_app().await;
+    let admin = app.admin().await;
+    app.repos.users.delete(admin.id).await.unwrap();
+
+    let (status, _) = app
+        .json(authed(
+            test::TestRequest::get().uri("/api/users"),
+            &admin.token,
+        ))
+        .await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+}
+
+#[actix_web::test]
+async fn outdated_hashes_are_upgraded_on_login() {
+    let app = spawn_app_with(|settings| settings.argon2.pepper = Some("pepper".to_string())).await;
+    let legacy = PasswordHashing::new(&Argon2Settings {
+        memory_kib: 1024,
+        iterations: 1,
+        ..Argon2Settings::default()
+    })
+    .unwrap();
+    let user = app
+        .create_user_with_hash("alice", Role::User, legacy.hash(PASSWORD).unwrap())
+        .await;
+
+    // `create_user_with_hash` has logged in once already.
+    let hash = app
+        .repos
+        .users
+        .find_by_id(user.id)
+        .await
+        .unwrap()
+        .unwrap()
+        .password_hash;
+    assert!(!app.hashing.needs_rehash(&hash), "{}", hash);
+    assert!(hash.contains("m=19456,t=2,p=1"), "{}", hash);
+
+    let (status, _) = app.login("alice", PASSWORD).await;
+    assert_eq!(status, StatusCode::OK);
+    let (status, _) = app.login("alice", "Wr0ng!password").await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+}
@@ -0,0 +1,20 @@
+//! End-to-end tests of the HTTP API, see `support` for the harness.
+
+mod access_tokens;
+mod errors;
+mod health;
+mod i18n;
+mod impersonation;
+mod login;
+mod metrics;
+mod mock_idp;
+mod oidc;
+mod password_policy;
+mod posts;
+mod request_id;
+mod sessions;
+mod setup;
+mod support;
+mod tracing;
+mod users;
+mod validation;
@@ -0,0 +1,62 @@
+use actix_web::{http::StatusCode, test};
+use api_back_trio::domain::model::user::Role;
+
+use crate::support::{METRICS_TOKEN, PASSWORD, authed, spawn_app};
+
+#[actix_web::test]
+async fn metrics_require_the_metrics_token() {
+    let app = spawn_app().await;
+    let user = app.user().await;
+
+    let resp = app.call(test::TestRequest::get().uri("/metrics")).await;
+    assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+
+    let resp = app
+        .call(authed(
+            test::TestRequest::get().uri("/metrics"),
+            &user.token,
+        ))
+        .await;
+    assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+
+    let resp = app
+        .call(authed(
+            test::TestRequest::get().uri("/metrics"),
+            METRICS_TOKEN,
+        ))
+        .await;
+    assert_eq!(resp.status(), StatusCode::OK);
+}
+
+#[actix_web::test]
+async fn metrics_count_requests_logins_and_posts() {
+    let app = spawn_app().await;
+    // create_user logs in once
+    let alice = app.create_user("alice", Role::User).await;
+    app.login("alice", "Wr0ng!password").await;
+    app.login("alice", PASSWORD).await;
+    app.create_post(&alice, "Hello world").await;
+    app.call(authed(
+        test::TestRequest::get().uri("/api/posts/not-a-uuid"),
+        &alice.token,
+    ))
+    .await;
+
+    let resp = app
+        .call(authed(
+            test::TestRequest::get().uri("/metrics"),
+            METRICS_TOKEN,
+        ))
+        .await;
+    assert_eq!(resp.status(), StatusCode::OK);
+    let text = String::from_utf8(test::read_body(resp).await.to_vec()).unwrap();
+
+    assert!(text.contains(r#"blog_api_logins_total{outcome="success"} 2"#));
+    assert!(text.contains(r#"blog_api_logins_total{outcome="failure"} 1"#));
+    assert!(text.contains("blog_api_posts_created_total 1"));
+    assert!(text.contains(
+        r#"blog_api_http_requests_total{method="GET",route="/api/posts/{id}",status="400"} 1"#
+    ));
+    assert!(text.contains("blog_api_http_request_duration_seconds_bucket"));
+    assert!(text.contains("blog_api_db_pool_connections"));
+}
@@ -0,0 +1,199 @@
+//! A minimal OpenID Connect provider listening on a local port: discovery,
+//! JWKS and a token endpoint checking PKCE. The authorization endpoint is
+//! played by `MockIdp::authorize`, which stands for the user signing in.
+
+use std::{
+    collections::HashMap,
+    net::TcpListener,
+    sync::{Arc, Mutex},
+};
+
+use actix_web::{App, HttpRequest, HttpResponse, HttpServer, http::header, web};
+use api_back_trio::config::OidcSettings;
+use base64::{
+    Engine,
+    engine::general_purpose::{STANDARD, URL_SAFE_NO_PAD},
+};
+use chrono::Utc;
+use jsonwebtoken::{Algorithm, EncodingKey, Header, encode};
+use serde::Deserialize;
+use serde_json::{Value, json};
+use sha2::{Digest, Sha256};
+use url::Url;
+
+pub const CLIENT_ID: &str = "blog-api";
+pub const CLIENT_SECRET: &str = "client-secret";
+pub const REDIRECT_URI: &str = "https://blog.example/api/oidc/callback";
+
+const KEY_ID: &str = "test-key";
+/// Test-only key pair; `MODULUS` is the `n` of its public half.
+const SIGNING_KEY: &str = include_str!("fixtures/oidc_signing_key.pem");
+const MODULUS: &str = "qvwL1Ox24XerkeHSALwBN_7f4axx8WHPpZrH7_Usx--bD8CHXfuVnhfAhdGxeO2bXYqpIBY1LNiQkVUoKlFlwxUOjAaciSsABVKYYXu28OxojmEVWxpWWDwwsK41iMvae-WlgFycq4uQb_bzgvMtJPD1CuCyh6ToYVYPhUtcJj1CAi7GMgulZra8-0CPslcbDCCISiOjWccud1rZ2x9hzCHRX2Hp_WSyywNli8OdJeA3pIuH0Qfsj_NQTY_z3JDQebHTQFh6ixodHdzlgg4K9lfXxufUt4t2es5W7FuaIYus2kVP9FlGAnA9M8IrW9k3XEePkUNgD_A4exq71pkmJw";
+
+/// An issued authorization code, redeemable once.
+struct Grant {
+    claims: Value,
+    code_challenge: String,
+    redirect_uri: String,
+}
+
+#[derive(Clone)]
+struct Provider {
+    issuer: String,
+    grants: Arc<Mutex<HashMap<String, Grant>>>,
+}
+
+pub struct MockIdp {
+    provider: Provider,
+}
+
+impl MockIdp {
+    pub async fn start() -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").expect("local port");
+        let port = listener.local_addr().expect("bound address").port();
+        let provider = Provider {
+            issuer: format!("http://127.0.0.1:{}", port),
+            grants: Arc::default(),
+        };
+
+        let data = provider.clone();
+        let server = HttpServer::new(move || {
+            App::new()
+                .app_data(web::Data::new(data.clone()))
+                .route(
+                    "/.well-known/openid-configuration",
+                    web::get().to(discovery),
+                )
+                .route("/jwks", web::get().to(jwks))
+                .route("/token", web::post().to(token))
+        })
+        .workers(1)
+        .listen(listener)
+        .expect("mock IdP listener")
+        .run();
+        actix_web::rt::spawn(server);
+
+        Self { provider }
+    }
+
+    pub fn settings(&self) -> OidcSettings {
+        OidcSettings {
+            issuer: self.provider.issuer.clone(),
+            client_id: CLIENT_ID.to_string(),
+            client_secret: Some(CLIENT_SECRET.to_string()),
+            redirect_uri: REDIRECT_URI.to_string(),
+            scopes: "openid email profile".to_string(),
+            auto_provision: true,
+        }
+    }
+
+    /// Checks the authorization request the API redirected to, then returns
+    /// the `code` and `state` the browser would bring back to the callback.
+    /// `claims` go into the ID token, over the standard ones.
+    pub fn authorize(&self, location: &str, claims: Value) -> (String, String) {
+        let url = Url::parse(location).expect("authorization URL");
+        assert!(url.as_str().starts_with(&self.provider.issuer));
+        let params: HashMap<String, String> = url.query_pairs().into_owned().collect();
+        assert_eq!(params["response_type"], "code");
+        assert_eq!(params["client_id"], CLIENT_ID);
+        assert_eq!(params["code_challenge_method"], "S256");
+        assert!(params["scope"].split(' ').any(|s| s == "openid"));
+
+        let now = Utc::now().timestamp();
+        let mut id_claims = json!({
+            "iss": self.provider.issuer,
+            "aud": CLIENT_ID,
+            "iat": now,
+            "exp": now + 300,
+            "nonce": params["nonce"],
+        });
+        for (key, value) in claims.as_object().expect("claims object") {
+            id_claims[key] = value.clone();
+        }
+
+        let code = URL_SAFE_NO_PAD.encode(uuid::Uuid::new_v4().as_bytes());
+        self.provider.grants.lock().unwrap().insert(
+            code.clone(),
+            Grant {
+                claims: id_claims,
+                code_challenge: params["code_challenge"].clone(),
+                redirect_uri: params["redirect_uri"].clone(),
+            },
+        );
+
+        (code, params["state"].clone())
+    }
+}
+
+async fn discovery(provider: web::Data<Provider>) -> HttpResponse {
+    HttpResponse::Ok().json(json!({
+        "issuer": provider.issuer,
+        "authorization_endpoint": format!("{}/authorize", provider.issuer),
+        "token_endpoint": format!("{}/token", provider.issuer),
+        "jwks_uri": format!("{}/jwks", provider.issuer),
+        "response_types_supported": ["code"],
+        "code_challenge_methods_supported": ["S256"],
+        "id_token_signing_alg_values_supported": ["RS256"],
+    }))
+}
+
+async fn jwks() -> HttpResponse {
+    HttpResponse::Ok().json(json!({
+        "keys": [{
+            "kty": "RSA",
+            "kid": KEY_ID,
+            "use": "sig",
+            "alg": "RS256",
+            "n": MODULUS,
+            "e": "AQAB",
+        }]
+    }))
+}
+
+#[derive(Deserialize)]
+struct TokenRequest {
+    grant_type: String,
+    code: String,
+    redirect_uri: String,
+    code_verifier: String,
+}
+
+async fn token(
+    req: HttpRequest,
+    form: web::Form<TokenRequest>,
+    provider: web::Data<Provider>,
+) -> HttpResponse {
+    let invalid = |error: &str| HttpResponse::BadRequest().json(json!({ "error": error }));
+
+    let credentials = req
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|h| h.to_str().ok())
+        .and_then(|h| h.strip_prefix("Basic "))
+        .and_then(|b| STANDARD.decode(b).ok());
+    if credentials.as_deref() != Some(format!("{}:{}", CLIENT_ID, CLIENT_SECRET).as_bytes()) {
+        return invalid("invalid_client");
+    }
+    if form.grant_type != "authorization_code" {
+        return invalid("unsupported_grant_type");
+    }
+    let Some(grant) = provider.grants.lock().unwrap().remove(&form.code) else {
+        return invalid("invalid_grant");
+    };
+    let challenge = URL_SAFE_NO_PAD.encode(Sha256::digest(form.code_verifier.as_bytes()));
+    if grant.code_challenge != challenge || grant.redirect_uri != form.redirect_uri {
+        return invalid("invalid_grant");
+    }
+
+    let mut header = Header::new(Algorithm::RS256);
+    header.kid = Some(KEY_ID.to_string());
+    let key = EncodingKey::from_rsa_pem(SIGNING_KEY.as_bytes()).expect("test signing key");
+    let id_token = encode(&header, &grant.claims, &key).expect("signed ID token");
+
+    HttpResponse::Ok().json(json!({
+        "access_token": "opaque",
+        "token_type": "Bearer",
+        "expires_in": 300,
+        "id_token": id_token,
+    }))
+}
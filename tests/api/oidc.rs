@@ -0,0 +1,303 @@
+use actix_http::Request;
+use actix_web::{
+    Error,
+    body::MessageBody,
+    cookie::Cookie,
+    dev::{Service, ServiceResponse},
+    http::{StatusCode, header},
+    test,
+};
+use api_back_trio::{config::OidcSettings, domain::model::user::Role};
+use jsonwebtoken::{DecodingKey, Validation, decode};
+use serde_json::{Value, json};
+
+use crate::{
+    mock_idp::MockIdp,
+    support::{METRICS_TOKEN, TestApp, authed, spawn_app, spawn_app_with},
+};
+
+async fn spawn_with_idp(
+    configure: impl FnOnce(&mut OidcSettings),
+) -> (
+    MockIdp,
+    TestApp<impl Service<Request, Response = ServiceResponse<impl MessageBody>, Error = Error>>,
+) {
+    let idp = MockIdp::start().await;
+    let mut oidc = idp.settings();
+    configure(&mut oidc);
+    let app = spawn_app_with(|s| s.oidc = Some(oidc)).await;
+
+    (idp, app)
+}
+
+/// Starts a sign-in, returning the provider URL and the cookie the API set.
+async fn start<S, B>(app: &TestApp<S>) -> (String, Cookie<'static>)
+where
+    S: Service<Request, Response = ServiceResponse<B>, Error = Error>,
+    B: MessageBody,
+{
+    let resp = app
+        .call(test::TestRequest::get().uri("/api/oidc/login"))
+        .await;
+    assert_eq!(resp.status(), StatusCode::FOUND);
+
+    let location = resp
+        .headers()
+        .get(header::LOCATION)
+        .unwrap()
+        .to_str()
+        .unwrap()
+        .to_string();
+    let cookie = resp
+        .response()
+        .cookies()
+        .find(|c| c.name() == "blog_oidc")
+        .expect("pending sign-in cookie")
+        .into_owned();
+    assert_eq!(cookie.http_only(), Some(true));
+
+    (location, cookie)
+}
+
+async fn sign_in<S, B>(app: &TestApp<S>, idp: &MockIdp, claims: Value) -> (StatusCode, Value)
+where
+    S: Service<Request, Response = ServiceResponse<B>, Error = Error>,
+    B: MessageBody,
+{
+    let (location, cookie) = start(app).await;
+    let (code, state) = idp.authorize(&location, claims);
+
+    app.json(
+        test::TestRequest::get()
+            .uri(&format!("/api/oidc/callback?code={}&state={}", code, state))
+            .cookie(cookie),
+    )
+    .await
+}
+
+#[actix_web::test]
+async fn first_sign_in_provisions_an_account() {
+    let (idp, app) = spawn_with_idp(|_| {}).await;
+    // Taken, so the new account gets another username.
+    app.create_user("carol", Role::User).await;
+
+    let claims = json!({
+        "sub": "carol-sso",
+        "email": "c.smith@corp.example",
+        "email_verified": true,
+        "preferred_username": "carol",
+        "given_name": "Carol",
+        "family_name": "Smith",
+    });
+    let (status, body) = sign_in(&app, &idp, claims).await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+
+    let token = body["token"].as_str().unwrap();
+    let (status, profile) = app
+        .json(authed(test::TestRequest::get().uri("/api/profile"), token))
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(profile["username"], "carol2");
+    assert_eq!(profile["email"], "c.smith@corp.example");
+
+    // The identity is linked: a new address at the provider changes nothing.
+    let claims = json!({ "sub": "carol-sso", "email": "carol@corp.example" });
+    let (status, body) = sign_in(&app, &idp, claims).await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    let (_, again) = app
+        .json(authed(
+            test::TestRequest::get().uri("/api/profile"),
+            body["token"].as_str().unwrap(),
+        ))
+        .await;
+    assert_eq!(again["id"], profile["id"]);
+}
+
+#[actix_web::test]
+async fn provisioned_usernames_follow_the_username_rules() {
+    let (idp, app) = spawn_with_idp(|_| {}).await;
+    let long = format!("Jean Dupont <{}>", "x".repeat(60));
+
+    for (n, wanted) in [long.as_str(), long.as_str(), "é"].into_iter().enumerate() {
+        let claims = json!({
+            "sub": format!("sso-{}", n),
+            "email": format!("user{}@corp.example", n),
+            "email_verified": true,
+            "preferred_username": wanted,
+        });
+        let (status, body) = sign_in(&app, &idp, claims).await;
+        assert_eq!(status, StatusCode::OK, "{}", body);
+    }
+
+    let mut names: Vec<String> = app
+        .repos
+        .users
+        .list()
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|user| user.username)
+        .collect();
+    names.sort();
+    let first = format!("Jean_Dupont__{}", "x".repeat(37));
+    let second = format!("Jean_Dupont__{}2", "x".repeat(36));
+    assert_eq!(names, [second, first, "é__".to_string()]);
+
+    // Each account created this way counts as a registration.
+    let resp = app
+        .call(authed(
+            test::TestRequest::get().uri("/metrics"),
+            METRICS_TOKEN,
+        ))
+        .await;
+    let text = String::from_utf8(test::read_body(resp).await.to_vec()).unwrap();
+    assert!(
+        text.contains("blog_api_users_registered_total 3"),
+        "{}",
+        text
+    );
+}
+
+#[actix_web::test]
+async fn a_verified_email_links_the_existing_account() {
+    let (idp, app) = spawn_with_idp(|o| o.auto_provision = false).await;
+    let alice = app.create_user("alice", Role::User).await;
+
+    let claims =
+        json!({ "sub": "alice-sso", "email": "ALICE@example.com", "email_verified": true });
+    let (status, body) = sign_in(&app, &idp, claims).await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+
+    let (_, profile) = app
+        .json(authed(
+            test::TestRequest::get().uri("/api/profile"),
+            body["token"].as_str().unwrap(),
+        ))
+        .await;
+    assert_eq!(profile["id"], alice.id.to_string());
+    let linked = app.repos.identities.list_by_user(alice.id).await.unwrap();
+    assert_eq!(linked.len(), 1);
+    assert_eq!(linked[0].subject, "alice-sso");
+}
+
+#[actix_web::test]
+async fn unverified_or_unknown_identities_are_refused() {
+    let (idp, app) = spawn_with_idp(|o| o.auto_provision = false).await;
+    app.create_user("alice", Role::User).await;
+
+    let claims = json!({ "sub": "mallory", "email": "alice@example.com", "email_verified": false });
+    let (status, body) = sign_in(&app, &idp, claims).await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    assert_eq!(body["code"], "email_not_verified");
+
+    let claims = json!({ "sub": "dave", "email": "dave@example.com", "email_verified": true });
+    let (status, body) = sign_in(&app, &idp, claims).await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    assert_eq!(body["code"], "unknown_account");
+}
+
+#[actix_web::test]
+async fn the_callback_checks_the_state_and_the_id_token() {
+    let (idp, app) = spawn_with_idp(|_| {}).await;
+    let claims = json!({ "sub": "erin", "email": "erin@example.com", "email_verified": true });
+
+    let (location, cookie) = start(&app).await;
+    let (code, _) = idp.authorize(&location, claims.clone());
+    let (status, body) = app
+        .json(
+            test::TestRequest::get()
+                .uri(&format!("/api/oidc/callback?code={}&state=forged", code))
+                .cookie(cookie),
+        )
+        .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["code"], "oidc_state_mismatch");
+
+    let (location, _) = start(&app).await;
+    let (code, state) = idp.authorize(&location, claims.clone());
+    let (status, _) = app
+        .json(
+            test::TestRequest::get()
+                .uri(&format!("/api/oidc/callback?code={}&state={}", code, state)),
+        )
+        .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    for forged in [
+        json!({ "aud": "another-client" }),
+        json!({ "nonce": "replayed" }),
+    ] {
+        let mut forged_claims = claims.clone();
+        forged_claims
+            .as_object_mut()
+            .unwrap()
+            .extend(forged.as_object().unwrap().clone());
+        let (status, body) = sign_in(&app, &idp, forged_claims).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+        assert_eq!(body["code"], "invalid_id_token");
+    }
+}
+
+#[actix_web::test]
+async fn the_pending_login_cookie_is_no_access_token() {
+    let (_idp, app) = spawn_with_idp(|_| {}).await;
+    let alice = app.create_user("alice", Role::User).await;
+
+    // Signed with a key of its own, not with the JWT secret.
+    let (_, cookie) = start(&app).await;
+    let mut validation = Validation::default();
+    validation.required_spec_claims.clear();
+    assert!(
+        decode::<Value>(
+            cookie.value(),
+            &DecodingKey::from_secret(b"test-secret"),
+            &validation
+        )
+        .is_err()
+    );
+    let (status, body) = app
+        .json(authed(
+            test::TestRequest::get().uri("/api/profile"),
+            cookie.value(),
+        ))
+        .await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    assert_eq!(body["code"], "invalid_token");
+
+    // Nor is an access token accepted in place of the cookie.
+    let (status, body) = app
+        .json(
+            test::TestRequest::get()
+                .uri("/api/oidc/callback?code=any&state=any")
+                .cookie(Cookie::new("blog_oidc", alice.token)),
+        )
+        .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["code"], "oidc_state_mismatch");
+}
+
+#[actix_web::test]
+async fn in_cookie_mode_the_callback_keeps_the_jwt_in_the_cookie() {
+    let idp = MockIdp::start().await;
+    let app = spawn_app_with(|s| {
+        s.oidc = Some(idp.settings());
+        s.session.enabled = true;
+    })
+    .await;
+
+    let claims = json!({ "sub": "frank", "email": "frank@example.com", "email_verified": true });
+    let (status, body) = sign_in(&app, &idp, claims).await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    assert!(body.get("token").is_none());
+    assert!(body["csrfToken"].is_string());
+}
+
+#[actix_web::test]
+async fn oidc_routes_are_absent_when_not_configured() {
+    let app = spawn_app().await;
+
+    let (status, _) = app
+        .json(test::TestRequest::get().uri("/api/oidc/login"))
+        .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
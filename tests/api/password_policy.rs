@@ -0,0 +1,126 @@
+use actix_web::{http::StatusCode, test};
+use serde_json::{Value, json};
+use sha1::{Digest, Sha1};
+use uuid::Uuid;
+
+use crate::support::{PASSWORD, authed, spawn_app, spawn_app_with};
+
+/// Codes reported for the `field` member of a 422 problem document.
+fn codes(body: &Value, field: &str) -> Vec<String> {
+    body["errors"]
+        .as_array()
+        .expect("errors member")
+        .iter()
+        .filter(|e| e["field"] == field)
+        .map(|e| e["code"].as_str().unwrap().to_string())
+        .collect()
+}
+
+fn new_user(username: &str, password: &str) -> Value {
+    json!({
+        "username": username,
+        "password": password,
+        "email": format!("{}@example.com", username),
+    })
+}
+
+#[actix_web::test]
+async fn passwords_containing_the_username_or_email_are_rejected() {
+    let app = spawn_app().await;
+    let admin = app.admin().await;
+
+    let (status, body) = app
+        .json(
+            authed(test::TestRequest::post().uri("/api/users"), &admin.token)
+                .set_json(new_user("newcomer", "NewComer!42")),
+        )
+        .await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+    assert_eq!(
+        codes(&body, "password"),
+        vec!["password_contains_personal_info"]
+    );
+
+    // The account being updated is checked too, not only the fields sent.
+    let (status, body) = app
+        .json(
+            authed(test::TestRequest::patch().uri("/api/profile"), &admin.token).set_json(
+                json!({ "plain_password": "My-admin-1!", "confirm_password": "My-admin-1!" }),
+            ),
+        )
+        .await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+    assert_eq!(
+        codes(&body, "plain_password"),
+        vec!["password_contains_personal_info"]
+    );
+}
+
+#[actix_web::test]
+async fn the_policy_comes_from_the_settings() {
+    let app = spawn_app_with(|settings| {
+        settings.password.min_length = 12;
+        settings.password.require_special_char = false;
+        settings.password.min_score = 3;
+    })
+    .await;
+    let admin = app.admin().await;
+
+    let (status, body) = app
+        .json(
+            authed(test::TestRequest::post().uri("/api/users"), &admin.token)
+                .set_json(new_user("newcomer", PASSWORD)),
+        )
+        .await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+    assert_eq!(
+        codes(&body, "password"),
+        vec!["password_too_short", "password_too_weak"]
+    );
+
+    let (status, body) = app
+        .json(
+            authed(test::TestRequest::post().uri("/api/users"), &admin.token)
+                .set_json(new_user("newcomer", "Chang3sMeNow")),
+        )
+        .await;
+    assert_eq!(status, StatusCode::CREATED, "{}", body);
+}
+
+#[actix_web::test]
+async fn passwords_of_the_breached_list_are_rejected() {
+    let leaked = "Bl0gger!2024";
+    let hash = hex::encode_upper(Sha1::digest(leaked));
+    let (prefix, suffix) = hash.split_at(5);
+
+    let dir = std::env::temp_dir().join(format!("blog-api-breached-{}", Uuid::new_v4()));
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(
+        dir.join(format!("{}.txt", prefix)),
+        format!("{}:3\r\n{}:42\r\n", "0".repeat(35), suffix),
+    )
+    .unwrap();
+
+    let path = dir.to_string_lossy().into_owned();
+    let app = spawn_app_with(|settings| settings.password.breached_path = Some(path)).await;
+    let admin = app.admin().await;
+
+    let (status, body) = app
+        .json(
+            authed(test::TestRequest::post().uri("/api/users"), &admin.token)
+                .set_json(new_user("newcomer", leaked)),
+        )
+        .await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+    assert_eq!(codes(&body, "password"), vec!["password_breached"]);
+
+    let (status, _) = app
+        .json(
+            authed(test::TestRequest::post().uri("/api/users"), &admin.token)
+                .set_json(new_user("newcomer", PASSWORD)),
+        )
+        .await;
+    assert_eq!(status, StatusCode::CREATED);
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
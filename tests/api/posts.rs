@@ -0,0 +1,368 @@
+use actix_web::{http::StatusCode, test};
+use api_back_trio::{
+    domain::model::{media::media_storage_key, user::Role},
+    infrastructure::storage::UrlSigner,
+};
+use serde_json::json;
+use uuid::Uuid;
+
+use crate::support::{authed, spawn_app};
+
+#[actix_web::test]
+async fn post_routes_require_a_valid_token() {
+    let app = spawn_app().await;
+    let id = Uuid::new_v4();
+
+    let requests = [
+        test::TestRequest::get().uri("/api/posts"),
+        test::TestRequest::post().uri("/api/posts"),
+        test::TestRequest::get().uri(&format!("/api/posts/{}", id)),
+        test::TestRequest::patch().uri(&format!("/api/posts/{}", id)),
+        test::TestRequest::delete().uri(&format!("/api/posts/{}", id)),
+        test::TestRequest::get().uri(&format!("/api/posts/{}/media", id)),
+        test::TestRequest::put().uri(&format!("/api/posts/{}/media/{}", id, id)),
+        test::TestRequest::delete().uri(&format!("/api/posts/{}/media/{}", id, id)),
+        test::TestRequest::put().uri(&format!("/api/posts/{}/cover", id)),
+    ];
+
+    for req in requests {
+        let (status, body) = app.json(req).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+        assert_eq!(body["code"], "missing_credentials");
+    }
+
+    let (status, _) = app
+        .json(authed(
+            test::TestRequest::get().uri("/api/posts"),
+            "not-a-jwt",
+        ))
+        .await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+}
+
+#[actix_web::test]
+async fn create_and_read_posts() {
+    let app = spawn_app().await;
+    let user = app.user().await;
+
+    let created = app.create_post(&user, "Hello world").await;
+    assert_eq!(created["title"], "Hello world");
+    assert_eq!(created["user_id"], user.id.to_string());
+    let id = created["id"].as_str().unwrap();
+
+    let (status, list) = app
+        .json(authed(
+            test::TestRequest::get().uri("/api/posts"),
+            &user.token,
+        ))
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(list.as_array().unwrap().len(), 1);
+    assert_eq!(list[0]["author"]["username"], user.username);
+    assert!(list[0]["author"].get("email").is_none());
+
+    let (status, post) = app
+        .json(authed(
+            test::TestRequest::get().uri(&format!("/api/posts/{}", id)),
+            &user.token,
+        ))
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(post["id"], id);
+    assert_eq!(post["author"]["id"], user.id.to_string());
+}
+
+#[actix_web::test]
+async fn create_post_validates_its_payload() {
+    let app = spawn_app().await;
+    let user = app.user().await;
+
+    let (status, _) = app
+        .json(
+            authed(test::TestRequest::post().uri("/api/posts"), &user.token).set_json(json!({
+                "title": "x",
+                "content": "Some content",
+                "published": false,
+                "user_id": user.id,
+            })),
+        )
+        .await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+}
+
+#[actix_web::test]
+async fn get_post_rejects_unknown_and_malformed_ids() {
+    let app = spawn_app().await;
+    let user = app.user().await;
+
+    let (status, _) = app
+        .json(authed(
+            test::TestRequest::get().uri(&format!("/api/posts/{}", Uuid::new_v4())),
+            &user.token,
+        ))
+        .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    let (status, _) = app
+        .json(authed(
+            test::TestRequest::get().uri("/api/posts/not-a-uuid"),
+            &user.token,
+        ))
+        .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
+
+#[actix_web::test]
+async fn update_post() {
+    let app = spawn_app().await;
+    let user = app.user().await;
+    let created = app.create_post(&user, "Draft").await;
+    let uri = format!("/api/posts/{}", created["id"].as_str().unwrap());
+
+    let (status, updated) = app
+        .json(
+            authed(test::TestRequest::patch().uri(&uri), &user.token)
+                .set_json(json!({ "title": "Final", "published": false })),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(updated["title"], "Final");
+    assert_eq!(updated["published"], false);
+    assert!(updated["updated_at"].is_string());
+
+    let (status, _) = app
+        .json(
+            authed(
+                test::TestRequest::patch().uri(&format!("/api/posts/{}", Uuid::new_v4())),
+                &user.token,
+            )
+            .set_json(json!({ "title": "Final" })),
+        )
+        .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+#[actix_web::test]
+async fn delete_post() {
+    let app = spawn_app().await;
+    let user = app.user().await;
+    let created = app.create_post(&user, "Doomed").await;
+    let uri = format!("/api/posts/{}", created["id"].as_str().unwrap());
+
+    let (status, _) = app
+        .json(authed(test::TestRequest::delete().uri(&uri), &user.token))
+        .await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+
+    let (status, _) = app
+        .json(authed(test::TestRequest::delete().uri(&uri), &user.token))
+        .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+#[actix_web::test]
+async fn attach_and_detach_media() {
+    let app = spawn_app().await;
+    let user = app.user().await;
+    let post = app.create_post(&user, "Gallery").await;
+    let media = app.upload_media(&user).await;
+    let post_id = post["id"].as_str().unwrap();
+    let media_uri = format!(
+        "/api/posts/{}/media/{}",
+        post_id,
+        media["id"].as_str().unwrap()
+    );
+
+    let (status, _) = app
+        .json(authed(
+            test::TestRequest::put().uri(&media_uri),
+            &user.token,
+        ))
+        .await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+
+    let (status, list) = app
+        .json(authed(
+            test::TestRequest::get().uri(&format!("/api/posts/{}/media", post_id)),
+            &user.token,
+        ))
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(list[0]["id"], media["id"]);
+
+    let (status, _) = app
+        .json(authed(
+            test::TestRequest::delete().uri(&media_uri),
+            &user.token,
+        ))
+        .await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+
+    let (status, _) = app
+        .json(authed(
+            test::TestRequest::delete().uri(&media_uri),
+            &user.token,
+        ))
+        .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+#[actix_web::test]
+async fn concurrent_uploads_and_deletions_keep_shared_files() {
+    let app = spawn_app().await;
+    let alice = app.create_user("alice", Role::User).await;
+    let bob = app.create_user("bob_the", Role::User).await;
+
+    // Same bytes each time: both libraries point to one blob.
+    for _ in 0..20 {
+        let bobs = app.upload_media(&bob).await;
+        let delete = app.json(authed(
+            test::TestRequest::delete()
+                .uri(&format!("/api/media/{}", bobs["id"].as_str().unwrap())),
+            &bob.token,
+        ));
+        let (alices, (status, _)) = futures_util::join!(app.upload_media(&alice), delete);
+        assert_eq!(status, StatusCode::NO_CONTENT);
+        assert!(app.stored(&media_storage_key(alices["sha256"].as_str().unwrap())));
+
+        let (status, _) = app
+            .json(authed(
+                test::TestRequest::delete()
+                    .uri(&format!("/api/media/{}", alices["id"].as_str().unwrap())),
+                &alice.token,
+            ))
+            .await;
+        assert_eq!(status, StatusCode::NO_CONTENT);
+    }
+}
+
+#[actix_web::test]
+async fn media_of_another_user_cannot_be_attached() {
+    let app = spawn_app().await;
+    let owner = app.user().await;
+    let other = app.create_user("other", Role::User).await;
+    let post = app.create_post(&other, "Not mine").await;
+    let media = app.upload_media(&owner).await;
+
+    let (status, _) = app
+        .json(authed(
+            test::TestRequest::put().uri(&format!(
+                "/api/posts/{}/media/{}",
+                post["id"].as_str().unwrap(),
+                media["id"].as_str().unwrap()
+            )),
+            &other.token,
+        ))
+        .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+#[actix_web::test]
+async fn only_the_author_changes_the_media_of_a_post() {
+    let app = spawn_app().await;
+    let author = app.user().await;
+    let other = app.create_user("other", Role::User).await;
+    let post = app.create_post(&author, "Mine").await;
+    let post_id = post["id"].as_str().unwrap();
+    let media = app.upload_media(&author).await;
+    let media_uri = format!(
+        "/api/posts/{}/media/{}",
+        post_id,
+        media["id"].as_str().unwrap()
+    );
+    let (status, _) = app
+        .json(authed(
+            test::TestRequest::put().uri(&media_uri),
+            &author.token,
+        ))
+        .await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+
+    let theirs = app.upload_media(&other).await;
+    let requests = [
+        test::TestRequest::put().uri(&format!(
+            "/api/posts/{}/media/{}",
+            post_id,
+            theirs["id"].as_str().unwrap()
+        )),
+        test::TestRequest::delete().uri(&media_uri),
+        test::TestRequest::put()
+            .uri(&format!("/api/posts/{}/cover", post_id))
+            .set_json(json!({ "media_id": theirs["id"] })),
+    ];
+    for req in requests {
+        let (status, body) = app.json(authed(req, &other.token)).await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+        assert_eq!(body["code"], "not_post_author");
+    }
+
+    let (_, list) = app
+        .json(authed(
+            test::TestRequest::get().uri(&format!("/api/posts/{}/media", post_id)),
+            &author.token,
+        ))
+        .await;
+    assert_eq!(list.as_array().unwrap().len(), 1);
+
+    // Administrators moderate any post.
+    let admin = app.admin().await;
+    let (status, _) = app
+        .json(authed(
+            test::TestRequest::delete().uri(&media_uri),
+            &admin.token,
+        ))
+        .await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+}
+
+#[actix_web::test]
+async fn set_and_clear_the_cover() {
+    let app = spawn_app().await;
+    let user = app.user().await;
+    let post = app.create_post(&user, "Covered").await;
+    let media = app.upload_media(&user).await;
+    let post_id = post["id"].as_str().unwrap();
+    let cover_uri = format!("/api/posts/{}/cover", post_id);
+
+    let (status, updated) = app
+        .json(
+            authed(test::TestRequest::put().uri(&cover_uri), &user.token)
+                .set_json(json!({ "media_id": media["id"] })),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(updated["cover_media_id"], media["id"]);
+
+    let (_, read) = app
+        .json(authed(
+            test::TestRequest::get().uri(&format!("/api/posts/{}", post_id)),
+            &user.token,
+        ))
+        .await;
+    let cover_url = read["cover_url"].as_str().unwrap();
+    assert!(cover_url.contains("signature="));
+    let resp = app.call(test::TestRequest::get().uri(cover_url)).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+
+    // Not signed with the JWT secret itself.
+    let (path, query) = cover_url.split_once('?').unwrap();
+    let key = path.trim_start_matches("/api/files/");
+    let params: Vec<(&str, &str)> = query
+        .split('&')
+        .filter_map(|pair| pair.split_once('='))
+        .collect();
+    let expires: i64 = params[0].1.parse().unwrap();
+    assert_ne!(
+        params[1].1,
+        UrlSigner::new(b"test-secret").sign(key, expires)
+    );
+
+    let (status, updated) = app
+        .json(
+            authed(test::TestRequest::put().uri(&cover_uri), &user.token)
+                .set_json(json!({ "media_id": null })),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(updated["cover_media_id"].is_null());
+}
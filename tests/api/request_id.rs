@@ -0,0 +1,60 @@
+use actix_web::{http::StatusCode, test};
+
+use crate::support::{authed, spawn_app};
+
+#[actix_web::test]
+async fn every_response_carries_a_request_id() {
+    let app = spawn_app().await;
+
+    let resp = app.call(test::TestRequest::get().uri("/health/live")).await;
+    let id = resp
+        .headers()
+        .get("x-request-id")
+        .unwrap()
+        .to_str()
+        .unwrap();
+    assert!(uuid::Uuid::parse_str(id).is_ok());
+}
+
+#[actix_web::test]
+async fn a_valid_incoming_request_id_is_kept() {
+    let app = spawn_app().await;
+
+    let resp = app
+        .call(
+            test::TestRequest::get()
+                .uri("/health/live")
+                .insert_header(("X-Request-Id", "edge-42.abc")),
+        )
+        .await;
+    assert_eq!(resp.headers().get("x-request-id").unwrap(), "edge-42.abc");
+
+    let resp = app
+        .call(
+            test::TestRequest::get()
+                .uri("/health/live")
+                .insert_header(("X-Request-Id", "not valid\"id")),
+        )
+        .await;
+    assert_ne!(resp.headers().get("x-request-id").unwrap(), "not valid\"id");
+}
+
+#[actix_web::test]
+async fn error_bodies_quote_the_request_id() {
+    let app = spawn_app().await;
+    let user = app.user().await;
+
+    let resp = app
+        .call(
+            authed(
+                test::TestRequest::get().uri("/api/posts/not-a-uuid"),
+                &user.token,
+            )
+            .insert_header(("X-Request-Id", "trace-me")),
+        )
+        .await;
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+    let body: serde_json::Value = test::read_body_json(resp).await;
+    assert_eq!(body["request_id"], "trace-me");
+    assert_eq!(body["code"], "invalid_id");
+}
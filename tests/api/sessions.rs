@@ -0,0 +1,142 @@
+use actix_web::{
+    cookie::{Cookie, SameSite},
+    http::{StatusCode, header},
+    test,
+};
+use api_back_trio::domain::model::user::Role;
+use serde_json::json;
+
+use crate::support::{PASSWORD, spawn_app, spawn_app_with};
+
+fn set_cookies(
+    resp: &actix_web::dev::ServiceResponse<impl actix_web::body::MessageBody>,
+) -> Vec<Cookie<'static>> {
+    resp.headers()
+        .get_all(header::SET_COOKIE)
+        .map(|v| Cookie::parse_encoded(v.to_str().unwrap().to_string()).unwrap())
+        .collect()
+}
+
+fn login_request() -> test::TestRequest {
+    test::TestRequest::post()
+        .uri("/api/login")
+        .set_json(json!({ "username": "alice", "password": PASSWORD }))
+}
+
+#[actix_web::test]
+async fn login_sets_the_session_cookies_when_enabled() {
+    let app = spawn_app_with(|s| s.session.enabled = true).await;
+    app.create_user("alice", Role::User).await;
+
+    let resp = app.call(login_request()).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+    let cookies = set_cookies(&resp);
+    let body: serde_json::Value = test::read_body_json(resp).await;
+
+    // The JWT stays out of reach of scripts.
+    assert!(body.get("token").is_none());
+    let session = cookies.iter().find(|c| c.name() == "blog_session").unwrap();
+    assert!(!session.value().is_empty());
+    assert_eq!(session.http_only(), Some(true));
+    assert_eq!(session.secure(), Some(true));
+    assert_eq!(session.same_site(), Some(SameSite::Strict));
+
+    let csrf = cookies.iter().find(|c| c.name() == "blog_csrf").unwrap();
+    assert_eq!(csrf.value(), body["csrfToken"]);
+    assert_ne!(csrf.http_only(), Some(true));
+}
+
+#[actix_web::test]
+async fn the_session_cookie_authenticates_requests() {
+    let app = spawn_app_with(|s| s.session.enabled = true).await;
+    let user = app.create_user("alice", Role::User).await;
+
+    let resp = app.call(login_request()).await;
+    let cookies = set_cookies(&resp);
+    let session = cookies.iter().find(|c| c.name() == "blog_session").unwrap();
+    let csrf = cookies.iter().find(|c| c.name() == "blog_csrf").unwrap();
+
+    let (status, profile) = app
+        .json(
+            test::TestRequest::get()
+                .uri("/api/profile")
+                .cookie(session.clone()),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(profile["id"], user.id.to_string());
+
+    let new_post = json!({
+        "title": "Hello",
+        "content": "Some content",
+        "published": true,
+        "user_id": user.id,
+    });
+    let (status, body) = app
+        .json(
+            test::TestRequest::post()
+                .uri("/api/posts")
+                .cookie(session.clone())
+                .cookie(csrf.clone())
+                .set_json(&new_post),
+        )
+        .await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    assert_eq!(body["code"], "csrf_failed");
+
+    let (status, _) = app
+        .json(
+            test::TestRequest::post()
+                .uri("/api/posts")
+                .cookie(session.clone())
+                .cookie(csrf.clone())
+                .insert_header(("X-CSRF-Token", "0".repeat(64)))
+                .set_json(&new_post),
+        )
+        .await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+
+    let (status, _) = app
+        .json(
+            test::TestRequest::post()
+                .uri("/api/posts")
+                .cookie(session.clone())
+                .cookie(csrf.clone())
+                .insert_header(("X-CSRF-Token", csrf.value()))
+                .set_json(&new_post),
+        )
+        .await;
+    assert_eq!(status, StatusCode::CREATED);
+}
+
+#[actix_web::test]
+async fn sessions_are_disabled_by_default() {
+    let app = spawn_app().await;
+    app.create_user("alice", Role::User).await;
+
+    let resp = app.call(login_request()).await;
+    assert!(set_cookies(&resp).is_empty());
+    let body: serde_json::Value = test::read_body_json(resp).await;
+    let token = body["token"].as_str().unwrap().to_string();
+
+    let (status, _) = app
+        .json(
+            test::TestRequest::get()
+                .uri("/api/profile")
+                .cookie(Cookie::new("blog_session", token)),
+        )
+        .await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+}
+
+#[actix_web::test]
+async fn logout_clears_the_session_cookies() {
+    let app = spawn_app_with(|s| s.session.enabled = true).await;
+
+    let resp = app.call(test::TestRequest::post().uri("/api/logout")).await;
+    assert_eq!(resp.status(), StatusCode::NO_CONTENT);
+
+    let cookies = set_cookies(&resp);
+    assert_eq!(cookies.len(), 2);
+    assert!(cookies.iter().all(|c| c.value().is_empty()));
+}
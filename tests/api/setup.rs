@@ -0,0 +1,110 @@
+use actix_web::{http::StatusCode, test};
+use api_back_trio::domain::{model::user::Role, repository::UserRepository};
+use serde_json::{Value, json};
+
+use crate::support::{PASSWORD, authed, spawn_app};
+
+fn admin(token: &str) -> Value {
+    json!({
+        "token": token,
+        "username": "root",
+        "email": "root@example.com",
+        "password": PASSWORD,
+    })
+}
+
+#[actix_web::test]
+async fn setup_creates_the_first_admin_once() {
+    let app = spawn_app().await;
+    let token = app.setup.issue().await;
+
+    let (status, created) = app
+        .json(
+            test::TestRequest::post()
+                .uri("/api/setup")
+                .set_json(admin(&token)),
+        )
+        .await;
+    assert_eq!(status, StatusCode::CREATED);
+    assert_eq!(created["role"], "Admin");
+
+    let (status, body) = app.login("root", PASSWORD).await;
+    assert_eq!(status, StatusCode::OK);
+    let (status, _) = app
+        .json(authed(
+            test::TestRequest::get().uri("/api/users"),
+            body["token"].as_str().unwrap(),
+        ))
+        .await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (status, _) = app
+        .json(
+            test::TestRequest::post()
+                .uri("/api/setup")
+                .set_json(admin(&token)),
+        )
+        .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+#[actix_web::test]
+async fn setup_requires_the_token() {
+    let app = spawn_app().await;
+
+    let (status, _) = app
+        .json(
+            test::TestRequest::post()
+                .uri("/api/setup")
+                .set_json(admin("guess")),
+        )
+        .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    app.setup.issue().await;
+    let (status, _) = app
+        .json(
+            test::TestRequest::post()
+                .uri("/api/setup")
+                .set_json(admin("guess")),
+        )
+        .await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+}
+
+#[actix_web::test]
+async fn setup_validates_the_admin() {
+    let app = spawn_app().await;
+    let token = app.setup.issue().await;
+
+    let mut weak = admin(&token);
+    weak["password"] = json!("weak");
+    let (status, _) = app
+        .json(test::TestRequest::post().uri("/api/setup").set_json(weak))
+        .await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+}
+
+#[actix_web::test]
+async fn setup_is_closed_once_users_exist() {
+    let app = spawn_app().await;
+    let token = app.setup.issue().await;
+    app.create_user("alice", Role::User).await;
+
+    let (status, _) = app
+        .json(
+            test::TestRequest::post()
+                .uri("/api/setup")
+                .set_json(admin(&token)),
+        )
+        .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    assert!(
+        app.repos
+            .users
+            .find_by_username("root")
+            .await
+            .unwrap()
+            .is_none()
+    );
+}
@@ -0,0 +1,312 @@
+//! Runs the real router, with the middleware stack of `main.rs`, over a fresh
+//! in-memory SQLite database and a temporary upload directory.
+
+use std::{io::Cursor, path::PathBuf};
+
+use actix_http::Request;
+use actix_web::{
+    Error,
+    body::MessageBody,
+    dev::{Service, ServiceResponse},
+    http::{StatusCode, header},
+    test,
+};
+use api_back_trio::{
+    build_app,
+    config::{
+        Argon2Settings, MetricsSettings, PasswordSettings, ServerSettings, SessionSettings,
+        Settings, StorageSettings, TracingSettings, UploadSettings,
+    },
+    domain::model::user::{Role, User},
+    infrastructure::{
+        auth::{password::PasswordHashing, setup::SetupToken},
+        db::{Repositories, init_db},
+        storage::build_blob_store,
+    },
+    interfaces::api::{i18n::Locale, state::AppState},
+};
+use chrono::Utc;
+use serde_json::{Value, json};
+use uuid::Uuid;
+
+/// Satisfies the default password requirements.
+pub const PASSWORD: &str = "Sup3r$ecret";
+
+pub const METRICS_TOKEN: &str = "metrics-token";
+
+pub struct TestUser {
+    pub id: Uuid,
+    pub username: String,
+    pub token: String,
+}
+
+pub struct TestApp<S> {
+    service: S,
+    pub repos: Repositories,
+    pub setup: SetupToken,
+    pub hashing: PasswordHashing,
+    uploads: PathBuf,
+}
+
+pub async fn spawn_app()
+-> TestApp<impl Service<Request, Response = ServiceResponse<impl MessageBody>, Error = Error>> {
+    spawn_app_with(|_| {}).await
+}
+
+/// Same as `spawn_app`, with settings adjusted by `configure` first.
+pub async fn spawn_app_with(
+    configure: impl FnOnce(&mut Settings),
+) -> TestApp<impl Service<Request, Response = ServiceResponse<impl MessageBody>, Error = Error>> {
+    let uploads = std::env::temp_dir().join(format!("blog-api-test-{}", Uuid::new_v4()));
+    let mut settings = Settings {
+        database_url: "sqlite::memory:".to_string(),
+        server: ServerSettings {
+            host: "127.0.0.1".to_string(),
+            port: 0,
+        },
+        tls: None,
+        jwt_secret: "test-secret".to_string(),
+        cors_origin: "^https://localhost$".to_string(),
+        storage: StorageSettings {
+            local_path: uploads.to_string_lossy().into_owned(),
+            ..StorageSettings::default()
+        },
+        uploads: UploadSettings::default(),
+        admin: None,
+        oidc: None,
+        metrics: MetricsSettings {
+            token: Some(METRICS_TOKEN.to_string()),
+            ..MetricsSettings::default()
+        },
+        tracing: TracingSettings::default(),
+        password: PasswordSettings::default(),
+        argon2: Argon2Settings::default(),
+        session: SessionSettings::default(),
+        default_locale: Locale::En,
+    };
+    configure(&mut settings);
+
+    let db = init_db(&settings.database_url)
+        .await
+        .expect("in-memory SQLite database");
+    let repos = db.repositories();
+    let store =
+        build_blob_store(&settings.storage, &settings.jwt_secret).expect("local blob store");
+    let hashing = PasswordHashing::new(&settings.argon2).expect("Argon2 settings");
+    let state = AppState::new(&db, store, hashing.clone(), settings);
+    let setup = state.setup.clone();
+
+    TestApp {
+        service: test::init_service(build_app(state)).await,
+        repos,
+        setup,
+        hashing,
+        uploads,
+    }
+}
+
+impl<S, B> TestApp<S>
+where
+    S: Service<Request, Response = ServiceResponse<B>, Error = Error>,
+    B: MessageBody,
+{
+    pub async fn call(&self, req: test::TestRequest) -> ServiceResponse<B> {
+        test::call_service(&self.service, req.to_request()).await
+    }
+
+    /// Sends the request and returns the status with the JSON body, `Null`
+    /// when the body is empty.
+    pub async fn json(&self, req: test::TestRequest) -> (StatusCode, Value) {
+        let resp = self.call(req).await;
+        let status = resp.status();
+        let body = test::read_body(resp).await;
+
+        let value = if body.is_empty() {
+            Value::Null
+        } else {
+            serde_json::from_slice(&body).expect("JSON body")
+        };
+
+        (status, value)
+    }
+
+    pub async fn login(&self, username: &str, password: &str) -> (StatusCode, Value) {
+        self.json(
+            test::TestRequest::post()
+                .uri("/api/login")
+                .set_json(json!({ "username": username, "password": password })),
+        )
+        .await
+    }
+
+    /// Inserts a user with the given role straight into the database, then
+    /// logs in through `/api/login`.
+    pub async fn create_user(&self, username: &str, role: Role) -> TestUser {
+        let hash = self.hashing.hash(PASSWORD).expect("hash");
+        self.create_user_with_hash(username, role, hash).await
+    }
+
+    /// Same as `create_user`, with a password hash computed by the caller.
+    pub async fn create_user_with_hash(
+        &self,
+        username: &str,
+        role: Role,
+        password_hash: String,
+    ) -> TestUser {
+        let user = self
+            .repos
+            .users
+            .create(User {
+                id: Uuid::new_v4(),
+                username: username.to_string(),
+                password_hash,
+                email: format!("{}@example.com", username),
+                first_name: None,
+                last_name: None,
+                display_name: None,
+                bio: None,
+                website: None,
+                avatar_key: None,
+                created_at: Utc::now(),
+                updated_at: None,
+                role,
+                erased_at: None,
+                tokens_valid_after: None,
+            })
+            .await
+            .expect("user creation");
+
+        let resp = self
+            .call(
+                test::TestRequest::post()
+                    .uri("/api/login")
+                    .set_json(json!({ "username": username, "password": PASSWORD })),
+            )
+            .await;
+        assert_eq!(resp.status(), StatusCode::OK, "login failed");
+        // In cookie mode the JWT is only in the session cookie.
+        let session = resp
+            .response()
+            .cookies()
+            .find(|c| c.name() == "blog_session")
+            .map(|c| c.value().to_string());
+        let body: Value = test::read_body_json(resp).await;
+        let token = match body["token"].as_str() {
+            Some(token) => token.to_string(),
+            None => session.expect("token or session cookie"),
+        };
+
+        TestUser {
+            id: user.id,
+            username: user.username,
+            token,
+        }
+    }
+
+    pub async fn user(&self) -> TestUser {
+        self.create_user("reader", Role::User).await
+    }
+
+    pub async fn admin(&self) -> TestUser {
+        self.create_user("admin", Role::Admin).await
+    }
+
+    pub async fn create_post(&self, author: &TestUser, title: &str) -> Value {
+        let (status, body) = self
+            .json(
+                authed(test::TestRequest::post().uri("/api/posts"), &author.token).set_json(
+                    json!({
+                        "title": title,
+                        "content": "Some content",
+                        "published": true,
+                        "user_id": author.id,
+                    }),
+                ),
+            )
+            .await;
+        assert_eq!(
+            status,
+            StatusCode::CREATED,
+            "post creation failed: {}",
+            body
+        );
+
+        body
+    }
+
+    /// Creates a personal access token of `owner` through `/api/profile/tokens`.
+    pub async fn create_access_token(
+        &self,
+        owner: &TestUser,
+        scopes: &[&str],
+    ) -> (StatusCode, Value) {
+        self.json(
+            authed(
+                test::TestRequest::post().uri("/api/profile/tokens"),
+                &owner.token,
+            )
+            .set_json(json!({ "name": "ci", "scopes": scopes, "expires_in_days": 7 })),
+        )
+        .await
+    }
+
+    /// Whether the blob store holds `key`.
+    pub fn stored(&self, key: &str) -> bool {
+        self.uploads.join(key).exists()
+    }
+
+    /// Uploads a PNG to the media library of `owner`.
+    pub async fn upload_media(&self, owner: &TestUser) -> Value {
+        let (content_type, body) = multipart("file", "image.png", "image/png", &png(32, 32));
+        let (status, body) = self
+            .json(
+                authed(test::TestRequest::post().uri("/api/media"), &owner.token)
+                    .insert_header((header::CONTENT_TYPE, content_type))
+                    .set_payload(body),
+            )
+            .await;
+        assert_eq!(status, StatusCode::CREATED, "media upload failed: {}", body);
+
+        body
+    }
+}
+
+impl<S> Drop for TestApp<S> {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.uploads);
+    }
+}
+
+pub fn authed(req: test::TestRequest, token: &str) -> test::TestRequest {
+    req.insert_header((header::AUTHORIZATION, format!("Bearer {}", token)))
+}
+
+pub fn png(width: u32, height: u32) -> Vec<u8> {
+    let image = image::RgbImage::from_pixel(width, height, image::Rgb([200, 80, 40]));
+    let mut bytes = Cursor::new(Vec::new());
+    image
+        .write_to(&mut bytes, image::ImageFormat::Png)
+        .expect("PNG encoding");
+
+    bytes.into_inner()
+}
+
+/// Builds a `multipart/form-data` body holding a single file part; returns the
+/// `Content-Type` header value and the body.
+pub fn multipart(
+    field: &str,
+    filename: &str,
+    content_type: &str,
+    bytes: &[u8],
+) -> (String, Vec<u8>) {
+    let boundary = format!("boundary-{}", Uuid::new_v4().simple());
+    let mut body = format!(
+        "--{}\r\nContent-Disposition: form-data; name=\"{}\"; filename=\"{}\"\r\nContent-Type: {}\r\n\r\n",
+        boundary, field, filename, content_type
+    )
+    .into_bytes();
+    body.extend_from_slice(bytes);
+    body.extend_from_slice(format!("\r\n--{}--\r\n", boundary).as_bytes());
+
+    (format!("multipart/form-data; boundary={}", boundary), body)
+}
@@ -0,0 +1,126 @@
+use std::{sync::OnceLock, time::Duration};
+
+use actix_web::test;
+use opentelemetry::{
+    Value, global,
+    trace::{SpanId, TraceId, TracerProvider},
+};
+use opentelemetry_sdk::{
+    propagation::TraceContextPropagator,
+    trace::{InMemorySpanExporter, SdkTracerProvider, SpanData},
+};
+use tracing_subscriber::layer::SubscriberExt;
+use uuid::Uuid;
+
+use crate::support::{authed, spawn_app};
+
+/// Spans of the whole test binary, kept in memory instead of being sent to a
+/// collector. The subscriber is global: a per-test default would race with the
+/// callsite interest cache of the tests running on other threads.
+fn recorder() -> &'static (InMemorySpanExporter, SdkTracerProvider) {
+    static RECORDER: OnceLock<(InMemorySpanExporter, SdkTracerProvider)> = OnceLock::new();
+
+    RECORDER.get_or_init(|| {
+        global::set_text_map_propagator(TraceContextPropagator::new());
+        let exporter = InMemorySpanExporter::default();
+        let provider = SdkTracerProvider::builder()
+            .with_simple_exporter(exporter.clone())
+            .build();
+        let subscriber = tracing_subscriber::registry()
+            .with(tracing_opentelemetry::layer().with_tracer(provider.tracer("test")));
+        tracing::subscriber::set_global_default(subscriber).expect("no other global subscriber");
+
+        (exporter, provider)
+    })
+}
+
+/// Exported spans, once one of them matches `until`. A span is only exported
+/// when its last handle is dropped, which work spawned by the request may do
+/// after the response was sent.
+async fn finished_spans(until: impl Fn(&SpanData) -> bool) -> Vec<SpanData> {
+    let (exporter, provider) = recorder();
+    for _ in 0..50 {
+        provider.force_flush().unwrap();
+        let spans = exporter.get_finished_spans().unwrap();
+        if spans.iter().any(&until) {
+            return spans;
+        }
+        actix_web::rt::time::sleep(Duration::from_millis(100)).await;
+    }
+
+    exporter.get_finished_spans().unwrap()
+}
+
+fn attribute<'a>(span: &'a SpanData, key: &str) -> Option<&'a Value> {
+    span.attributes
+        .iter()
+        .find(|kv| kv.key.as_str() == key)
+        .map(|kv| &kv.value)
+}
+
+fn named<'a>(spans: &'a [SpanData], trace_id: TraceId, name: &str) -> &'a SpanData {
+    spans
+        .iter()
+        .find(|span| span.span_context.trace_id() == trace_id && span.name == name)
+        .unwrap_or_else(|| panic!("no span named {name} in trace {trace_id}"))
+}
+
+#[actix_web::test]
+async fn requests_continue_the_incoming_trace() {
+    recorder();
+    let app = spawn_app().await;
+    let user = app.user().await;
+    let trace_id = TraceId::from_bytes(*Uuid::new_v4().as_bytes());
+    let parent_id = SpanId::from_hex("00f067aa0ba902b7").unwrap();
+
+    app.call(
+        authed(test::TestRequest::get().uri("/api/posts"), &user.token)
+            .insert_header(("traceparent", format!("00-{trace_id}-{parent_id}-01"))),
+    )
+    .await;
+    let spans = finished_spans(|span| {
+        span.span_context.trace_id() == trace_id && span.name == "http_request"
+    })
+    .await;
+
+    let request = named(&spans, trace_id, "http_request");
+    assert_eq!(request.parent_span_id, parent_id);
+
+    let handler = named(&spans, trace_id, "list_posts");
+    let service = named(&spans, trace_id, "PostService::list");
+    let query = named(&spans, trace_id, "SqlitePostRepo::list");
+    assert_eq!(handler.parent_span_id, request.span_context.span_id());
+    assert_eq!(service.parent_span_id, handler.span_context.span_id());
+    assert_eq!(query.parent_span_id, service.span_context.span_id());
+    assert_eq!(
+        attribute(query, "db.system").map(Value::as_str).as_deref(),
+        Some("sqlite")
+    );
+}
+
+#[actix_web::test]
+async fn requests_without_traceparent_start_a_trace() {
+    recorder();
+    let app = spawn_app().await;
+    let request_id = Uuid::new_v4().to_string();
+
+    app.call(
+        test::TestRequest::get()
+            .uri("/health/live")
+            .insert_header(("x-request-id", request_id.as_str())),
+    )
+    .await;
+    let is_request = |span: &SpanData| {
+        span.name == "http_request"
+            && attribute(span, "request_id").map(Value::as_str).as_deref()
+                == Some(request_id.as_str())
+    };
+    let spans = finished_spans(is_request).await;
+
+    let request = spans
+        .iter()
+        .find(|span| is_request(span))
+        .expect("request span exported");
+    assert_ne!(request.span_context.trace_id(), TraceId::INVALID);
+    assert_eq!(request.parent_span_id, SpanId::INVALID);
+}
@@ -0,0 +1,572 @@
+use actix_web::{
+    http::{StatusCode, header},
+    test,
+};
+use api_back_trio::domain::model::{identity::ExternalIdentity, user::Role};
+use chrono::Utc;
+use serde_json::json;
+use uuid::Uuid;
+
+use crate::support::{PASSWORD, authed, multipart, png, spawn_app};
+
+#[actix_web::test]
+async fn admin_routes_require_a_token() {
+    let app = spawn_app().await;
+    let id = Uuid::new_v4();
+
+    let requests = [
+        test::TestRequest::get().uri("/api/users"),
+        test::TestRequest::post().uri("/api/users"),
+        test::TestRequest::get().uri(&format!("/api/users/{}", id)),
+        test::TestRequest::patch().uri(&format!("/api/users/{}", id)),
+        test::TestRequest::delete().uri(&format!("/api/users/{}", id)),
+        test::TestRequest::get().uri(&format!("/api/users/{}/export", id)),
+        test::TestRequest::post().uri(&format!("/api/users/{}/erase", id)),
+    ];
+
+    for req in requests {
+        let (status, body) = app.json(req).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+        assert_eq!(body["code"], "missing_credentials");
+    }
+
+    let (status, body) = app
+        .json(authed(
+            test::TestRequest::get().uri("/api/users"),
+            "not-a-jwt",
+        ))
+        .await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    assert_eq!(body["code"], "invalid_token");
+}
+
+#[actix_web::test]
+async fn admin_routes_are_forbidden_to_users() {
+    let app = spawn_app().await;
+    let user = app.user().await;
+
+    let (status, _) = app
+        .json(authed(
+            test::TestRequest::get().uri("/api/users"),
+            &user.token,
+        ))
+        .await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+
+    let (status, _) = app
+        .json(authed(
+            test::TestRequest::delete().uri(&format!("/api/users/{}", user.id)),
+            &user.token,
+        ))
+        .await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+}
+
+#[actix_web::test]
+async fn admin_lists_and_reads_users() {
+    let app = spawn_app().await;
+    let admin = app.admin().await;
+    let user = app.user().await;
+
+    let (status, list) = app
+        .json(authed(
+            test::TestRequest::get().uri("/api/users"),
+            &admin.token,
+        ))
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(list.as_array().unwrap().len(), 2);
+    assert!(list[0].get("password_hash").is_none());
+    assert!(list[0]["email"].is_string());
+
+    let (status, read) = app
+        .json(authed(
+            test::TestRequest::get().uri(&format!("/api/users/{}", user.id)),
+            &admin.token,
+        ))
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(read["username"], user.username);
+    assert_eq!(read["role"], "User");
+
+    let (status, _) = app
+        .json(authed(
+            test::TestRequest::get().uri(&format!("/api/users/{}", Uuid::new_v4())),
+            &admin.token,
+        ))
+        .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    let (status, _) = app
+        .json(authed(
+            test::TestRequest::get().uri("/api/users/not-a-uuid"),
+            &admin.token,
+        ))
+        .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
+
+#[actix_web::test]
+async fn admin_creates_users() {
+    let app = spawn_app().await;
+    let admin = app.admin().await;
+
+    let (status, created) = app
+        .json(
+            authed(test::TestRequest::post().uri("/api/users"), &admin.token).set_json(json!({
+                "username": "newcomer",
+                "password": PASSWORD,
+                "email": "newcomer@example.com",
+            })),
+        )
+        .await;
+    assert_eq!(status, StatusCode::CREATED);
+    assert_eq!(created["email"], "newcomer@example.com");
+    assert_eq!(created["role"], "User");
+
+    let (status, _) = app.login("newcomer", PASSWORD).await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (status, _) = app
+        .json(
+            authed(test::TestRequest::post().uri("/api/users"), &admin.token).set_json(json!({
+                "username": "newcomer",
+                "password": PASSWORD,
+                "email": "newcomer@example.com",
+            })),
+        )
+        .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    let (status, _) = app
+        .json(
+            authed(test::TestRequest::post().uri("/api/users"), &admin.token).set_json(json!({
+                "username": "weak",
+                "password": "weak",
+                "email": "weak@example.com",
+            })),
+        )
+        .await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+}
+
+#[actix_web::test]
+async fn admin_updates_users() {
+    let app = spawn_app().await;
+    let admin = app.admin().await;
+    let user = app.user().await;
+    let uri = format!("/api/users/{}", user.id);
+
+    let (status, updated) = app
+        .json(
+            authed(test::TestRequest::patch().uri(&uri), &admin.token)
+                .set_json(json!({ "role": "Admin", "email": "promoted@example.com" })),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(updated["role"], "Admin");
+    assert_eq!(updated["email"], "promoted@example.com");
+
+    let (status, _) = app
+        .json(
+            authed(test::TestRequest::patch().uri(&uri), &admin.token)
+                .set_json(json!({ "email": "not-an-email" })),
+        )
+        .await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+}
+
+#[actix_web::test]
+async fn admin_deletes_users() {
+    let app = spawn_app().await;
+    let admin = app.admin().await;
+    let user = app.user().await;
+    let uri = format!("/api/users/{}", user.id);
+
+    let (status, _) = app
+        .json(authed(test::TestRequest::delete().uri(&uri), &admin.token))
+        .await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+
+    let (status, _) = app
+        .json(authed(test::TestRequest::delete().uri(&uri), &admin.token))
+        .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+#[actix_web::test]
+async fn admin_exports_and_erases_users() {
+    let app = spawn_app().await;
+    let admin = app.admin().await;
+    let user = app.user().await;
+    app.create_post(&user, "Kept after erasure").await;
+
+    let resp = app
+        .call(authed(
+            test::TestRequest::get().uri(&format!("/api/users/{}/export", user.id)),
+            &admin.token,
+        ))
+        .await;
+    assert_eq!(resp.status(), StatusCode::OK);
+    assert_eq!(
+        resp.headers().get(header::CONTENT_TYPE).unwrap(),
+        "application/zip"
+    );
+    assert!(test::read_body(resp).await.starts_with(b"PK"));
+
+    let (status, _) = app
+        .json(authed(
+            test::TestRequest::post().uri(&format!("/api/users/{}/erase", user.id)),
+            &admin.token,
+        ))
+        .await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+
+    let (_, read) = app
+        .json(authed(
+            test::TestRequest::get().uri(&format!("/api/users/{}", user.id)),
+            &admin.token,
+        ))
+        .await;
+    assert_ne!(read["username"], user.username);
+    assert!(read["erasedAt"].is_string());
+
+    let (status, _) = app.login(&user.username, PASSWORD).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+}
+
+#[actix_web::test]
+async fn profile_routes_require_a_token() {
+    let app = spawn_app().await;
+
+    let requests = [
+        test::TestRequest::get().uri("/api/profile"),
+        test::TestRequest::patch().uri("/api/profile"),
+        test::TestRequest::get().uri("/api/profile/export"),
+        test::TestRequest::post().uri("/api/profile/erase"),
+        test::TestRequest::post().uri("/api/profile/avatar"),
+        test::TestRequest::delete().uri("/api/profile/avatar"),
+    ];
+
+    for req in requests {
+        let (status, _) = app.json(req).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+    }
+
+    let (status, _) = app
+        .json(authed(
+            test::TestRequest::get().uri("/api/profile"),
+            "not-a-jwt",
+        ))
+        .await;
+    // The `Claims` extractor answers 403 to a token it cannot decode.
+    assert_eq!(status, StatusCode::FORBIDDEN);
+}
+
+#[actix_web::test]
+async fn read_and_update_the_profile() {
+    let app = spawn_app().await;
+    let user = app.create_user("alice", Role::User).await;
+
+    let (status, profile) = app
+        .json(authed(
+            test::TestRequest::get().uri("/api/profile"),
+            &user.token,
+        ))
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(profile["email"], "alice@example.com");
+    assert!(profile.get("erasedAt").is_none());
+
+    let (status, updated) = app
+        .json(
+            authed(test::TestRequest::patch().uri("/api/profile"), &user.token).set_json(json!({
+                "display_name": "Alice",
+                "bio": "Writes about Rust",
+                "website": "https://alice.example.com",
+            })),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(updated["displayName"], "Alice");
+    assert_eq!(updated["bio"], "Writes about Rust");
+
+    let (status, _) = app
+        .json(
+            authed(test::TestRequest::patch().uri("/api/profile"), &user.token)
+                .set_json(json!({ "website": "not a url" })),
+        )
+        .await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+
+    for website in [
+        "javascript:alert(1)",
+        "data:text/html,<script>alert(1)</script>",
+    ] {
+        let (status, body) = app
+            .json(
+                authed(test::TestRequest::patch().uri("/api/profile"), &user.token)
+                    .set_json(json!({ "website": website })),
+            )
+            .await;
+        assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+        assert_eq!(body["errors"][0]["field"], "website");
+        assert_eq!(body["errors"][0]["code"], "url_scheme");
+    }
+
+    let (status, _) = app
+        .json(
+            authed(test::TestRequest::patch().uri("/api/profile"), &user.token)
+                .set_json(json!({ "username": "a".repeat(51) })),
+        )
+        .await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+
+    let (status, cleared) = app
+        .json(
+            authed(test::TestRequest::patch().uri("/api/profile"), &user.token)
+                .set_json(json!({ "bio": null, "website": null })),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(cleared["bio"].is_null());
+    assert!(cleared["website"].is_null());
+    assert_eq!(cleared["displayName"], "Alice");
+
+    let (status, _) = app
+        .json(
+            authed(test::TestRequest::patch().uri("/api/profile"), &user.token).set_json(json!({
+                "plain_password": "N3w$ecret",
+                "confirm_password": "Other$ecret1",
+            })),
+        )
+        .await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+}
+
+#[actix_web::test]
+async fn change_the_password_from_the_profile() {
+    let app = spawn_app().await;
+    let user = app.create_user("alice", Role::User).await;
+
+    let (status, _) = app
+        .json(
+            authed(test::TestRequest::patch().uri("/api/profile"), &user.token).set_json(json!({
+                "plain_password": "N3w$ecret",
+                "confirm_password": "N3w$ecret",
+            })),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+
+    assert_eq!(
+        app.login("alice", PASSWORD).await.0,
+        StatusCode::UNAUTHORIZED
+    );
+    assert_eq!(app.login("alice", "N3w$ecret").await.0, StatusCode::OK);
+}
+
+#[actix_web::test]
+async fn export_and_erase_the_profile() {
+    let app = spawn_app().await;
+    let user = app.user().await;
+
+    let resp = app
+        .call(authed(
+            test::TestRequest::get().uri("/api/profile/export"),
+            &user.token,
+        ))
+        .await;
+    assert_eq!(resp.status(), StatusCode::OK);
+    assert!(
+        resp.headers()
+            .get(header::CONTENT_DISPOSITION)
+            .unwrap()
+            .to_str()
+            .unwrap()
+            .contains("export-")
+    );
+
+    let (status, _) = app
+        .json(authed(
+            test::TestRequest::post().uri("/api/profile/erase"),
+            &user.token,
+        ))
+        .await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+
+    let (status, _) = app.login(&user.username, PASSWORD).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+}
+
+#[actix_web::test]
+async fn erasure_leaves_no_tokens_identities_or_media() {
+    let app = spawn_app().await;
+    let user = app.user().await;
+    app.create_access_token(&user, &["posts:read"]).await;
+    app.repos
+        .identities
+        .create(ExternalIdentity {
+            issuer: "https://idp.example".to_string(),
+            subject: "reader-sso".to_string(),
+            user_id: user.id,
+            email: Some("reader@example.com".to_string()),
+            created_at: Utc::now(),
+        })
+        .await
+        .unwrap();
+    let media = app.upload_media(&user).await;
+    let media_id = media["id"].as_str().unwrap();
+    let post = app.create_post(&user, "Illustrated").await;
+    let post_id = post["id"].as_str().unwrap();
+    let (status, _) = app
+        .json(authed(
+            test::TestRequest::put().uri(&format!("/api/posts/{}/media/{}", post_id, media_id)),
+            &user.token,
+        ))
+        .await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+    let key = app.repos.media.list_by_owner(user.id).await.unwrap()[0].storage_key();
+    assert!(app.stored(&key));
+
+    let (status, _) = app
+        .json(authed(
+            test::TestRequest::post().uri("/api/profile/erase"),
+            &user.token,
+        ))
+        .await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+
+    assert!(
+        app.repos
+            .access_tokens
+            .list_by_user(user.id)
+            .await
+            .unwrap()
+            .is_empty()
+    );
+    assert!(
+        app.repos
+            .identities
+            .list_by_user(user.id)
+            .await
+            .unwrap()
+            .is_empty()
+    );
+    assert!(
+        app.repos
+            .media
+            .list_by_owner(user.id)
+            .await
+            .unwrap()
+            .is_empty()
+    );
+    let post_id = Uuid::parse_str(post_id).unwrap();
+    assert!(
+        app.repos
+            .media
+            .list_for_post(post_id)
+            .await
+            .unwrap()
+            .is_empty()
+    );
+    assert!(!app.stored(&key));
+}
+
+#[actix_web::test]
+async fn deleting_a_user_removes_their_attached_media() {
+    let app = spawn_app().await;
+    let admin = app.admin().await;
+    let user = app.user().await;
+    let media = app.upload_media(&user).await;
+    let media_id = Uuid::parse_str(media["id"].as_str().unwrap()).unwrap();
+    let post = app.create_post(&admin, "Borrowed picture").await;
+    let post_id = Uuid::parse_str(post["id"].as_str().unwrap()).unwrap();
+    app.repos.media.attach(post_id, media_id).await.unwrap();
+    let key = app
+        .repos
+        .media
+        .find_by_id(media_id)
+        .await
+        .unwrap()
+        .unwrap()
+        .storage_key();
+
+    let (status, body) = app
+        .json(authed(
+            test::TestRequest::delete().uri(&format!("/api/users/{}", user.id)),
+            &admin.token,
+        ))
+        .await;
+    assert_eq!(status, StatusCode::NO_CONTENT, "{}", body);
+
+    assert!(app.repos.users.find_by_id(user.id).await.unwrap().is_none());
+    assert!(
+        app.repos
+            .media
+            .list_for_post(post_id)
+            .await
+            .unwrap()
+            .is_empty()
+    );
+    assert!(!app.stored(&key));
+    let (status, _) = app
+        .json(authed(
+            test::TestRequest::get().uri(&format!("/api/posts/{}", post_id)),
+            &admin.token,
+        ))
+        .await;
+    assert_eq!(status, StatusCode::OK);
+}
+
+#[actix_web::test]
+async fn upload_and_remove_the_avatar() {
+    let app = spawn_app().await;
+    let user = app.user().await;
+    let (content_type, body) = multipart("avatar", "me.png", "image/png", &png(300, 200));
+
+    let (status, profile) = app
+        .json(
+            authed(
+                test::TestRequest::post().uri("/api/profile/avatar"),
+                &user.token,
+            )
+            .insert_header((header::CONTENT_TYPE, content_type))
+            .set_payload(body),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    let avatar_url = profile["avatarUrl"].as_str().unwrap().to_string();
+    assert!(avatar_url.starts_with("/api/files/avatars/"));
+
+    let resp = app.call(test::TestRequest::get().uri(&avatar_url)).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+
+    let (status, profile) = app
+        .json(authed(
+            test::TestRequest::delete().uri("/api/profile/avatar"),
+            &user.token,
+        ))
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(profile["avatarUrl"].is_null());
+}
+
+#[actix_web::test]
+async fn avatar_must_be_an_image() {
+    let app = spawn_app().await;
+    let user = app.user().await;
+    let (content_type, body) = multipart("avatar", "notes.txt", "text/plain", b"hello");
+
+    let (status, _) = app
+        .json(
+            authed(
+                test::TestRequest::post().uri("/api/profile/avatar"),
+                &user.token,
+            )
+            .insert_header((header::CONTENT_TYPE, content_type))
+            .set_payload(body),
+        )
+        .await;
+    assert_eq!(status, StatusCode::UNSUPPORTED_MEDIA_TYPE);
+}
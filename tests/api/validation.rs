@@ -0,0 +1,122 @@
+use actix_web::{http::StatusCode, test};
+use serde_json::{Value, json};
+
+use crate::support::{authed, spawn_app};
+
+/// `(field, code)` of each entry of the `errors` member.
+fn fields(body: &Value) -> Vec<(String, String)> {
+    body["errors"]
+        .as_array()
+        .expect("errors member")
+        .iter()
+        .map(|e| {
+            assert!(e["message"].is_string());
+            (
+                e["field"].as_str().unwrap().to_string(),
+                e["code"].as_str().unwrap().to_string(),
+            )
+        })
+        .collect()
+}
+
+fn pair(field: &str, code: &str) -> (String, String) {
+    (field.to_string(), code.to_string())
+}
+
+#[actix_web::test]
+async fn every_invalid_field_is_listed() {
+    let app = spawn_app().await;
+    let user = app.user().await;
+
+    let (status, body) = app
+        .json(
+            authed(test::TestRequest::post().uri("/api/posts"), &user.token).set_json(json!({
+                "title": "x",
+                "content": "",
+                "published": false,
+                "user_id": user.id,
+            })),
+        )
+        .await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+    assert_eq!(body["code"], "validation_failed");
+    assert_eq!(
+        fields(&body),
+        vec![pair("content", "length"), pair("title", "length")]
+    );
+}
+
+#[actix_web::test]
+async fn password_rules_are_reported_one_by_one() {
+    let app = spawn_app().await;
+    let admin = app.admin().await;
+
+    let (status, body) = app
+        .json(
+            authed(test::TestRequest::post().uri("/api/users"), &admin.token).set_json(json!({
+                "username": "newcomer",
+                "password": "weakpass",
+                "email": "not-an-email",
+            })),
+        )
+        .await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+    assert_eq!(
+        fields(&body),
+        vec![
+            pair("email", "email"),
+            pair("password", "password_missing_uppercase"),
+            pair("password", "password_missing_digit"),
+            pair("password", "password_missing_special_char"),
+        ]
+    );
+}
+
+#[actix_web::test]
+async fn profile_passwords_must_match() {
+    let app = spawn_app().await;
+    let user = app.user().await;
+
+    let (status, body) = app
+        .json(
+            authed(test::TestRequest::patch().uri("/api/profile"), &user.token).set_json(json!({
+                "plain_password": "N3w$ecret",
+                "confirm_password": "Other$ecret1",
+            })),
+        )
+        .await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+    assert_eq!(
+        fields(&body),
+        vec![pair("confirm_password", "password_mismatch")]
+    );
+}
+
+#[actix_web::test]
+async fn deserialization_errors_name_the_field() {
+    let app = spawn_app().await;
+    let user = app.user().await;
+
+    let (status, body) = app
+        .json(
+            authed(test::TestRequest::post().uri("/api/posts"), &user.token).set_json(json!({
+                "title": "A title",
+                "content": "Some content",
+                "published": "yes",
+            })),
+        )
+        .await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+    assert_eq!(fields(&body), vec![pair("published", "invalid_type")]);
+
+    let (status, body) = app
+        .json(
+            authed(test::TestRequest::post().uri("/api/posts"), &user.token).set_json(json!({
+                "title": "A title",
+                "content": "Some content",
+            })),
+        )
+        .await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+    assert_eq!(fields(&body), vec![pair("published", "required")]);
+}
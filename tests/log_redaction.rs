@@ -0,0 +1,48 @@
+use api_back_trio::infrastructure::logging::redact::{REDACTED, redact_line};
+use serde_json::{Value, json};
+
+#[test]
+fn sensitive_json_fields_are_redacted() {
+    let line = json!({
+        "level": "INFO",
+        "fields": {
+            "message": "login",
+            "password": "Sup3r$ecret",
+            "Authorization": "Bearer abc.def.ghi",
+            "username": "alice",
+        },
+        "span": { "request_id": "r-1", "jwt_token": "abc" },
+    })
+    .to_string();
+
+    let redacted: Value = serde_json::from_str(&redact_line(&line)).unwrap();
+    assert_eq!(redacted["fields"]["password"], REDACTED);
+    assert_eq!(redacted["fields"]["Authorization"], REDACTED);
+    assert_eq!(redacted["fields"]["username"], "alice");
+    assert_eq!(redacted["span"]["jwt_token"], REDACTED);
+    assert_eq!(redacted["span"]["request_id"], "r-1");
+}
+
+#[test]
+fn secrets_inside_messages_are_redacted() {
+    let line = json!({
+        "fields": {
+            "message": r#"payload NewUser { username: Some("alice"), password: Some("Sup3r$ecret") } with Bearer abc.def token=xyz"#,
+        },
+    })
+    .to_string();
+
+    let redacted = redact_line(&line);
+    assert!(!redacted.contains("Sup3r$ecret"));
+    assert!(!redacted.contains("abc.def"));
+    assert!(!redacted.contains("xyz"));
+    assert!(redacted.contains(r#"username: Some(\"alice\")"#));
+}
+
+#[test]
+fn plain_text_lines_are_redacted_too() {
+    assert_eq!(
+        redact_line("connecting with password=hunter2 to db"),
+        format!("connecting with password={} to db", REDACTED)
+    );
+}
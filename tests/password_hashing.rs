@@ -0,0 +1,69 @@
+use api_back_trio::{config::Argon2Settings, infrastructure::auth::password::PasswordHashing};
+
+const PASSWORD: &str = "Sup3r$ecret";
+
+fn hashing(memory_kib: u32, pepper: Option<&str>) -> PasswordHashing {
+    PasswordHashing::new(&Argon2Settings {
+        memory_kib,
+        iterations: 1,
+        parallelism: 1,
+        pepper: pepper.map(str::to_string),
+    })
+    .expect("Argon2 settings")
+}
+
+#[test]
+fn hashes_follow_the_configured_parameters() {
+    let hash = hashing(2048, None).hash(PASSWORD).unwrap();
+
+    assert!(
+        hash.starts_with("$argon2id$v=19$m=2048,t=1,p=1$"),
+        "{}",
+        hash
+    );
+    assert!(hashing(2048, None).verify(PASSWORD, &hash).unwrap());
+    assert!(!hashing(2048, None).verify("Other$ecret1", &hash).unwrap());
+}
+
+#[test]
+fn hashes_with_other_parameters_need_a_rehash() {
+    let hash = hashing(1024, None).hash(PASSWORD).unwrap();
+
+    assert!(!hashing(1024, None).needs_rehash(&hash));
+    assert!(hashing(2048, None).needs_rehash(&hash));
+    assert!(hashing(1024, Some("pepper")).needs_rehash(&hash));
+    // Still verified with the parameters recorded in the hash.
+    assert!(
+        hashing(2048, Some("pepper"))
+            .verify(PASSWORD, &hash)
+            .unwrap()
+    );
+}
+
+#[test]
+fn peppered_hashes_need_the_pepper() {
+    let hash = hashing(1024, Some("pepper")).hash(PASSWORD).unwrap();
+
+    assert!(hash.contains("keyid="), "{}", hash);
+    assert!(
+        hashing(1024, Some("pepper"))
+            .verify(PASSWORD, &hash)
+            .unwrap()
+    );
+    assert!(
+        !hashing(1024, Some("other"))
+            .verify(PASSWORD, &hash)
+            .unwrap()
+    );
+    assert!(hashing(1024, None).verify(PASSWORD, &hash).is_err());
+}
+
+#[test]
+fn invalid_parameters_are_refused() {
+    let settings = Argon2Settings {
+        parallelism: 0,
+        ..Argon2Settings::default()
+    };
+
+    assert!(PasswordHashing::new(&settings).is_err());
+}
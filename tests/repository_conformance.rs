@@ -0,0 +1,815 @@
+//! Behaviour every repository backend must share. Each check runs against a
+//! fresh SQLite in-memory database, against the in-memory repositories and,
+//! when `TEST_POSTGRES_URL` is set, against a throwaway PostgreSQL database.
+
+use api_back_trio::{
+    domain::{
+        error::DomainError,
+        model::{
+            access_token::{AccessToken, Scope},
+            audit::{AuditAction, AuditEntry},
+            identity::ExternalIdentity,
+            media::Media,
+            post::Post,
+            user::{Role, User},
+        },
+    },
+    infrastructure::{
+        db::{Repositories, init_db},
+        persistence::memory::database::InMemoryDatabase,
+    },
+};
+use chrono::{DateTime, Duration, Utc};
+use sqlx::PgPool;
+use url::Url;
+use uuid::Uuid;
+
+/// Each backend gives the repositories of a fresh store, along with what
+/// removes that store once the check passed.
+async fn sqlite() -> Option<(Repositories, impl Future<Output = ()>)> {
+    let repos = init_db("sqlite::memory:")
+        .await
+        .expect("in-memory SQLite database")
+        .repositories();
+
+    Some((repos, async {}))
+}
+
+async fn memory() -> Option<(Repositories, impl Future<Output = ()>)> {
+    Some((InMemoryDatabase::new().repositories(), async {}))
+}
+
+/// A database created for the check on the server of `TEST_POSTGRES_URL`,
+/// whose role must be allowed to create databases; `None`, skipping the
+/// check, when the variable is not set. A failed check leaves its database
+/// behind for inspection.
+async fn postgres() -> Option<(Repositories, impl Future<Output = ()>)> {
+    let url = std::env::var("TEST_POSTGRES_URL").ok()?;
+    let server = PgPool::connect(&url)
+        .await
+        .expect("TEST_POSTGRES_URL server");
+    let name = format!("blog_conformance_{}", Uuid::new_v4().simple());
+    sqlx::query(&format!("CREATE DATABASE {name}"))
+        .execute(&server)
+        .await
+        .expect("scratch database");
+
+    let mut scratch = Url::parse(&url).expect("TEST_POSTGRES_URL");
+    scratch.set_path(&name);
+    let repos = init_db(scratch.as_str())
+        .await
+        .expect("migrated scratch database")
+        .repositories();
+    let drop = async move {
+        sqlx::query(&format!("DROP DATABASE {name} WITH (FORCE)"))
+            .execute(&server)
+            .await
+            .expect("scratch database dropped");
+    };
+
+    Some((repos, drop))
+}
+
+fn user(name: &str) -> User {
+    User {
+        id: Uuid::new_v4(),
+        username: name.to_string(),
+        password_hash: "hash".to_string(),
+        email: format!("{}@example.com", name),
+        first_name: None,
+        last_name: None,
+        display_name: None,
+        bio: None,
+        website: None,
+        avatar_key: None,
+        created_at: Utc::now(),
+        updated_at: None,
+        role: Role::User,
+        erased_at: None,
+        tokens_valid_after: None,
+    }
+}
+
+fn post(user_id: Uuid, title: &str, created_at: DateTime<Utc>) -> Post {
+    Post {
+        id: Uuid::new_v4(),
+        user_id,
+        title: title.to_string(),
+        content: "content".to_string(),
+        published: true,
+        created_at,
+        updated_at: None,
+        cover_media_id: None,
+    }
+}
+
+fn media(owner_id: Uuid, sha256: &str) -> Media {
+    Media {
+        id: Uuid::new_v4(),
+        owner_id,
+        sha256: sha256.to_string(),
+        mime_type: "image/png".to_string(),
+        size_bytes: 42,
+        original_name: Some("image.png".to_string()),
+        created_at: Utc::now(),
+    }
+}
+
+fn access_token(user_id: Uuid, hash: &str, created_at: DateTime<Utc>) -> AccessToken {
+    AccessToken {
+        id: Uuid::new_v4(),
+        user_id,
+        name: "ci".to_string(),
+        prefix: "blog_pat_abcdefgh".to_string(),
+        token_hash: hash.to_string(),
+        scopes: vec![Scope::PostsRead, Scope::PostsWrite],
+        created_at,
+        expires_at: created_at + Duration::days(30),
+        last_used_at: None,
+        revoked_at: None,
+    }
+}
+
+fn identity(user_id: Uuid, subject: &str, created_at: DateTime<Utc>) -> ExternalIdentity {
+    ExternalIdentity {
+        issuer: "https://idp.example".to_string(),
+        subject: subject.to_string(),
+        user_id,
+        email: Some("sso@example.com".to_string()),
+        created_at,
+    }
+}
+
+fn audit_entry(
+    action: AuditAction,
+    actor_id: Uuid,
+    session_id: Uuid,
+    created_at: DateTime<Utc>,
+) -> AuditEntry {
+    AuditEntry {
+        id: Uuid::new_v4(),
+        action,
+        actor_id,
+        subject_id: Some(Uuid::new_v4()),
+        session_id: Some(session_id),
+        created_at,
+    }
+}
+
+mod checks {
+    use super::*;
+
+    pub async fn create_and_find_user(repos: Repositories) {
+        let created = repos.users.create(user("alice")).await.unwrap();
+
+        let by_id = repos.users.find_by_id(created.id).await.unwrap().unwrap();
+        assert_eq!(by_id.username, "alice");
+        assert_eq!(by_id.email, "alice@example.com");
+        assert!(matches!(by_id.role, Role::User));
+
+        let by_name = repos.users.find_by_username("alice").await.unwrap();
+        assert_eq!(by_name.map(|u| u.id), Some(created.id));
+        assert!(
+            repos
+                .users
+                .find_by_username("nobody")
+                .await
+                .unwrap()
+                .is_none()
+        );
+        assert!(
+            repos
+                .users
+                .find_by_id(Uuid::new_v4())
+                .await
+                .unwrap()
+                .is_none()
+        );
+    }
+
+    pub async fn duplicate_username_is_rejected(repos: Repositories) {
+        repos.users.create(user("alice")).await.unwrap();
+
+        let mut twin = user("alice");
+        twin.email = "other@example.com".to_string();
+
+        assert!(matches!(
+            repos.users.create(twin).await,
+            Err(DomainError::DuplicateEmail)
+        ));
+    }
+
+    pub async fn duplicate_email_is_rejected(repos: Repositories) {
+        repos.users.create(user("alice")).await.unwrap();
+
+        let mut twin = user("bob");
+        twin.email = "alice@example.com".to_string();
+
+        assert!(matches!(
+            repos.users.create(twin).await,
+            Err(DomainError::DuplicateEmail)
+        ));
+    }
+
+    pub async fn update_user(repos: Repositories) {
+        let alice = repos.users.create(user("alice")).await.unwrap();
+        repos.users.create(user("bob")).await.unwrap();
+
+        let updated = repos
+            .users
+            .update(User {
+                bio: Some("Hello".to_string()),
+                role: Role::Admin,
+                ..alice.clone()
+            })
+            .await
+            .unwrap();
+        assert_eq!(updated.bio.as_deref(), Some("Hello"));
+        assert!(matches!(updated.role, Role::Admin));
+        assert!(updated.updated_at.is_some());
+
+        let taken = repos
+            .users
+            .update(User {
+                email: "bob@example.com".to_string(),
+                ..alice
+            })
+            .await;
+        assert!(matches!(taken, Err(DomainError::DuplicateEmail)));
+    }
+
+    pub async fn list_users_newest_first(repos: Repositories) {
+        let now = Utc::now();
+        let mut old = user("old");
+        old.created_at = now - Duration::days(1);
+        let mut new = user("new");
+        new.created_at = now;
+        repos.users.create(old).await.unwrap();
+        repos.users.create(new).await.unwrap();
+
+        let names: Vec<String> = repos
+            .users
+            .list()
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|u| u.username)
+            .collect();
+        assert_eq!(names, ["new", "old"]);
+    }
+
+    pub async fn count_users(repos: Repositories) {
+        assert_eq!(repos.users.count().await.unwrap(), 0);
+
+        let alice = repos.users.create(user("alice")).await.unwrap();
+        repos.users.create(user("bob")).await.unwrap();
+        assert_eq!(repos.users.count().await.unwrap(), 2);
+
+        repos.users.delete(alice.id).await.unwrap();
+        assert_eq!(repos.users.count().await.unwrap(), 1);
+    }
+
+    pub async fn delete_user(repos: Repositories) {
+        let alice = repos.users.create(user("alice")).await.unwrap();
+
+        repos.users.delete(alice.id).await.unwrap();
+        assert!(repos.users.find_by_id(alice.id).await.unwrap().is_none());
+        assert!(matches!(
+            repos.users.delete(alice.id).await,
+            Err(DomainError::NotFound)
+        ));
+    }
+
+    pub async fn delete_user_detaches_their_media(repos: Repositories) {
+        let alice = repos.users.create(user("alice")).await.unwrap();
+        let bob = repos.users.create(user("bob")).await.unwrap();
+        let image = repos.media.create(media(alice.id, "ab")).await.unwrap();
+        let mut bobs = repos
+            .posts
+            .create(post(bob.id, "Post", Utc::now()))
+            .await
+            .unwrap();
+        repos.media.attach(bobs.id, image.id).await.unwrap();
+        bobs.cover_media_id = Some(image.id);
+        repos.posts.update(bobs.clone()).await.unwrap();
+
+        repos.users.delete(alice.id).await.unwrap();
+
+        assert!(repos.media.find_by_id(image.id).await.unwrap().is_none());
+        assert!(repos.media.list_for_post(bobs.id).await.unwrap().is_empty());
+        let kept = repos.posts.find_by_id(bobs.id).await.unwrap().unwrap();
+        assert!(kept.cover_media_id.is_none());
+    }
+
+    pub async fn delete_author_is_refused(repos: Repositories) {
+        let alice = repos.users.create(user("alice")).await.unwrap();
+        repos
+            .posts
+            .create(post(alice.id, "Post", Utc::now()))
+            .await
+            .unwrap();
+
+        assert!(repos.users.delete(alice.id).await.is_err());
+        assert!(repos.users.find_by_id(alice.id).await.unwrap().is_some());
+    }
+
+    pub async fn anonymize_user(repos: Repositories) {
+        let mut alice = user("alice");
+        alice.bio = Some("Bio".to_string());
+        let alice = repos.users.create(alice).await.unwrap();
+        let now = Utc::now();
+        repos
+            .access_tokens
+            .create(access_token(alice.id, "h1", now))
+            .await
+            .unwrap();
+        repos
+            .identities
+            .create(identity(alice.id, "a-1", now))
+            .await
+            .unwrap();
+        let image = repos.media.create(media(alice.id, "aa")).await.unwrap();
+        let mut illustrated = repos
+            .posts
+            .create(post(alice.id, "Illustrated", now))
+            .await
+            .unwrap();
+        repos.media.attach(illustrated.id, image.id).await.unwrap();
+        illustrated.cover_media_id = Some(image.id);
+        repos.posts.update(illustrated.clone()).await.unwrap();
+
+        let erased = repos
+            .users
+            .anonymize(alice.id, "deleted-user", "x@erased.invalid", now)
+            .await
+            .unwrap();
+        assert_eq!(erased.username, "deleted-user");
+        assert_eq!(erased.email, "x@erased.invalid");
+        assert!(erased.bio.is_none());
+        assert!(erased.erased_at.is_some());
+
+        assert!(
+            repos
+                .access_tokens
+                .list_by_user(alice.id)
+                .await
+                .unwrap()
+                .is_empty()
+        );
+        assert!(
+            repos
+                .identities
+                .list_by_user(alice.id)
+                .await
+                .unwrap()
+                .is_empty()
+        );
+        assert!(
+            repos
+                .media
+                .list_by_owner(alice.id)
+                .await
+                .unwrap()
+                .is_empty()
+        );
+        assert!(
+            repos
+                .media
+                .list_for_post(illustrated.id)
+                .await
+                .unwrap()
+                .is_empty()
+        );
+        let kept = repos
+            .posts
+            .find_by_id(illustrated.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(kept.cover_media_id.is_none());
+
+        assert!(matches!(
+            repos
+                .users
+                .anonymize(Uuid::new_v4(), "p", "p@erased.invalid", now)
+                .await,
+            Err(DomainError::NotFound)
+        ));
+    }
+
+    pub async fn create_and_list_posts(repos: Repositories) {
+        let alice = repos.users.create(user("alice")).await.unwrap();
+        let bob = repos.users.create(user("bob")).await.unwrap();
+        let now = Utc::now();
+
+        let first = repos
+            .posts
+            .create(post(alice.id, "First", now - Duration::hours(1)))
+            .await
+            .unwrap();
+        repos
+            .posts
+            .create(post(alice.id, "Second", now))
+            .await
+            .unwrap();
+        repos
+            .posts
+            .create(post(bob.id, "Bob", now - Duration::hours(2)))
+            .await
+            .unwrap();
+
+        let titles: Vec<String> = repos
+            .posts
+            .list()
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|p| p.title)
+            .collect();
+        assert_eq!(titles, ["Second", "First", "Bob"]);
+
+        let found = repos.posts.find_by_id(first.id).await.unwrap().unwrap();
+        assert_eq!(found.title, "First");
+        assert_eq!(found.author.id, alice.id);
+        assert_eq!(found.author.username, "alice");
+        assert!(
+            repos
+                .posts
+                .find_by_id(Uuid::new_v4())
+                .await
+                .unwrap()
+                .is_none()
+        );
+
+        let by_alice = repos.posts.list_by_author(alice.id).await.unwrap();
+        assert_eq!(by_alice.len(), 2);
+        assert_eq!(by_alice[0].title, "Second");
+    }
+
+    pub async fn update_post(repos: Repositories) {
+        let alice = repos.users.create(user("alice")).await.unwrap();
+        let created = repos
+            .posts
+            .create(post(alice.id, "Draft", Utc::now()))
+            .await
+            .unwrap();
+
+        let updated = repos
+            .posts
+            .update(Post {
+                title: "Final".to_string(),
+                ..created.clone()
+            })
+            .await
+            .unwrap();
+        assert!(updated.updated_at.is_some());
+
+        let found = repos.posts.find_by_id(created.id).await.unwrap().unwrap();
+        assert_eq!(found.title, "Final");
+        assert!(found.updated_at.is_some());
+    }
+
+    pub async fn delete_post(repos: Repositories) {
+        let alice = repos.users.create(user("alice")).await.unwrap();
+        let created = repos
+            .posts
+            .create(post(alice.id, "Post", Utc::now()))
+            .await
+            .unwrap();
+
+        repos.posts.delete(created.id).await.unwrap();
+        assert!(repos.posts.find_by_id(created.id).await.unwrap().is_none());
+        assert!(matches!(
+            repos.posts.delete(created.id).await,
+            Err(DomainError::NotFound)
+        ));
+    }
+
+    pub async fn media_library(repos: Repositories) {
+        let alice = repos.users.create(user("alice")).await.unwrap();
+        let bob = repos.users.create(user("bob")).await.unwrap();
+        let sha = "ab".repeat(32);
+
+        let first = repos.media.create(media(alice.id, &sha)).await.unwrap();
+        repos.media.create(media(bob.id, &sha)).await.unwrap();
+        assert!(repos.media.create(media(alice.id, &sha)).await.is_err());
+
+        assert_eq!(repos.media.count_by_hash(&sha).await.unwrap(), 2);
+        let found = repos
+            .media
+            .find_by_owner_and_hash(alice.id, &sha)
+            .await
+            .unwrap();
+        assert_eq!(found.map(|m| m.id), Some(first.id));
+        assert_eq!(repos.media.list_by_owner(alice.id).await.unwrap().len(), 1);
+    }
+
+    pub async fn post_attachments(repos: Repositories) {
+        let alice = repos.users.create(user("alice")).await.unwrap();
+        let created = repos
+            .posts
+            .create(post(alice.id, "Post", Utc::now()))
+            .await
+            .unwrap();
+        let one = repos
+            .media
+            .create(media(alice.id, &"01".repeat(32)))
+            .await
+            .unwrap();
+        let two = repos
+            .media
+            .create(media(alice.id, &"02".repeat(32)))
+            .await
+            .unwrap();
+
+        repos.media.attach(created.id, two.id).await.unwrap();
+        repos.media.attach(created.id, one.id).await.unwrap();
+        repos.media.attach(created.id, two.id).await.unwrap();
+
+        let attached: Vec<Uuid> = repos
+            .media
+            .list_for_post(created.id)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|m| m.id)
+            .collect();
+        assert_eq!(attached, [two.id, one.id]);
+
+        assert!(repos.media.is_referenced(two.id).await.unwrap());
+        assert!(repos.media.delete(two.id).await.is_err());
+
+        repos.media.detach(created.id, two.id).await.unwrap();
+        assert!(!repos.media.is_referenced(two.id).await.unwrap());
+        assert!(matches!(
+            repos.media.detach(created.id, two.id).await,
+            Err(DomainError::NotFound)
+        ));
+        repos.media.delete(two.id).await.unwrap();
+        assert!(matches!(
+            repos.media.delete(two.id).await,
+            Err(DomainError::NotFound)
+        ));
+    }
+
+    pub async fn post_cover(repos: Repositories) {
+        let alice = repos.users.create(user("alice")).await.unwrap();
+        let cover = repos
+            .media
+            .create(media(alice.id, &"cd".repeat(32)))
+            .await
+            .unwrap();
+        let created = repos
+            .posts
+            .create(Post {
+                cover_media_id: Some(cover.id),
+                ..post(alice.id, "Post", Utc::now())
+            })
+            .await
+            .unwrap();
+
+        let found = repos.posts.find_by_id(created.id).await.unwrap().unwrap();
+        assert_eq!(found.cover_key, Some(cover.storage_key()));
+        assert!(repos.media.is_referenced(cover.id).await.unwrap());
+
+        repos.media.delete(cover.id).await.unwrap();
+        let found = repos.posts.find_by_id(created.id).await.unwrap().unwrap();
+        assert!(found.cover_media_id.is_none());
+        assert!(found.cover_key.is_none());
+    }
+
+    pub async fn access_tokens(repos: Repositories) {
+        let alice = repos.users.create(user("alice")).await.unwrap();
+        let bob = repos.users.create(user("bob")).await.unwrap();
+        let now = Utc::now();
+        let (older, newer) = ("1".repeat(64), "2".repeat(64));
+
+        let first = repos
+            .access_tokens
+            .create(access_token(alice.id, &older, now - Duration::hours(1)))
+            .await
+            .unwrap();
+        repos
+            .access_tokens
+            .create(access_token(alice.id, &newer, now))
+            .await
+            .unwrap();
+        assert!(
+            repos
+                .access_tokens
+                .create(access_token(bob.id, &older, now))
+                .await
+                .is_err()
+        );
+
+        let found = repos
+            .access_tokens
+            .find_by_hash(&older)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(found.id, first.id);
+        assert_eq!(found.scopes, vec![Scope::PostsRead, Scope::PostsWrite]);
+        let listed = repos.access_tokens.list_by_user(alice.id).await.unwrap();
+        assert_eq!(
+            listed.iter().map(|t| &t.token_hash).collect::<Vec<_>>(),
+            vec![&newer, &older]
+        );
+
+        repos.access_tokens.touch(first.id, now).await.unwrap();
+        assert!(matches!(
+            repos.access_tokens.revoke(first.id, bob.id, now).await,
+            Err(DomainError::NotFound)
+        ));
+        let revoked = repos
+            .access_tokens
+            .revoke(first.id, alice.id, now)
+            .await
+            .unwrap();
+        let again = repos
+            .access_tokens
+            .revoke(first.id, alice.id, now + Duration::hours(1))
+            .await
+            .unwrap();
+        assert!(revoked.last_used_at.is_some());
+        assert_eq!(again.revoked_at, revoked.revoked_at);
+
+        repos.users.delete(alice.id).await.unwrap();
+        assert!(
+            repos
+                .access_tokens
+                .find_by_hash(&newer)
+                .await
+                .unwrap()
+                .is_none()
+        );
+    }
+
+    pub async fn external_identities(repos: Repositories) {
+        let alice = repos.users.create(user("alice")).await.unwrap();
+        let bob = repos.users.create(user("bob")).await.unwrap();
+        let now = Utc::now();
+
+        let by_email = repos
+            .users
+            .find_by_email("Alice@Example.com")
+            .await
+            .unwrap();
+        assert_eq!(by_email.map(|u| u.id), Some(alice.id));
+        assert!(
+            repos
+                .users
+                .find_by_email("nobody@example.com")
+                .await
+                .unwrap()
+                .is_none()
+        );
+
+        repos
+            .identities
+            .create(identity(alice.id, "a-2", now))
+            .await
+            .unwrap();
+        repos
+            .identities
+            .create(identity(alice.id, "a-1", now - Duration::hours(1)))
+            .await
+            .unwrap();
+        assert!(
+            repos
+                .identities
+                .create(identity(bob.id, "a-1", now))
+                .await
+                .is_err()
+        );
+
+        let found = repos
+            .identities
+            .find("https://idp.example", "a-1")
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(found.user_id, alice.id);
+        assert_eq!(found.email.as_deref(), Some("sso@example.com"));
+        assert!(
+            repos
+                .identities
+                .find("https://other.example", "a-1")
+                .await
+                .unwrap()
+                .is_none()
+        );
+        let listed = repos.identities.list_by_user(alice.id).await.unwrap();
+        assert_eq!(
+            listed
+                .iter()
+                .map(|i| i.subject.as_str())
+                .collect::<Vec<_>>(),
+            vec!["a-1", "a-2"]
+        );
+
+        repos.users.delete(alice.id).await.unwrap();
+        assert!(
+            repos
+                .identities
+                .find("https://idp.example", "a-2")
+                .await
+                .unwrap()
+                .is_none()
+        );
+    }
+
+    pub async fn audit_log(repos: Repositories) {
+        let admin = repos.users.create(user("admin")).await.unwrap();
+        let (first, second) = (Uuid::new_v4(), Uuid::new_v4());
+        let now = Utc::now();
+
+        for entry in [
+            audit_entry(
+                AuditAction::ImpersonationStarted,
+                admin.id,
+                first,
+                now - Duration::minutes(3),
+            ),
+            audit_entry(
+                AuditAction::ImpersonationEnded,
+                admin.id,
+                first,
+                now - Duration::minutes(2),
+            ),
+            audit_entry(
+                AuditAction::ImpersonationStarted,
+                admin.id,
+                second,
+                now - Duration::minutes(1),
+            ),
+        ] {
+            repos.audit_log.record(entry).await.unwrap();
+        }
+
+        let latest = repos.audit_log.list(2).await.unwrap();
+        assert_eq!(latest.len(), 2);
+        assert_eq!(latest[0].session_id, Some(second));
+        assert_eq!(latest[1].action, AuditAction::ImpersonationEnded);
+
+        let session = repos.audit_log.list_by_session(first).await.unwrap();
+        assert_eq!(
+            session.iter().map(|e| e.action).collect::<Vec<_>>(),
+            vec![
+                AuditAction::ImpersonationStarted,
+                AuditAction::ImpersonationEnded
+            ]
+        );
+
+        // The log outlives the accounts it mentions.
+        repos.users.delete(admin.id).await.unwrap();
+        assert_eq!(repos.audit_log.list(10).await.unwrap().len(), 3);
+    }
+}
+
+macro_rules! conformance {
+    ($backend:ident: $($check:ident),* $(,)?) => {
+        mod $backend {
+            $(
+                #[actix_web::test]
+                async fn $check() {
+                    let Some((repos, teardown)) = super::$backend().await else {
+                        return;
+                    };
+                    super::checks::$check(repos).await;
+                    teardown.await;
+                }
+            )*
+        }
+    };
+}
+
+macro_rules! backends {
+    ($($backend:ident),*) => {
+        $(
+            conformance!($backend:
+                create_and_find_user,
+                duplicate_username_is_rejected,
+                duplicate_email_is_rejected,
+                update_user,
+                list_users_newest_first,
+                count_users,
+                delete_user,
+                delete_user_detaches_their_media,
+                delete_author_is_refused,
+                anonymize_user,
+                create_and_list_posts,
+                update_post,
+                delete_post,
+                media_library,
+                post_attachments,
+                post_cover,
+                access_tokens,
+                external_identities,
+                audit_log,
+            );
+        )*
+    };
+}
+
+backends!(sqlite, memory, postgres);